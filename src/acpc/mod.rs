@@ -0,0 +1,213 @@
+use game::card::Card;
+use game::player::{Player, Command};
+use game::table::Table;
+use game::event::Event;
+use game::betting::{BettingStructure, LegalActions};
+use bot::raise_to;
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+pub mod server;
+
+// the first line every client sends, of which only the major version counts
+pub const VERSION: &str = "VERSION:2.0.0";
+
+// a game of the Annual Computer Poker Competition: heads up or three handed,
+// limit or no-limit, with everyone's stack reset every hand
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameDef {
+    pub players: usize,
+    pub betting: BettingStructure,
+    pub small_blind: i32,
+    pub big_blind: i32,
+    pub stack: i32,
+}
+
+impl GameDef {
+    // limit is played 5/10 and no-limit 50/100 with 200 big blinds, as in
+    // the competition. Limit stacks never run out
+    pub fn new(players: usize, betting: BettingStructure) -> GameDef {
+        let (small_blind, big_blind) = match betting {
+            BettingStructure::FixedLimit => (5, 10),
+            _ => (50, 100),
+        };
+        GameDef { players, betting, small_blind, big_blind, stack: 20000 }
+    }
+
+    // like holdem.nolimit.2p.reverse_blinds
+    pub fn name(&self) -> String {
+        let betting = match self.betting {
+            BettingStructure::FixedLimit => "limit",
+            BettingStructure::PotLimit => "potlimit",
+            BettingStructure::NoLimit => "nolimit",
+        };
+        let blinds = if self.players == 2 { ".reverse_blinds" } else { "" };
+        format!("holdem.{}.{}p{}", betting, self.players, blinds)
+    }
+
+    // a table for one hand, with a seat for each position. The last position
+    // has the button, so heads up position 0 is the big blind, and three
+    // handed the small blind
+    pub fn table(&self, seed: u64) -> Table {
+        let mut table = Table::build_table();
+        table.set_blinds(self.small_blind, self.big_blind);
+        table.set_betting_structure(self.betting);
+        table.set_seed(seed);
+        for position in 0..self.players {
+            table.add_player(Player::with_chips(seat_name(position), false, self.stack));
+        }
+        table.set_button(self.players - 1);
+        table
+    }
+}
+
+// the name of the table's player in `position`
+pub fn seat_name(position: usize) -> String {
+    format!("p{}", position)
+}
+
+fn position_of(name: &str) -> Option<usize> {
+    name.strip_prefix('p')?.parse().ok()
+}
+
+// the betting in `events` with f, c and r for each action and rounds split by
+// /. No-limit raises are followed by what the raiser has put in over the hand
+pub fn betting(events: &[Event], structure: BettingStructure) -> String {
+    let mut betting = String::new();
+    let mut put_in: HashMap<&str, i32> = HashMap::new();
+    for event in events {
+        match *event {
+            Event::BlindPosted { ref name, amount }
+            | Event::DeadBlindPosted { ref name, amount } => {
+                *put_in.entry(name).or_insert(0) += amount;
+            },
+            Event::StreetDealt { .. } => betting.push('/'),
+            Event::Action { ref name, ref command, amount } => {
+                let total = put_in.entry(name).or_insert(0);
+                *total += amount;
+                match *command {
+                    Command::Fold | Command::Leave => betting.push('f'),
+                    Command::Raise(_) if structure == BettingStructure::FixedLimit => {
+                        betting.push('r')
+                    },
+                    Command::Raise(_) => betting.push_str(&format!("r{}", total)),
+                    _ => betting.push('c'),
+                }
+            },
+            _ => {},
+        }
+    }
+    betting
+}
+
+// everyone's hole cards split by |, then the board a round at a time. Only
+// `viewer`'s cards and those shown down are visible, or all of them without
+// a viewer
+pub fn cards(events: &[Event], players: usize, viewer: Option<usize>) -> String {
+    let short = |cards: &[Rc<Card>]| cards.iter().map(|card| card.short()).collect::<String>();
+    let mut holes = vec![String::new(); players];
+    let mut shown = HashSet::new();
+    let mut board: &[Rc<Card>] = &[];
+    for event in events {
+        match *event {
+            Event::HoleCards { ref name, ref cards } => {
+                if let Some(hole) = position_of(name).and_then(|p| holes.get_mut(p)) {
+                    *hole = short(&[cards.0.clone(), cards.1.clone()]);
+                }
+            },
+            Event::Showdown { ref name, .. } => {
+                shown.insert(position_of(name));
+            },
+            Event::StreetDealt { ref cards, .. } => board = cards,
+            _ => {},
+        }
+    }
+    for (position, hole) in holes.iter_mut().enumerate() {
+        let visible = viewer.is_none_or(|viewer| viewer == position)
+            || shown.contains(&Some(position));
+        if !visible {
+            hole.clear();
+        }
+    }
+    let mut cards = holes.join("|");
+    for round in [&board[..board.len().min(3)], board.get(3..4).unwrap_or(&[]),
+                  board.get(4..5).unwrap_or(&[])].iter() {
+        if !round.is_empty() {
+            cards.push('/');
+            cards.push_str(&short(round));
+        }
+    }
+    cards
+}
+
+// what the client in `position` is sent about hand `hand`
+pub fn match_state(events: &[Event], game: &GameDef, position: usize, hand: u64) -> String {
+    format!("MATCHSTATE:{}:{}:{}:{}", position, hand, betting(events, game.betting),
+            cards(events, game.players, Some(position)))
+}
+
+// the command for a client's action of f, c, r or r<size>, where the size is
+// what it will have put in over the hand after raising. `put_in` is what it
+// put in on earlier rounds. Raises are moved into the legal range and
+// anything else calls, as the competition's dealer does
+pub fn parse_action(action: &str, legal: &LegalActions, put_in: i32) -> Command {
+    let action = action.trim();
+    if action.starts_with('f') {
+        return Command::Fold;
+    }
+    if let Some(size) = action.strip_prefix('r') {
+        let to = match size.parse::<i32>() {
+            Ok(size) => size - put_in,
+            Err(_) => legal.raise_to.map_or(0, |(min, _)| min),
+        };
+        if let Some(command) = raise_to(legal, to) {
+            return command;
+        }
+    }
+    legal.check_or_call()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::event::{EventLog, View};
+    use game::testing::cards;
+
+    use std::cell::RefCell;
+
+    #[test]
+    fn match_states_show_the_betting_and_visible_cards() {
+        let game = GameDef::new(2, BettingStructure::NoLimit);
+        let mut table = game.table(1);
+        table.stack_deck(cards("As Ks Qh Qd 2c 7d 9h Th 3s"));
+        let log = Rc::new(RefCell::new(EventLog::default()));
+        table.add_observer(View::Omniscient, log.clone());
+        table.start_hand().unwrap();
+        // the button raises to 300 and the big blind calls and checks the flop
+        table.act(1, Command::Raise(200)).unwrap();
+        table.act(0, Command::Call).unwrap();
+        table.act(0, Command::Check).unwrap();
+
+        let events = &log.borrow().events;
+        assert_eq!(match_state(events, &game, 0, 7), "MATCHSTATE:0:7:r300c/c:QhQd|/2c7d9h");
+        assert_eq!(match_state(events, &game, 1, 7), "MATCHSTATE:1:7:r300c/c:|AsKs/2c7d9h");
+        assert_eq!(betting(events, BettingStructure::FixedLimit), "rc/c");
+    }
+
+    #[test]
+    fn parses_actions_into_legal_commands() {
+        let game = GameDef::new(2, BettingStructure::NoLimit);
+        let mut table = game.table(1);
+        table.start_hand().unwrap();
+        // the button has put in its small blind of 50 and faces 100
+        let legal = table.legal_actions(1).unwrap();
+        assert_eq!(parse_action("f", &legal, 0), Command::Fold);
+        assert_eq!(parse_action("c\r\n", &legal, 0), Command::Call);
+        assert_eq!(Some(parse_action("r300", &legal, 0)), raise_to(&legal, 300));
+        // too small or too big a raise is moved into range
+        assert_eq!(Some(parse_action("r120", &legal, 0)), raise_to(&legal, 200));
+        assert_eq!(Some(parse_action("r99999", &legal, 0)), raise_to(&legal, 20000));
+        assert_eq!(parse_action("x", &legal, 0), Command::Call);
+    }
+}
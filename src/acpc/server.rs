@@ -0,0 +1,167 @@
+use game::player::Command;
+use game::event::{EventLog, View};
+use super::{GameDef, VERSION, match_state, betting, cards, parse_action};
+
+use std::cell::RefCell;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::rc::Rc;
+use std::time::Duration;
+
+// a research bot connected over TCP
+pub struct Client {
+    pub name: String,
+    reader: BufReader<TcpStream>,
+    stream: TcpStream,
+    connected: bool,
+}
+
+impl Client {
+    // waits for a client on `listener` and checks it speaks the same major
+    // version. It gets `timeout` for each action
+    pub fn accept(listener: &TcpListener, name: &str, timeout: Duration) -> io::Result<Client> {
+        let (stream, _) = listener.accept()?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut version = String::new();
+        reader.read_line(&mut version)?;
+        let major = |version: &str| version.trim().split('.').next().map(|v| v.to_string());
+        if major(&version) != major(VERSION) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      format!("{} sent {:?}, not {}", name, version.trim(),
+                                              VERSION)));
+        }
+        stream.set_read_timeout(Some(timeout))?;
+        // states are short and answered right away
+        stream.set_nodelay(true)?;
+        Ok(Client { name: name.to_string(), reader, stream, connected: true })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    fn send(&mut self, state: &str) {
+        let line = format!("{}\r\n", state);
+        if self.connected && self.stream.write_all(line.as_bytes()).is_err() {
+            self.connected = false;
+        }
+    }
+
+    // the action the client answers `state` with. Comment lines starting with
+    // # or ; and answers to other states are skipped
+    fn answer(&mut self, state: &str) -> Option<String> {
+        while self.connected {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => self.connected = false,
+                Ok(_) => {
+                    let line = line.trim_end();
+                    let action = line.strip_prefix(state).and_then(|a| a.strip_prefix(':'));
+                    if let Some(action) = action {
+                        return Some(action.to_string());
+                    }
+                },
+                // out of time
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock
+                    || err.kind() == io::ErrorKind::TimedOut => return None,
+                Err(_) => self.connected = false,
+            }
+        }
+        None
+    }
+}
+
+// a match of `hands` hands between clients who move round a position every
+// hand. Each hand is dealt from the seed and its number, so a match played
+// again with the same seed deals the same cards to the same positions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub game: GameDef,
+    pub hands: u64,
+    pub seed: u64,
+    duplicate: bool,
+}
+
+impl Match {
+    pub fn new(game: GameDef, hands: u64, seed: u64) -> Match {
+        Match { game, hands, seed, duplicate: false }
+    }
+
+    // plays the match once for each position, starting the clients a
+    // position further round each time, so every client is dealt every
+    // position's cards
+    pub fn duplicate(mut self) -> Match {
+        self.duplicate = true;
+        self
+    }
+
+    // plays every hand, logging each in the competition's format, and
+    // returns what each client won
+    pub fn play<W: Write>(&self, clients: &mut [Client], mut log: W) -> io::Result<Vec<i64>> {
+        let players = self.game.players;
+        let names: Vec<String> = clients.iter().map(|c| c.name.clone()).collect();
+        writeln!(log, "# name/game/hands/seed {} {} {} {}",
+                 names.join("."), self.game.name(), self.hands, self.seed)?;
+        let mut totals = vec![0; clients.len()];
+        let passes = if self.duplicate { players } else { 1 };
+        for pass in 0..passes {
+            if self.duplicate {
+                writeln!(log, "# pass {} of {}", pass + 1, passes)?;
+            }
+            for hand in 0..self.hands {
+                // the client in each position, client c sitting in position
+                // c + pass + hand
+                let turn = pass + hand as usize % players;
+                let seated: Vec<usize> = (0..players)
+                    .map(|position| (position + 2 * players - turn) % players)
+                    .collect();
+                let values = self.play_hand(hand, clients, &seated, &mut log)?;
+                for (position, value) in values.into_iter().enumerate() {
+                    totals[seated[position]] += value as i64;
+                }
+            }
+        }
+        let totals_text: Vec<String> = totals.iter().map(|t| t.to_string()).collect();
+        writeln!(log, "SCORE:{}:{}", totals_text.join("|"), names.join("|"))?;
+        log.flush()?;
+        Ok(totals)
+    }
+
+    // what each position won
+    fn play_hand<W: Write>(&self, hand: u64, clients: &mut [Client], seated: &[usize],
+                           log: &mut W) -> io::Result<Vec<i32>> {
+        let mut table = self.game.table(self.seed.wrapping_add(hand));
+        let events = Rc::new(RefCell::new(EventLog::default()));
+        table.add_observer(View::Omniscient, events.clone());
+        table.start_hand().map_err(io::Error::other)?;
+        loop {
+            let states: Vec<String> = (0..self.game.players)
+                .map(|position| match_state(&events.borrow().events, &self.game, position, hand))
+                .collect();
+            for (position, state) in states.iter().enumerate() {
+                clients[seated[position]].send(state);
+            }
+            let seat = match table.to_act() {
+                Some(seat) => seat,
+                None => break,
+            };
+            let legal = table.legal_actions(seat).map_err(io::Error::other)?;
+            let player = table.player(seat);
+            let put_in = player.chips_in_pot - player.chips_in_play;
+            // a client that's out of time or gone checks or folds
+            let command = match clients[seated[seat]].answer(&states[seat]) {
+                Some(action) => parse_action(&action, &legal, put_in),
+                None if legal.can_check => Command::Check,
+                None => Command::Fold,
+            };
+            table.act(seat, command).map_err(io::Error::other)?;
+        }
+        let values: Vec<i32> = table.players().iter().map(|p| p.chips - self.game.stack).collect();
+        let events = &events.borrow().events;
+        let values_text: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        let names: Vec<&str> = seated.iter().map(|&c| clients[c].name.as_str()).collect();
+        writeln!(log, "STATE:{}:{}:{}:{}:{}", hand, betting(events, self.game.betting),
+                 cards(events, self.game.players, None), values_text.join("|"), names.join("|"))?;
+        Ok(values)
+    }
+}
@@ -0,0 +1,229 @@
+use game::player::Player;
+use game::table::Table;
+use game::error::GameError;
+use game::dealer::Dealer;
+use game::betting::BettingStructure;
+use game::strategy::{Strategy, CheckCall};
+use bot::rules::RuleBot;
+use bot::monte_carlo::MonteCarloBot;
+use bot::profile::Profile;
+use bot::solved::SolvedBot;
+use bot::chart::{ChartBot, PreflopChart};
+use bot::push_fold::{ChartBook, PushFoldBot};
+use bot::external::ExternalBot;
+use solver::SolvedStrategy;
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::BufReader;
+use std::rc::Rc;
+
+// big blinds everyone starts every hand with unless told otherwise
+const STACK: i32 = 100;
+// standard errors either side of a win rate for a 95% interval
+const Z_95: f64 = 1.96;
+
+// the strategy named by `spec`: rules, montecarlo or checkcall, a profile
+// like lag or "nit bluff=0.1", pushfold, chart:<file> or solved:<file>, or
+// external:<command>
+pub fn strategy(spec: &str, seed: u64) -> Result<Box<dyn Strategy>, GameError> {
+    let (kind, argument) = match spec.find(':') {
+        Some(i) => (&spec[..i], &spec[i + 1..]),
+        None => (spec, ""),
+    };
+    let open = |path: &str| {
+        File::open(path)
+            .map(BufReader::new)
+            .map_err(|err| GameError::InvalidStrategy(format!("{}: {}", path, err)))
+    };
+    Ok(match kind {
+        "rules" => Box::new(RuleBot::new(seed)),
+        "montecarlo" => Box::new(MonteCarloBot::new(seed)),
+        "checkcall" => Box::new(CheckCall),
+        "pushfold" => {
+            let book = Rc::new(RefCell::new(ChartBook::new()));
+            Box::new(PushFoldBot::new(Box::new(RuleBot::new(seed)), book, seed))
+        },
+        "chart" => {
+            let chart = PreflopChart::load(open(argument)?)?;
+            Box::new(ChartBot::new(Rc::new(chart), Box::new(RuleBot::new(seed)), seed))
+        },
+        "solved" => Box::new(SolvedBot::new(SolvedStrategy::load(open(argument)?)?, seed)?),
+        "external" => Box::new(ExternalBot::launch(argument)?),
+        _ => match spec.parse::<Profile>() {
+            Ok(profile) => Box::new(profile.bot(seed)),
+            Err(_) => {
+                return Err(GameError::InvalidStrategy(format!("there's no {} strategy", spec)))
+            },
+        },
+    })
+}
+
+// plays strategies against each other headless, as duplicate poker: every
+// deal is played once in each seating, each strategy getting each seat's
+// cards in turn, so the luck of the cards mostly cancels out. Stacks are
+// reset every hand
+pub struct Arena {
+    dealer: Dealer,
+    names: Vec<String>,
+    betting: BettingStructure,
+    stack: i32, // in big blinds
+    seed: u64,
+}
+
+impl Arena {
+    pub fn new(seed: u64) -> Arena {
+        Arena {
+            dealer: Dealer::new(Table::build_table()),
+            names: Vec::new(),
+            betting: BettingStructure::NoLimit,
+            stack: STACK,
+            seed,
+        }
+    }
+
+    pub fn betting(mut self, betting: BettingStructure) -> Arena {
+        self.betting = betting;
+        self
+    }
+
+    // in big blinds
+    pub fn stack(mut self, stack: i32) -> Arena {
+        self.stack = stack;
+        self
+    }
+
+    // seats `strategy` under `name`, numbered if the name is taken, and
+    // returns the name it got
+    pub fn enter(&mut self, name: &str, strategy: Box<dyn Strategy>) -> String {
+        let mut entered = name.to_string();
+        let mut n = 1;
+        while self.names.contains(&entered) {
+            n += 1;
+            entered = format!("{} #{}", name, n);
+        }
+        self.dealer.set_strategy(&entered, strategy);
+        self.names.push(entered.clone());
+        entered
+    }
+
+    // plays `deals` deals in every seating
+    pub fn run(&mut self, deals: u64) -> Result<Standings, GameError> {
+        let players = self.names.len();
+        if players < 2 {
+            return Err(GameError::NotEnoughPlayers);
+        }
+        let big_blind = self.dealer.table().big_blind();
+        let stack = self.stack * big_blind;
+        let mut results = vec![Vec::with_capacity(deals as usize); players];
+        for deal in 0..deals {
+            let mut won = vec![0; players];
+            for seating in 0..players {
+                let mut table = Table::build_table();
+                table.set_betting_structure(self.betting);
+                table.set_seed(self.seed.wrapping_add(deal));
+                // the cards are dealt from the button, so each seat gets
+                // the same cards in every seating
+                for seat in 0..players {
+                    let name = self.names[(seat + seating) % players].clone();
+                    table.add_player(Player::with_chips(name, false, stack));
+                }
+                table.set_button(players - 1);
+                *self.dealer.table_mut() = table;
+                self.dealer.play_hand()?;
+                for (i, name) in self.names.iter().enumerate() {
+                    if let Some(player) = self.dealer.table().find_player(name) {
+                        won[i] += player.chips - stack;
+                    }
+                }
+            }
+            for (i, won) in won.into_iter().enumerate() {
+                results[i].push(won as f64);
+            }
+        }
+        Ok(Standings { names: self.names.clone(), big_blind, seatings: players, results })
+    }
+}
+
+// what each strategy won on every deal, over all its seatings
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standings {
+    pub names: Vec<String>,
+    pub big_blind: i32,
+    pub seatings: usize,
+    results: Vec<Vec<f64>>,
+}
+
+impl Standings {
+    pub fn deals(&self) -> usize {
+        self.results.first().map_or(0, |results| results.len())
+    }
+
+    pub fn hands(&self) -> usize {
+        self.deals() * self.seatings
+    }
+
+    // the big blinds strategy `i` won per 100 hands
+    pub fn win_rate(&self, i: usize) -> f64 {
+        let deals = self.deals().max(1) as f64;
+        let mean = self.results[i].iter().sum::<f64>() / deals;
+        self.per_100_hands(mean)
+    }
+
+    // how far either side of the win rate the true one is, 95% of the
+    // time. Deals are the samples, as the seatings of one deal aren't
+    // independent
+    pub fn interval(&self, i: usize) -> f64 {
+        let deals = self.deals();
+        if deals < 2 {
+            return f64::INFINITY;
+        }
+        let results = &self.results[i];
+        let mean = results.iter().sum::<f64>() / deals as f64;
+        let variance = results.iter().map(|r| (r - mean) * (r - mean)).sum::<f64>()
+            / (deals - 1) as f64;
+        self.per_100_hands(Z_95 * (variance / deals as f64).sqrt())
+    }
+
+    fn per_100_hands(&self, per_deal: f64) -> f64 {
+        100.0 * per_deal / self.seatings as f64 / self.big_blind.max(1) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chips_are_only_won_from_each_other() {
+        let mut arena = Arena::new(3);
+        for spec in &["rules", "maniac", "checkcall"] {
+            arena.enter(spec, strategy(spec, 3).unwrap());
+        }
+        let standings = arena.run(20).unwrap();
+        assert_eq!((standings.deals(), standings.hands()), (20, 60));
+        for deal in 0..20 {
+            let won: f64 = standings.results.iter().map(|results| results[deal]).sum();
+            assert_eq!(won, 0.0);
+        }
+        let total: f64 = (0..3).map(|i| standings.win_rate(i)).sum();
+        assert!(total.abs() < 1e-9, "total {}", total);
+    }
+
+    #[test]
+    fn duplicate_deals_cancel_out_the_cards() {
+        // two players who check and call everything win back in one seating
+        // exactly what they lost in the other
+        let mut arena = Arena::new(5);
+        assert_eq!(arena.enter("checkcall", Box::new(CheckCall)), "checkcall");
+        assert_eq!(arena.enter("checkcall", Box::new(CheckCall)), "checkcall #2");
+        let standings = arena.run(10).unwrap();
+        assert_eq!((standings.win_rate(0), standings.win_rate(1)), (0.0, 0.0));
+    }
+
+    #[test]
+    fn unknown_strategies_are_invalid() {
+        assert!(strategy("shark", 1).is_err());
+        assert!(strategy("chart:no-such-chart", 1).is_err());
+    }
+}
@@ -0,0 +1,75 @@
+extern crate poker;
+
+use poker::arena::{self, Arena};
+use poker::game::betting::BettingStructure;
+
+use std::env;
+
+const HANDS: u64 = 10000;
+// flags followed by a value
+const VALUE_FLAGS: [&str; 3] = ["--hands", "--seed", "--stack"];
+
+// arena <strategy> <strategy> ... plays the strategies against each other
+// for --hands <n> hands of duplicate poker, dealt from --seed <n>, everyone
+// starting each hand --stack <big blinds> deep in no-limit, or --limit or
+// --pot-limit, and reports what each won
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut specs = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        if VALUE_FLAGS.contains(&args[i].as_str()) {
+            i += 1;
+        } else if !args[i].starts_with("--") {
+            specs.push(args[i].clone());
+        }
+        i += 1;
+    }
+    if specs.len() < 2 {
+        return println!("Usage: arena <strategy> <strategy> ... [--hands <n>] [--seed <n>] \
+                         [--stack <big blinds>] [--limit | --pot-limit]\n\
+                         Strategies are rules, montecarlo, checkcall, pushfold, a profile \
+                         like lag, chart:<file>, solved:<file> or external:<command>");
+    }
+    let hands = arg_value("--hands").and_then(|n| n.parse().ok()).unwrap_or(HANDS);
+    let seed = arg_value("--seed").and_then(|n| n.parse().ok()).unwrap_or(0);
+    let betting = if env::args().any(|arg| arg == "--limit") {
+        BettingStructure::FixedLimit
+    } else if env::args().any(|arg| arg == "--pot-limit") {
+        BettingStructure::PotLimit
+    } else {
+        BettingStructure::NoLimit
+    };
+    let mut arena = Arena::new(seed).betting(betting);
+    if let Some(stack) = arg_value("--stack").and_then(|n| n.parse().ok()) {
+        arena = arena.stack(stack);
+    }
+    for (i, spec) in specs.iter().enumerate() {
+        match arena::strategy(spec, seed.wrapping_add(i as u64)) {
+            Ok(strategy) => {
+                arena.enter(spec, strategy);
+            },
+            Err(err) => return println!("{}", err),
+        }
+    }
+    // whole deals, each played in every seating
+    let seatings = specs.len() as u64;
+    let standings = match arena.run(hands.div_ceil(seatings)) {
+        Ok(standings) => standings,
+        Err(err) => return println!("{}", err),
+    };
+    println!("{} hands, {} deals each played in {} seatings\n",
+             standings.hands(), standings.deals(), standings.seatings);
+    let width = standings.names.iter().map(|name| name.chars().count()).max().unwrap_or(0) + 2;
+    println!("{:<width$}{:>10}{:>14}", "Strategy", "bb/100", "95% interval", width = width);
+    for (i, name) in standings.names.iter().enumerate() {
+        println!("{:<width$}{:>+10.2}{:>14}", name, standings.win_rate(i),
+                 format!("± {:.2}", standings.interval(i)), width = width);
+    }
+}
+
+// the value following `name` on the command line
+fn arg_value(name: &str) -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+    args.iter().position(|arg| arg == name).and_then(|i| args.get(i + 1).cloned())
+}
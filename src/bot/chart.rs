@@ -0,0 +1,323 @@
+use game::player::Command;
+use game::table::Street;
+use game::event::Event;
+use game::cash::CashGame;
+use game::error::GameError;
+use game::strategy::{Strategy, Observation};
+use super::hand_class::HandClass;
+use super::{raise_to, seeded_rng};
+
+use rand::{Rng, StdRng};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::BufRead;
+use std::rc::Rc;
+use std::str::FromStr;
+
+// counting back from the big blind. Whoever acts first is also the UTG
+const POSITIONS: [&str; 9] = ["BB", "SB", "BTN", "CO", "HJ", "LJ", "UTG+2", "UTG+1", "UTG"];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChartAction {
+    Open, // raise when nobody has
+    ThreeBet, // reraise a single raise
+    Call, // call a single raise
+}
+
+impl fmt::Display for ChartAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ChartAction::Open => write!(f, "open"),
+            ChartAction::ThreeBet => write!(f, "3bet"),
+            ChartAction::Call => write!(f, "call"),
+        }
+    }
+}
+
+impl FromStr for ChartAction {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<ChartAction, GameError> {
+        match s.to_lowercase().as_str() {
+            "open" => Ok(ChartAction::Open),
+            "3bet" | "3-bet" => Ok(ChartAction::ThreeBet),
+            "call" => Ok(ChartAction::Call),
+            _ => Err(GameError::InvalidChart(format!("{} isn't open, 3bet or call", s))),
+        }
+    }
+}
+
+// how often each hand class opens, 3-bets or calls from each position.
+// Charts are written one range per line, as a position, an action and the
+// hands separated by commas or spaces:
+//
+//     # the button
+//     BTN, open, 22+, A2s+, KTo+, 76s
+//     BTN, 3bet, QQ+, AKs, A5s:0.5
+//
+// A hand is a class like AKs, 72o or TT, one and everything above it with
+// the same high card, like ATs+ or 77+, or a run like 22-JJ or A2s-A5s.
+// Hands may be followed by how often they're played, and are always played
+// otherwise
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PreflopChart {
+    ranges: BTreeMap<(String, ChartAction), Vec<f64>>, // by HandClass::index
+}
+
+impl PreflopChart {
+    pub fn load<R: BufRead>(input: R) -> Result<PreflopChart, GameError> {
+        let mut chart = PreflopChart::default();
+        let mut listed = BTreeSet::new();
+        for (i, line) in input.lines().enumerate() {
+            let line = line.map_err(|err| GameError::InvalidChart(err.to_string()))?;
+            let at_line = |reason: String| {
+                GameError::InvalidChart(format!("line {}: {}", i + 1, reason))
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut words = line.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|word| !word.is_empty());
+            let (position, action) = match (words.next(), words.next()) {
+                (Some(position), Some(action)) => (position.to_uppercase(), action),
+                _ => return Err(at_line("expected a position, an action and hands".into())),
+            };
+            if !POSITIONS.contains(&position.as_str()) {
+                return Err(at_line(format!("{} isn't a position", position)));
+            }
+            let action: ChartAction = action.parse()
+                .map_err(|_| at_line(format!("{} isn't open, 3bet or call", action)))?;
+            let range = chart.ranges.entry((position.clone(), action))
+                .or_insert_with(|| vec![0.0; 169]);
+            for word in words {
+                let (hands, frequency) = parse_hands(word).map_err(at_line)?;
+                for class in hands {
+                    if !listed.insert((position.clone(), action, class)) {
+                        return Err(at_line(format!("{} is in the {} {} range twice",
+                                                   class, position, action)));
+                    }
+                    range[class.index()] = frequency;
+                }
+            }
+        }
+        chart.check()?;
+        Ok(chart)
+    }
+
+    // a hand can't be 3-bet and called more than all the time
+    fn check(&self) -> Result<(), GameError> {
+        for (&(ref position, action), range) in &self.ranges {
+            if action != ChartAction::ThreeBet {
+                continue;
+            }
+            let calls = self.ranges.get(&(position.clone(), ChartAction::Call));
+            for class in HandClass::all() {
+                let call = calls.map_or(0.0, |calls| calls[class.index()]);
+                if range[class.index()] + call > 1.0 {
+                    let reason = format!("{} 3-bets and calls {} more than all the time",
+                                         position, class);
+                    return Err(GameError::InvalidChart(reason));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // whether the chart says anything about `action` from `position`
+    pub fn covers(&self, position: &str, action: ChartAction) -> bool {
+        self.ranges.contains_key(&(position.to_string(), action))
+    }
+
+    pub fn frequency(&self, position: &str, action: ChartAction, class: &HandClass) -> f64 {
+        self.ranges.get(&(position.to_string(), action))
+            .map_or(0.0, |range| range[class.index()])
+    }
+}
+
+// the classes in one entry of a range, like "AKs", "ATs+" or "TT+:0.5",
+// with how often they're played
+fn parse_hands(word: &str) -> Result<(Vec<HandClass>, f64), String> {
+    let (hands, frequency) = match word.find(':') {
+        Some(i) => (&word[..i], &word[i + 1..]),
+        None => (word, "1"),
+    };
+    let frequency = match frequency.parse::<f64>() {
+        Ok(f) if (0.0..=1.0).contains(&f) => f,
+        _ => return Err(format!("{} isn't a frequency between 0 and 1", frequency)),
+    };
+    let class = |name: &str| name.parse::<HandClass>().map_err(|err| err.to_string());
+    // the classes from `low` to `high` of the same kind, moving the pair or
+    // the low card
+    let between = |low: HandClass, high: HandClass| {
+        let same_kind = low.suited == high.suited && low.is_pair() == high.is_pair()
+            && (low.is_pair() || low.high == high.high);
+        if !same_kind {
+            return Err(format!("{} isn't a range", hands));
+        }
+        Ok(HandClass::all().into_iter()
+            .filter(|c| c.suited == low.suited && c.is_pair() == low.is_pair())
+            .filter(|c| if low.is_pair() {
+                low.high <= c.high && c.high <= high.high
+            } else {
+                c.high == low.high && low.low <= c.low && c.low <= high.low
+            })
+            .collect())
+    };
+    let classes = if let Some(name) = hands.strip_suffix('+') {
+        let low = class(name)?;
+        let high = if low.is_pair() {
+            HandClass { high: 14, low: 14, suited: false }
+        } else {
+            HandClass { low: low.high - 1, ..low }
+        };
+        between(low, high)?
+    } else if let Some(i) = hands.find('-') {
+        let (a, b) = (class(&hands[..i])?, class(&hands[i + 1..])?);
+        between(a.min(b), a.max(b))?
+    } else {
+        vec![class(hands)?]
+    };
+    Ok((classes, frequency))
+}
+
+// the names of the seat to act, like "BTN", or "HJ" and "UTG"
+pub fn positions(obs: &Observation) -> Vec<&'static str> {
+    let found = obs.history.iter()
+        .filter_map(|event| match *event {
+            Event::HandStarted { ref button, ref seats, .. } => Some((button, seats)),
+            _ => None,
+        })
+        .next();
+    let (button, seats) = match found {
+        Some(found) => found,
+        None => return Vec::new(),
+    };
+    let players = seats.len();
+    let index = |name: &str| seats.iter().position(|s| s.1 == name);
+    let after_button = match (index(obs.name()), index(button)) {
+        (Some(seat), Some(button)) => (seat + players - button) % players,
+        _ => return Vec::new(),
+    };
+    // heads up the button is the small blind
+    let from_big_blind = if players == 2 {
+        1 - after_button
+    } else {
+        (2 + players - after_button) % players
+    };
+    let mut names: Vec<&str> = POSITIONS.get(from_big_blind).cloned().into_iter().collect();
+    if from_big_blind == players - 1 && from_big_blind >= 3 && !names.contains(&"UTG") {
+        names.push("UTG");
+    }
+    names
+}
+
+// plays preflop from a chart and leaves the rest, like the flop or facing a
+// 3-bet, to another strategy
+pub struct ChartBot {
+    chart: Rc<PreflopChart>,
+    strategy: Box<dyn Strategy>,
+    rng: StdRng,
+}
+
+impl ChartBot {
+    pub fn new(chart: Rc<PreflopChart>, strategy: Box<dyn Strategy>, seed: u64) -> ChartBot {
+        ChartBot { chart, strategy, rng: seeded_rng(seed) }
+    }
+
+    fn preflop(&mut self, obs: &Observation) -> Option<Command> {
+        let raises = obs.history.iter()
+            .filter(|e| matches!(*e, Event::Action { command: Command::Raise(_), .. }))
+            .count();
+        let actions: &[ChartAction] = match raises {
+            0 => &[ChartAction::Open],
+            1 => &[ChartAction::ThreeBet, ChartAction::Call],
+            _ => return None,
+        };
+        let position = positions(obs).into_iter()
+            .find(|position| actions.iter().any(|&action| self.chart.covers(position, action)))?;
+        let legal = &obs.legal;
+        let class = HandClass::of(&obs.cards.0, &obs.cards.1);
+        let mut roll = self.rng.gen::<f64>();
+        for &action in actions {
+            let frequency = self.chart.frequency(position, action, &class);
+            if roll >= frequency {
+                roll -= frequency;
+                continue;
+            }
+            // three big blinds to open, plus one for each limper, otherwise
+            // three times the bet
+            let big_blind = obs.table.big_blind;
+            let command = match action {
+                ChartAction::Open => {
+                    raise_to(legal, 3 * big_blind + obs.pot() - obs.table.small_blind - big_blind)
+                },
+                ChartAction::ThreeBet => raise_to(legal, 3 * legal.current_bet),
+                ChartAction::Call => None,
+            };
+            return Some(command.unwrap_or_else(|| legal.check_or_call()));
+        }
+        Some(if legal.can_check { Command::Check } else { Command::Fold })
+    }
+}
+
+impl Strategy for ChartBot {
+    fn act(&mut self, obs: &Observation) -> Command {
+        let command = if obs.table.street == Street::PreFlop { self.preflop(obs) } else { None };
+        match command {
+            Some(command) => command,
+            None => self.strategy.act(obs),
+        }
+    }
+
+    fn rebuy(&mut self, cash: &CashGame) -> Option<i32> {
+        self.strategy.rebuy(cash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(text: &str) -> Result<PreflopChart, GameError> {
+        PreflopChart::load(text.as_bytes())
+    }
+
+    fn class(name: &str) -> HandClass {
+        name.parse().unwrap()
+    }
+
+    #[test]
+    fn reads_ranges_and_frequencies() {
+        let chart = load("# the button\nBTN, open, 77+, ATs+, A2s-A4s, KQo\n\
+                          btn 3bet QQ+ A5s:0.5\n").unwrap();
+        let open = |name| chart.frequency("BTN", ChartAction::Open, &class(name));
+        for name in &["77", "AA", "ATs", "AKs", "A3s", "KQo"] {
+            assert_eq!(open(name), 1.0, "{}", name);
+        }
+        for name in &["66", "A9s", "A5s", "KJo", "KQs"] {
+            assert_eq!(open(name), 0.0, "{}", name);
+        }
+        assert_eq!(chart.frequency("BTN", ChartAction::ThreeBet, &class("A5s")), 0.5);
+        assert!(chart.covers("BTN", ChartAction::ThreeBet));
+        assert!(!chart.covers("BTN", ChartAction::Call));
+    }
+
+    #[test]
+    fn mistakes_are_reported_with_their_line() {
+        let mistakes = [
+            ("BTN\n", "line 1: expected a position, an action and hands"),
+            ("\nMP3, open, AA\n", "line 2: MP3 isn't a position"),
+            ("BTN, limp, AA\n", "line 1: limp isn't open, 3bet or call"),
+            ("BTN, open, AX\n", "line 1: AX isn't a hand class"),
+            ("BTN, open, AA:2\n", "line 1: 2 isn't a frequency between 0 and 1"),
+            ("BTN, open, A2s-KQs\n", "line 1: A2s-KQs isn't a range"),
+            ("BTN, open, 22+, TT\n", "line 1: TT is in the BTN open range twice"),
+            ("SB, 3bet, AA:0.6\nSB, call, AA:0.6\n",
+             "SB 3-bets and calls AA more than all the time"),
+        ];
+        for &(text, reason) in &mistakes {
+            assert_eq!(load(text), Err(GameError::InvalidChart(reason.to_string())));
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use game::card::Card;
+use game::player::Command;
+use game::table::Street;
+use game::event::Event;
+use game::error::GameError;
+use game::betting::LegalActions;
+use game::strategy::{Strategy, Observation};
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command as Process, Stdio};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// how long a bot gets to answer unless told otherwise
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+// a bot in its own program, in any language, that is sent the state of the
+// hand a line at a time on its standard input whenever it's its turn:
+//
+//     STATE 12:2:3:flop:AsKd:2c7h9d:60:180:20:40:200:r30cc/r20
+//
+// which is the hand number, its seat counting from 0, the number of players,
+// the street, its cards, the board, the pot, its stack, the amount to call,
+// the smallest and largest amounts it can raise to, or 0 and 0 if it can't,
+// and the betting so far street by street, with f for a fold, c for a check
+// or call and r for a raise to an amount. It answers on its standard output
+// with a line of f, c or r<amount>, or fold, check, call or raise <amount>.
+// Anything else, or no answer in time, checks or folds, as does every turn
+// once the bot has exited. QUIT is sent when the bot is no longer needed
+pub struct ExternalBot {
+    child: Child,
+    input: Option<ChildStdin>, // none once the bot has gone
+    answers: Receiver<String>,
+    timeout: Duration,
+}
+
+impl ExternalBot {
+    // runs `command`, a program followed by its arguments
+    pub fn launch(command: &str) -> Result<ExternalBot, GameError> {
+        let mut words = command.split_whitespace();
+        let program = words.next()
+            .ok_or_else(|| GameError::BotFailed("no command to run".to_string()))?;
+        let mut child = Process::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| GameError::BotFailed(format!("{}: {}", command, err)))?;
+        let input = child.stdin.take();
+        let output = child.stdout.take();
+        // reading on a thread of its own lets a slow bot be timed out
+        let (sender, answers) = mpsc::channel();
+        thread::spawn(move || {
+            let output = match output {
+                Some(output) => output,
+                None => return,
+            };
+            for line in BufReader::new(output).lines() {
+                let sent = line.ok().map(|line| sender.send(line));
+                if !matches!(sent, Some(Ok(()))) {
+                    break;
+                }
+            }
+        });
+        Ok(ExternalBot { child, input, answers, timeout: TIMEOUT })
+    }
+
+    // how long it waits for each answer
+    pub fn timeout(mut self, timeout: Duration) -> ExternalBot {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.input.is_some()
+    }
+
+    // the bot's answer to `message`, if it gives one within `timeout`
+    fn ask(&mut self, message: &str, timeout: Duration) -> Option<String> {
+        // throw away answers that came too late for earlier questions
+        while self.answers.try_recv().is_ok() {}
+        let sent = match self.input {
+            Some(ref mut input) => writeln!(input, "{}", message).and_then(|_| input.flush()),
+            None => return None,
+        };
+        if sent.is_err() {
+            self.input = None;
+            return None;
+        }
+        match self.answers.recv_timeout(timeout) {
+            Ok(answer) => Some(answer),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => {
+                self.input = None;
+                None
+            },
+        }
+    }
+}
+
+impl Strategy for ExternalBot {
+    fn act(&mut self, obs: &Observation) -> Command {
+        let legal = &obs.legal;
+        // no point waiting past the dealer's time limit
+        let timeout = obs.deadline.as_ref()
+            .map_or(self.timeout, |deadline| deadline.left().min(self.timeout));
+        let answer = self.ask(&state(obs), timeout);
+        answer.and_then(|answer| parse_answer(&answer, legal))
+            .unwrap_or_else(|| legal.check_or_fold())
+    }
+}
+
+// gives the bot as long as an answer to quit, then stops it
+impl Drop for ExternalBot {
+    fn drop(&mut self) {
+        if let Some(mut input) = self.input.take() {
+            let _ = writeln!(input, "QUIT").and_then(|_| input.flush());
+        }
+        let started = Instant::now();
+        while started.elapsed() < self.timeout {
+            match self.child.try_wait() {
+                Ok(None) => thread::sleep(Duration::from_millis(10)),
+                _ => return,
+            }
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// the STATE line sent to a bot deciding on `obs`
+pub fn state(obs: &Observation) -> String {
+    let cards = |cards: &[Rc<Card>]| cards.iter().map(|card| card.short()).collect::<String>();
+    let street = match obs.table.street {
+        Street::PreFlop => "preflop",
+        Street::Flop => "flop",
+        Street::Turn => "turn",
+        Street::River => "river",
+    };
+    let hole = [obs.cards.0.clone(), obs.cards.1.clone()];
+    let legal = &obs.legal;
+    let to_call = if legal.can_call { legal.call_amount } else { 0 };
+    let (min, max) = legal.raise_to.unwrap_or((0, 0));
+    format!("STATE {}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}:{}",
+            obs.table.hand_number, obs.seat, obs.table.seats.len(), street,
+            cards(&hole), cards(obs.board()), obs.pot(), obs.stack(),
+            to_call, min, max, betting(&obs.history))
+}
+
+// the betting in `history` as f, c and r<amount to>, streets split by /
+fn betting(history: &[Event]) -> String {
+    let mut betting = String::new();
+    let mut bet = 0;
+    for event in history {
+        match *event {
+            Event::BlindPosted { amount, .. } => bet = bet.max(amount),
+            Event::StreetDealt { .. } => {
+                betting.push('/');
+                bet = 0;
+            },
+            Event::Action { ref command, .. } => match *command {
+                Command::Fold | Command::Leave => betting.push('f'),
+                Command::Check | Command::Call => betting.push('c'),
+                Command::Raise(by) => {
+                    bet += by;
+                    betting.push_str(&format!("r{}", bet));
+                },
+                Command::PostBlind => {},
+            },
+            _ => {},
+        }
+    }
+    betting
+}
+
+// an answer, if it's a legal one
+fn parse_answer(answer: &str, legal: &LegalActions) -> Option<Command> {
+    let answer = answer.trim().to_lowercase();
+    let command = match answer.as_str() {
+        "f" | "fold" => Command::Fold,
+        "c" => legal.check_or_call(),
+        "check" => Command::Check,
+        "call" => Command::Call,
+        _ => {
+            let amount = answer.strip_prefix("raise")
+                .or_else(|| answer.strip_prefix('r'))?
+                .trim()
+                .parse()
+                .ok()?;
+            legal.raise(amount)?
+        },
+    };
+    if legal.allows(&command) { Some(command) } else { None }
+}
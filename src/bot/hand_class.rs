@@ -0,0 +1,109 @@
+use game::card::Card;
+use game::error::GameError;
+
+use std::fmt;
+use std::str::FromStr;
+
+const RANKS: &str = "23456789TJQKA";
+
+// one of the 169 kinds of starting hand, like "AKs", "72o" or "TT"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HandClass {
+    pub high: i32,
+    pub low: i32,
+    pub suited: bool,
+}
+
+impl HandClass {
+    pub fn of(c1: &Card, c2: &Card) -> HandClass {
+        HandClass {
+            high: c1.val.max(c2.val),
+            low: c1.val.min(c2.val),
+            suited: c1.suit == c2.suit,
+        }
+    }
+
+    // every class in chart order: row by row from aces down, suited hands
+    // right of the pairs and offsuit hands left of them
+    pub fn all() -> Vec<HandClass> {
+        let mut classes = Vec::new();
+        for row in 0..13 {
+            for column in 0..13 {
+                classes.push(HandClass::at(row, column));
+            }
+        }
+        classes
+    }
+
+    // the class at a row and column of a chart, both counted from the aces
+    pub fn at(row: usize, column: usize) -> HandClass {
+        let (a, b) = (14 - row as i32, 14 - column as i32);
+        HandClass { high: a.max(b), low: a.min(b), suited: column > row }
+    }
+
+    // the row and column of its chart cell
+    pub fn position(&self) -> (usize, usize) {
+        let (high, low) = ((14 - self.high) as usize, (14 - self.low) as usize);
+        if self.suited { (high, low) } else { (low, high) }
+    }
+
+    // 0 to 168, in chart order
+    pub fn index(&self) -> usize {
+        let (row, column) = self.position();
+        row * 13 + column
+    }
+
+    pub fn is_pair(&self) -> bool {
+        self.high == self.low
+    }
+
+    // how many ways it can be dealt
+    pub fn combos(&self) -> usize {
+        if self.is_pair() {
+            6
+        } else if self.suited {
+            4
+        } else {
+            12
+        }
+    }
+}
+
+fn rank_char(val: i32) -> char {
+    RANKS.as_bytes()[(val - 2) as usize] as char
+}
+
+impl fmt::Display for HandClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", rank_char(self.high), rank_char(self.low))?;
+        if !self.is_pair() {
+            write!(f, "{}", if self.suited { 's' } else { 'o' })?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for HandClass {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<HandClass, GameError> {
+        let invalid = || GameError::InvalidHandClass(s.to_string());
+        let chars: Vec<char> = s.chars().collect();
+        let rank = |c: char| RANKS.find(c.to_ascii_uppercase()).map(|i| i as i32 + 2);
+        let first = chars.first().cloned().and_then(rank);
+        let (a, b) = match (first, chars.get(1).cloned().and_then(rank)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return Err(invalid()),
+        };
+        let suited = match (chars.get(2), a == b) {
+            (None, true) => false,
+            (Some('s'), false) => true,
+            (Some('o'), false) => false,
+            _ => return Err(invalid()),
+        };
+        if chars.len() > 3 {
+            return Err(invalid());
+        }
+        Ok(HandClass { high: a.max(b), low: a.min(b), suited })
+    }
+}
@@ -0,0 +1,26 @@
+use game::player::Command;
+use game::betting::LegalActions;
+
+use rand::{SeedableRng, StdRng};
+
+pub mod strength;
+pub mod rules;
+pub mod monte_carlo;
+pub mod profile;
+pub mod solved;
+pub mod hand_class;
+pub mod push_fold;
+pub mod chart;
+pub mod external;
+
+// a raise to `amount`, moved into the legal range, if raising is allowed
+pub fn raise_to(legal: &LegalActions, amount: i32) -> Option<Command> {
+    let (min, max) = legal.raise_to?;
+    legal.raise(amount.max(min).min(max))
+}
+
+// bots are deterministic given their seed
+pub fn seeded_rng(seed: u64) -> StdRng {
+    let seed = [seed as usize, (seed >> 32) as usize];
+    SeedableRng::from_seed(&seed[..])
+}
@@ -0,0 +1,331 @@
+use game::card::{Card, Hand};
+use game::player::Command;
+use game::table::{self, Street};
+use game::event::Event;
+use game::strategy::{Strategy, Observation};
+use game::clock::{Clock, SystemClock};
+use super::strength::{preflop_strength, postflop_strength};
+use super::{raise_to, seeded_rng};
+
+use rand::{Rng, StdRng};
+use std::cmp::Ordering;
+use std::rc::Rc;
+use std::time::Duration;
+
+const ITERATIONS: usize = 500;
+// attempts at drawing a hand that fits an opponent's range before settling
+const RANGE_ATTEMPTS: usize = 100;
+// how often an opponent is given any hand at all, for bluffs
+const OFF_RANGE: f64 = 0.1;
+// how long before the dealer's deadline sampling stops, to answer in time
+const DEADLINE_MARGIN: Duration = Duration::from_millis(20);
+
+// the strength each of an opponent's bets and calls showed, with the board
+// at the time
+struct Range {
+    name: String,
+    actions: Vec<(f64, Vec<Rc<Card>>)>,
+}
+
+impl Range {
+    fn fits(&self, c1: &Rc<Card>, c2: &Rc<Card>) -> bool {
+        self.actions.iter().all(|&(needed, ref board)| strength(c1, c2, board) >= needed)
+    }
+}
+
+// one rollout: the hero's share of the pot and the best opponent's current
+// strength, which decides whether they'd call a bet
+struct Sample {
+    share: f64,
+    opponent_strength: f64,
+}
+
+// samples opponents' hands from ranges narrowed by their actions, rolls out
+// the board and takes the action with the best expected value. Decisions are
+// reproducible from the seed unless a time limit or the dealer's deadline
+// cuts the sampling short
+pub struct MonteCarloBot {
+    pub iterations: usize,
+    pub time_limit: Option<Duration>,
+    clock: Rc<dyn Clock>, // what the time limit is measured by
+    rng: StdRng,
+}
+
+impl MonteCarloBot {
+    pub fn new(seed: u64) -> MonteCarloBot {
+        MonteCarloBot {
+            iterations: ITERATIONS,
+            time_limit: None,
+            clock: Rc::new(SystemClock::new()),
+            rng: seeded_rng(seed),
+        }
+    }
+
+    pub fn iterations(mut self, iterations: usize) -> MonteCarloBot {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn time_limit(mut self, limit: Duration) -> MonteCarloBot {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    pub fn clock(mut self, clock: Rc<dyn Clock>) -> MonteCarloBot {
+        self.clock = clock;
+        self
+    }
+
+    fn simulate(&mut self, obs: &Observation) -> Vec<Sample> {
+        let ranges = opponent_ranges(obs);
+        let (hero1, hero2) = obs.cards.clone();
+        let board = obs.board().to_vec();
+        let deck: Vec<Rc<Card>> = table::build_deck().into_iter()
+            .filter(|c| *c != hero1 && *c != hero2 && !board.contains(c))
+            .collect();
+
+        let stop = self.time_limit.map(|limit| self.clock.now() + limit);
+        let mut samples = Vec::with_capacity(self.iterations);
+        for _ in 0..self.iterations {
+            if stop.is_some_and(|stop| self.clock.now() >= stop) {
+                break;
+            }
+            if obs.deadline.as_ref().is_some_and(|due| due.left() <= DEADLINE_MARGIN) {
+                break;
+            }
+            let mut deck = deck.clone();
+            self.rng.shuffle(&mut deck);
+            let mut opponents = Vec::new();
+            for range in &ranges {
+                opponents.push(self.draw_from(range, &mut deck));
+            }
+            let mut full_board = board.clone();
+            while full_board.len() < 5 {
+                if let Some(card) = deck.pop() {
+                    full_board.push(card);
+                }
+            }
+
+            let hero = showdown_hand(&hero1, &hero2, &full_board);
+            let mut tied = 1;
+            let mut lost = false;
+            let mut opponent_strength: f64 = 0.0;
+            for (c1, c2) in &opponents {
+                opponent_strength = opponent_strength.max(strength(c1, c2, &board));
+                let hand = showdown_hand(c1, c2, &full_board);
+                match hand.cmp(&hero) {
+                    Ordering::Greater => lost = true,
+                    Ordering::Equal => tied += 1,
+                    Ordering::Less => {},
+                }
+            }
+            let share = if lost { 0.0 } else { 1.0 / tied as f64 };
+            samples.push(Sample { share, opponent_strength });
+        }
+        samples
+    }
+
+    // hole cards from the shuffled deck that fit the range, if any turn up
+    // soon, or now and then any hand at all
+    fn draw_from(&mut self, range: &Range, deck: &mut Vec<Rc<Card>>) -> (Rc<Card>, Rc<Card>) {
+        if self.rng.gen::<f64>() >= OFF_RANGE {
+            for _ in 0..RANGE_ATTEMPTS {
+                let n = deck.len();
+                if n < 4 {
+                    break;
+                }
+                let i = self.rng.gen_range(0, n);
+                let j = (i + 1 + self.rng.gen_range(0, n - 1)) % n;
+                if range.fits(&deck[i], &deck[j]) {
+                    let (hi, lo) = if i > j { (i, j) } else { (j, i) };
+                    let c1 = deck.swap_remove(hi);
+                    let c2 = deck.swap_remove(lo);
+                    return (c1, c2);
+                }
+            }
+        }
+        let c1 = deck.pop().expect("a deck with cards left");
+        let c2 = deck.pop().expect("a deck with cards left");
+        (c1, c2)
+    }
+}
+
+impl Strategy for MonteCarloBot {
+    fn act(&mut self, obs: &Observation) -> Command {
+        let legal = obs.legal.clone();
+        let samples = self.simulate(obs);
+        if samples.is_empty() {
+            return legal.check_or_call();
+        }
+        let equity = samples.iter().map(|s| s.share).sum::<f64>() / samples.len() as f64;
+        let pot = obs.pot() as f64;
+        let to_call = if legal.can_call { legal.call_amount as f64 } else { 0.0 };
+
+        let mut best = (legal.check_or_call(), equity * (pot + to_call) - to_call);
+        if !legal.can_check && best.1 < 0.0 {
+            best = (Command::Fold, 0.0);
+        }
+        let own_bet = obs.table.seats[obs.seat].bet;
+        for &fraction in &[0.5, 1.0] {
+            let amount = legal.current_bet + (to_call + pot * fraction) as i32;
+            let cmd = match raise_to(&legal, amount) {
+                Some(cmd) => cmd,
+                None => continue,
+            };
+            let to = match cmd {
+                Command::Raise(x) => legal.current_bet + x,
+                _ => continue,
+            };
+            let cost = (to - own_bet) as f64;
+            let raise = (to - legal.current_bet) as f64;
+            // opponents continue with hands comfortably better than their pot odds
+            let needed = 0.15 + raise / (pot + cost + raise);
+            let called: Vec<&Sample> = samples.iter()
+                .filter(|s| s.opponent_strength >= needed)
+                .collect();
+            let fold = 1.0 - called.len() as f64 / samples.len() as f64;
+            let called_equity = if called.is_empty() {
+                equity
+            } else {
+                called.iter().map(|s| s.share).sum::<f64>() / called.len() as f64
+            };
+            let ev = fold * pot + (1.0 - fold) * (called_equity * (pot + cost + raise) - cost);
+            if ev > best.1 {
+                best = (cmd, ev);
+            }
+        }
+        best.0
+    }
+}
+
+fn strength(c1: &Rc<Card>, c2: &Rc<Card>, board: &[Rc<Card>]) -> f64 {
+    if board.is_empty() {
+        preflop_strength(c1, c2)
+    } else {
+        postflop_strength(c1, c2, board)
+    }
+}
+
+fn showdown_hand(c1: &Rc<Card>, c2: &Rc<Card>, board: &[Rc<Card>]) -> Option<Hand> {
+    let mut cards = vec![c1.clone(), c2.clone()];
+    cards.extend_from_slice(board);
+    Hand::make_hand(cards).ok()
+}
+
+// the opponents still in the hand and what their actions say about them
+fn opponent_ranges(obs: &Observation) -> Vec<Range> {
+    let mut ranges: Vec<Range> = obs.table.seats.iter()
+        .filter(|s| s.in_hand && s.seat != obs.seat)
+        .map(|s| Range { name: s.name.clone(), actions: Vec::new() })
+        .collect();
+    let mut board = Vec::new();
+    let mut raises = 0;
+    for event in &obs.history {
+        match *event {
+            Event::StreetDealt { street, ref cards } if street != Street::PreFlop => {
+                board = cards.clone();
+                raises = 0;
+            },
+            Event::Action { ref name, ref command, .. } => {
+                let call = if board.is_empty() { 0.3 } else { 0.35 };
+                // each raise on a street asks for more than the last
+                let needed = match *command {
+                    Command::Raise(_) => {
+                        raises += 1;
+                        call + 0.1 + 0.1 * raises as f64
+                    },
+                    Command::Call => call + 0.1 * raises as f64,
+                    _ => continue,
+                };
+                if let Some(range) = ranges.iter_mut().find(|r| r.name == *name) {
+                    range.actions.push((needed, board.clone()));
+                }
+            },
+            _ => {},
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::table::Table;
+    use game::player::Player;
+    use game::testing::cards;
+    use game::clock::{Deadline, ManualClock};
+
+    // a, b and c with 1000 chips each, a on the button, before the deal
+    fn three_handed() -> Table {
+        let mut table = Table::build_table();
+        for name in &["a", "b", "c"] {
+            table.add_player(Player::with_chips(name.to_string(), false, 1000));
+        }
+        table.set_button(0);
+        table
+    }
+
+    // c in the big blind with `hole`, after `raises` from a and b
+    fn big_blind_facing(hole: &str, raises: &[Command]) -> Observation {
+        let mut table = three_handed();
+        table.stack_deck(cards(&format!("Kd Qd Js Jh {} 2c 3d 4h 5s 6c", hole)));
+        table.start_hand().unwrap();
+        for (seat, cmd) in raises.iter().enumerate() {
+            table.act(seat, cmd.clone()).unwrap();
+        }
+        table.observation(2).unwrap()
+    }
+
+    // every decision the bot makes playing all three seats of a deal
+    fn decisions(deal: u64, seed: u64) -> Vec<Command> {
+        let mut table = three_handed();
+        table.set_seed(deal);
+        let mut bot = MonteCarloBot::new(seed).iterations(200);
+        let mut decisions = Vec::new();
+        table.start_hand().unwrap();
+        while let Some(seat) = table.to_act() {
+            let cmd = bot.act(&table.observation(seat).unwrap());
+            decisions.push(cmd.clone());
+            table.act(seat, cmd).unwrap();
+        }
+        decisions
+    }
+
+    #[test]
+    fn same_seed_same_decisions() {
+        for deal in 0..5 {
+            assert_eq!(decisions(deal, 7), decisions(deal, 7));
+        }
+    }
+
+    #[test]
+    fn folds_seven_deuce_to_a_raise_and_reraise() {
+        let obs = big_blind_facing("7c 2d", &[Command::Raise(20), Command::Raise(70)]);
+        for seed in 0..5 {
+            assert_eq!(MonteCarloBot::new(seed).iterations(200).act(&obs), Command::Fold);
+        }
+    }
+
+    #[test]
+    fn raises_aces() {
+        let obs = big_blind_facing("As Ah", &[Command::Raise(20), Command::Fold]);
+        for seed in 0..5 {
+            match MonteCarloBot::new(seed).iterations(200).act(&obs) {
+                Command::Raise(_) => {},
+                cmd => panic!("played {}", cmd),
+            }
+        }
+    }
+
+    #[test]
+    fn stops_sampling_at_the_deadline() {
+        let mut table = three_handed();
+        table.start_hand().unwrap();
+        let mut obs = table.observation(table.to_act().unwrap()).unwrap();
+        obs.deadline = Some(Deadline::new(Rc::new(ManualClock::new()), DEADLINE_MARGIN));
+        let mut bot = MonteCarloBot::new(11).iterations(1_000_000);
+        // nothing sampled, so it just checks or calls
+        assert_eq!(bot.simulate(&obs).len(), 0);
+        assert_eq!(bot.act(&obs), obs.legal.check_or_call());
+    }
+}
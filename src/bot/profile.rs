@@ -0,0 +1,128 @@
+use game::error::GameError;
+use super::rules::RuleBot;
+
+use std::io::BufRead;
+use std::str::FromStr;
+
+// a style of play for a RuleBot
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub vpip: f64, // the share of hands it plays when nobody has raised
+    pub aggression: f64,
+    pub bluff: f64,
+    pub tilt: f64,
+}
+
+impl Profile {
+    fn new(name: &str, vpip: f64, aggression: f64, bluff: f64, tilt: f64) -> Profile {
+        Profile { name: name.to_string(), vpip, aggression, bluff, tilt }
+    }
+
+    pub fn tight_passive() -> Profile {
+        Profile::new("tight-passive", 0.15, 0.2, 0.02, 0.1)
+    }
+
+    pub fn loose_aggressive() -> Profile {
+        Profile::new("loose-aggressive", 0.35, 0.8, 0.2, 0.3)
+    }
+
+    pub fn maniac() -> Profile {
+        Profile::new("maniac", 0.6, 1.0, 0.4, 0.5)
+    }
+
+    pub fn calling_station() -> Profile {
+        Profile::new("calling-station", 0.55, 0.1, 0.02, 0.2)
+    }
+
+    pub fn nit() -> Profile {
+        Profile::new("nit", 0.08, 0.4, 0.0, 0.0)
+    }
+
+    pub fn all() -> Vec<Profile> {
+        vec![Profile::tight_passive(), Profile::loose_aggressive(), Profile::maniac(),
+             Profile::calling_station(), Profile::nit()]
+    }
+
+    // one of the styles above, by name or a common short name
+    pub fn named(name: &str) -> Option<Profile> {
+        let name = match name.to_lowercase().as_str() {
+            "tp" => "tight-passive".to_string(),
+            "lag" => "loose-aggressive".to_string(),
+            "station" => "calling-station".to_string(),
+            other => other.to_string(),
+        };
+        Profile::all().into_iter().find(|p| p.name == name)
+    }
+
+    pub fn bot(&self, seed: u64) -> RuleBot {
+        RuleBot::new(seed)
+            .vpip(self.vpip)
+            .aggression(self.aggression)
+            .bluff(self.bluff)
+            .tilt(self.tilt)
+    }
+}
+
+// a style's name, optionally followed by changes to its parameters, as in
+// "nit bluff=0.1"
+impl FromStr for Profile {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Profile, GameError> {
+        let invalid = || GameError::InvalidProfile(s.to_string());
+        let mut words = s.split_whitespace();
+        let mut profile = words.next().and_then(Profile::named).ok_or_else(invalid)?;
+        for word in words {
+            let (key, value) = match word.find('=') {
+                Some(i) => (&word[..i], word[i + 1..].parse().map_err(|_| invalid())?),
+                None => return Err(invalid()),
+            };
+            match key {
+                "vpip" => profile.vpip = value,
+                "aggression" => profile.aggression = value,
+                "bluff" => profile.bluff = value,
+                "tilt" => profile.tilt = value,
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(profile)
+    }
+}
+
+// one profile per line, for each computer player in turn. Blank lines and
+// lines starting with '#' are skipped
+pub fn read_profiles<R: BufRead>(input: R) -> Result<Vec<Profile>, GameError> {
+    let mut profiles = Vec::new();
+    for line in input.lines() {
+        let line = line.map_err(|err| GameError::InvalidProfile(err.to_string()))?;
+        let line = line.trim();
+        if !line.is_empty() && !line.starts_with('#') {
+            profiles.push(line.parse()?);
+        }
+    }
+    Ok(profiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_a_config_file() {
+        let config = "# the regulars\ntp\n\nnit bluff=0.1 tilt=0.5\n";
+        let profiles = read_profiles(config.as_bytes()).unwrap();
+        let nit = Profile { bluff: 0.1, tilt: 0.5, ..Profile::nit() };
+        assert_eq!(profiles, vec![Profile::tight_passive(), nit]);
+    }
+
+    #[test]
+    fn bad_profiles_are_invalid() {
+        for line in &["shark", "nit bluff", "nit bluff=lots", "nit luck=0.5", ""] {
+            assert_eq!(line.parse::<Profile>(), Err(GameError::InvalidProfile(line.to_string())));
+        }
+        let config = "lag\nmaniac vpip=all\n";
+        assert_eq!(read_profiles(config.as_bytes()),
+                   Err(GameError::InvalidProfile("maniac vpip=all".to_string())));
+    }
+}
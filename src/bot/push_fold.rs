@@ -0,0 +1,159 @@
+use game::player::Command;
+use game::table::Street;
+use game::event::Event;
+use game::cash::CashGame;
+use game::strategy::{Strategy, Observation};
+use solver::push_fold::{Charts, Setup, MAX_PLAYERS};
+use super::hand_class::HandClass;
+use super::seeded_rng;
+
+use rand::{Rng, StdRng};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// charts are solved in the middle of a game, so with fewer deals and
+// iterations than the `pushfold` command uses. Deals are halved for every
+// player past two
+const DEALS: usize = 40000;
+const ITERATIONS: usize = 25;
+// in big blinds
+const THRESHOLD: f64 = 8.0;
+
+// chip-EV push/fold charts, solved the first time they're needed, for equal
+// stacks of a whole number of big blinds
+#[derive(Default)]
+pub struct ChartBook {
+    charts: HashMap<(usize, u32), Rc<Charts>>,
+}
+
+impl ChartBook {
+    pub fn new() -> ChartBook {
+        ChartBook::default()
+    }
+
+    pub fn charts(&mut self, players: usize, depth: u32) -> Option<Rc<Charts>> {
+        if let Some(charts) = self.charts.get(&(players, depth)) {
+            return Some(charts.clone());
+        }
+        let setup = Setup::new(players, depth as f64);
+        let deals = DEALS >> (players.clamp(2, MAX_PLAYERS) - 2);
+        let charts = Rc::new(Charts::solve(&setup, deals, ITERATIONS, depth as u64).ok()?);
+        self.charts.insert((players, depth), charts.clone());
+        Some(charts)
+    }
+}
+
+// a preflop decision as a push/fold chart sees it
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushFoldSpot {
+    // the players who hadn't folded when the first one acted. Players who
+    // fold first put nothing in without antes, so they can be left out
+    pub players: usize,
+    pub depth: u32, // the effective stack, in big blinds
+    pub history: String,
+}
+
+// the spot `obs` is in, if everyone so far has folded, gone all in or
+// called an all in
+pub fn spot(obs: &Observation) -> Option<PushFoldSpot> {
+    if obs.table.street != Street::PreFlop {
+        return None;
+    }
+    let mut stacks = Vec::new();
+    let mut history = String::new();
+    for event in &obs.history {
+        match *event {
+            Event::HandStarted { ref seats, .. } => {
+                stacks = seats.iter().map(|s| (s.1.clone(), s.2)).collect();
+            },
+            Event::Action { ref name, ref command, .. } => {
+                let all_in = obs.table.seats.iter().any(|s| s.name == *name && s.all_in);
+                let shoved = history.contains('r');
+                history.push(match *command {
+                    Command::Fold => 'f',
+                    Command::Raise(_) if all_in && !shoved => 'r',
+                    Command::Raise(_) if all_in => 'c',
+                    Command::Call if shoved => 'c',
+                    _ => return None,
+                });
+            },
+            _ => {},
+        }
+    }
+    let folds = history.len() - history.trim_start_matches('f').len();
+    let players = stacks.len().checked_sub(folds)?;
+    if folds + 2 > stacks.len() || players > MAX_PLAYERS {
+        return None;
+    }
+    // the largest stack it can win or lose against
+    let chips = obs.stack() + obs.table.seats[obs.seat].in_pot;
+    let biggest = stacks.iter()
+        .filter(|s| s.0 != obs.name())
+        .filter(|s| obs.table.seats.iter().any(|seat| seat.name == s.0 && seat.in_hand))
+        .map(|s| s.1)
+        .max()?;
+    let depth = chips.min(biggest) as f64 / obs.table.big_blind.max(1) as f64;
+    Some(PushFoldSpot {
+        players,
+        depth: depth.round().max(1.0) as u32,
+        history: history[folds..].to_string(),
+    })
+}
+
+// plays push or fold from the equilibrium charts once its stack is short,
+// and leaves everything else to another strategy
+pub struct PushFoldBot {
+    strategy: Box<dyn Strategy>,
+    book: Rc<RefCell<ChartBook>>,
+    threshold: f64,
+    rng: StdRng,
+}
+
+impl PushFoldBot {
+    pub fn new(strategy: Box<dyn Strategy>, book: Rc<RefCell<ChartBook>>,
+               seed: u64) -> PushFoldBot {
+        PushFoldBot { strategy, book, threshold: THRESHOLD, rng: seeded_rng(seed) }
+    }
+
+    // the stack, in big blinds, it starts pushing or folding below
+    pub fn threshold(mut self, threshold: f64) -> PushFoldBot {
+        self.threshold = threshold;
+        self
+    }
+
+    fn push_or_fold(&mut self, obs: &Observation) -> Option<Command> {
+        let chips = obs.stack() + obs.table.seats[obs.seat].in_pot;
+        if chips as f64 >= self.threshold * obs.table.big_blind as f64 {
+            return None;
+        }
+        let spot = spot(obs)?;
+        let charts = self.book.borrow_mut().charts(spot.players, spot.depth)?;
+        let class = HandClass::of(&obs.cards.0, &obs.cards.1);
+        let chance = charts.chance(&spot.history, &class)?;
+        let legal = &obs.legal;
+        Some(if self.rng.gen::<f64>() >= chance {
+            if legal.can_check { Command::Check } else { Command::Fold }
+        } else {
+            match legal.raise_to {
+                Some((_, max)) if !spot.history.contains('r') => {
+                    legal.raise(max).unwrap_or(Command::Call)
+                },
+                _ => legal.check_or_call(),
+            }
+        })
+    }
+}
+
+impl Strategy for PushFoldBot {
+    fn act(&mut self, obs: &Observation) -> Command {
+        match self.push_or_fold(obs) {
+            Some(command) => command,
+            None => self.strategy.act(obs),
+        }
+    }
+
+    fn rebuy(&mut self, cash: &CashGame) -> Option<i32> {
+        self.strategy.rebuy(cash)
+    }
+}
@@ -0,0 +1,259 @@
+use game::player::Command;
+use game::table::Street;
+use game::strategy::{Strategy, Observation};
+use game::table;
+use game::event::Event;
+use history::stats::{StatsTracker, PlayerStats};
+use super::strength::{chen_score, postflop_strength, against};
+use super::{raise_to, seeded_rng};
+
+use rand::{Rng, StdRng};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// opponents' stats are only believed after this many hands
+const MIN_HANDS: u32 = 30;
+// raising about one hand in seven, and betting or raising twice as often as
+// calling after the flop
+const TYPICAL_PFR: f64 = 0.15;
+const TYPICAL_AGGRESSION: f64 = 2.0;
+
+// plays by simple rules: preflop by Chen score, postflop by comparing the
+// estimated strength of its hand with the pot odds
+pub struct RuleBot {
+    // 0 plays almost any hand, 1 only premium ones, though `vpip` may set it
+    // beyond either end
+    pub tightness: f64,
+    // how keen it is to bet and raise its good hands rather than call
+    pub aggression: f64,
+    // how often it bets with nothing when checked to
+    pub bluff: f64,
+    // how much losing a big pot loosens it up and makes it bet more
+    pub tilt: f64,
+    tilted: f64,
+    // the hand it last played and its stack when the hand started
+    last_hand: Option<(u64, i32)>,
+    stats: Option<Rc<RefCell<StatsTracker>>>,
+    rng: StdRng,
+}
+
+impl RuleBot {
+    pub fn new(seed: u64) -> RuleBot {
+        RuleBot {
+            tightness: 0.5,
+            aggression: 0.5,
+            bluff: 0.1,
+            tilt: 0.0,
+            tilted: 0.0,
+            last_hand: None,
+            stats: None,
+            rng: seeded_rng(seed),
+        }
+    }
+
+    pub fn tightness(mut self, tightness: f64) -> RuleBot {
+        self.tightness = tightness;
+        self
+    }
+
+    pub fn aggression(mut self, aggression: f64) -> RuleBot {
+        self.aggression = aggression;
+        self
+    }
+
+    pub fn bluff(mut self, bluff: f64) -> RuleBot {
+        self.bluff = bluff;
+        self
+    }
+
+    // sets the tightness so that it plays roughly `vpip` of its hands when
+    // nobody has raised
+    pub fn vpip(mut self, vpip: f64) -> RuleBot {
+        let mut scores: Vec<i32> = Vec::new();
+        let deck = table::build_deck();
+        for (i, c1) in deck.iter().enumerate() {
+            for c2 in &deck[i + 1..] {
+                scores.push(chen_score(c1, c2));
+            }
+        }
+        scores.sort_by(|a, b| b.cmp(a));
+        let index = (vpip.max(0.0) * scores.len() as f64) as usize;
+        let score = scores[index.min(scores.len() - 1)] as f64;
+        // limps are allowed two points below the calling score
+        self.tightness = (score + 2.0 - 5.0) / 5.0;
+        self
+    }
+
+    pub fn tilt(mut self, tilt: f64) -> RuleBot {
+        self.tilt = tilt;
+        self
+    }
+
+    // calls lighter against players who raise and bet a lot, and tighter
+    // against ones who rarely do, once it has seen enough of their hands
+    pub fn stats(mut self, stats: Rc<RefCell<StatsTracker>>) -> RuleBot {
+        self.stats = Some(stats);
+        self
+    }
+
+    // the stats of whoever last bet or raised on this street
+    fn raiser_stats(&self, obs: &Observation) -> Option<PlayerStats> {
+        let raiser = obs.history.iter().rev()
+            .take_while(|event| !matches!(*event, Event::StreetDealt { .. }))
+            .filter_map(|event| match *event {
+                Event::Action { ref name, command: Command::Raise(_), .. } => Some(name),
+                _ => None,
+            })
+            .next()?;
+        let stats = self.stats.as_ref()?.borrow().player(raiser).cloned()?;
+        if stats.hands < MIN_HANDS { None } else { Some(stats) }
+    }
+
+    // on a new hand, tilts in proportion to the share of its stack it lost
+    // in the last one, calming down a little with every hand
+    fn update_tilt(&mut self, obs: &Observation) {
+        let seat = &obs.table.seats[obs.seat];
+        let hand = obs.table.hand_number;
+        let stack = seat.chips + seat.in_pot;
+        match self.last_hand {
+            Some((last, _)) if last == hand => return,
+            Some((_, last_stack)) if last_stack > 0 => {
+                let lost = (last_stack - stack).max(0) as f64 / last_stack as f64;
+                self.tilted = self.tilted / 2.0 + self.tilt * lost;
+            },
+            _ => {},
+        }
+        self.last_hand = Some((hand, stack));
+    }
+
+    // tightness, aggression and bluffing with any tilt taken into account
+    fn mood(&self) -> (f64, f64, f64) {
+        (self.tightness - self.tilted,
+         (self.aggression + self.tilted).min(1.0),
+         (self.bluff + self.tilted / 2.0).min(1.0))
+    }
+
+    fn preflop(&mut self, obs: &Observation) -> Command {
+        let legal = &obs.legal;
+        let (tightness, aggression, bluff) = self.mood();
+        let big_blind = obs.table.big_blind.max(1);
+        let score = chen_score(&obs.cards.0, &obs.cards.1) as f64;
+        // facing raises needs a better hand, more so for each doubling
+        let raised = (legal.current_bet as f64 / big_blind as f64).max(1.0).log2();
+        // from -1 against the tightest raisers to 1 against the loosest
+        let loose = match self.raiser_stats(obs).and_then(|stats| stats.pfr()) {
+            Some(pfr) if legal.current_bet > big_blind => {
+                ((pfr - TYPICAL_PFR) / TYPICAL_PFR).clamp(-1.0, 1.0)
+            },
+            _ => 0.0,
+        };
+        let call_at = 5.0 + 5.0 * tightness + 1.5 * raised - 1.5 * loose;
+        let raise_at = 9.0 + 3.0 * tightness + 1.5 * raised - loose;
+
+        let wants_raise = score >= raise_at && self.rng.gen::<f64>() < 0.5 + aggression / 2.0;
+        let steal = legal.current_bet <= big_blind && self.rng.gen::<f64>() < bluff / 2.0;
+        if wants_raise || steal {
+            // three big blinds to open, otherwise three times the bet
+            let amount = if legal.current_bet <= big_blind {
+                3 * big_blind + obs.pot() - obs.table.small_blind - big_blind
+            } else {
+                3 * legal.current_bet
+            };
+            if let Some(cmd) = raise_to(legal, amount) {
+                return cmd;
+            }
+        }
+        let cheap = legal.call_amount <= big_blind && score >= call_at - 2.0;
+        if legal.can_check || score >= call_at || cheap {
+            legal.check_or_call()
+        } else {
+            Command::Fold
+        }
+    }
+
+    fn postflop(&mut self, obs: &Observation) -> Command {
+        let legal = &obs.legal;
+        let (tightness, aggression, bluff) = self.mood();
+        let strength = postflop_strength(&obs.cards.0, &obs.cards.1, obs.board());
+        let equity = against(strength, obs.opponents());
+        let to_call = if legal.can_call { legal.call_amount } else { 0 };
+        let pot = obs.pot();
+        let pot_odds = to_call as f64 / (pot + to_call).max(1) as f64;
+
+        let value_at = 0.75 - 0.2 * aggression;
+        if equity >= value_at && self.rng.gen::<f64>() < 0.5 + aggression / 2.0 {
+            // bigger bets with better hands
+            let amount = legal.current_bet + ((pot + to_call) as f64 * (0.4 + 0.6 * equity)) as i32;
+            if let Some(cmd) = raise_to(legal, amount) {
+                return cmd;
+            }
+        }
+        if legal.can_check {
+            if self.rng.gen::<f64>() < bluff {
+                if let Some(cmd) = raise_to(legal, pot / 2) {
+                    return cmd;
+                }
+            }
+            return Command::Check;
+        }
+        let wild = match self.raiser_stats(obs).and_then(|stats| stats.aggression()) {
+            Some(aggression) => {
+                ((aggression - TYPICAL_AGGRESSION) / TYPICAL_AGGRESSION).clamp(-1.0, 1.0)
+            },
+            None => 0.0,
+        };
+        if equity >= pot_odds + 0.1 * tightness - 0.05 * wild {
+            Command::Call
+        } else {
+            Command::Fold
+        }
+    }
+}
+
+impl Strategy for RuleBot {
+    fn act(&mut self, obs: &Observation) -> Command {
+        self.update_tilt(obs);
+        if obs.table.street == Street::PreFlop {
+            self.preflop(obs)
+        } else {
+            self.postflop(obs)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::testing::{cash_table, cards};
+
+    // heads up, on a flop of `board`: a has `hole` on the button and faces
+    // a pot-sized bet from b
+    fn facing_a_pot_bet(hole: &str, board: &str) -> Observation {
+        let mut table = cash_table(&["a", "b"]);
+        table.set_button(0);
+        table.stack_deck(cards(&format!("{} 5h 6h {} 8d 9d", hole, board)));
+        table.start_hand().unwrap();
+        table.act(0, Command::Call).unwrap();
+        table.act(1, Command::Check).unwrap();
+        let pot = table.pot();
+        table.act(1, Command::Raise(pot)).unwrap();
+        table.observation(0).unwrap()
+    }
+
+    #[test]
+    fn folds_nothing_to_a_pot_sized_bet() {
+        let obs = facing_a_pot_bet("7c 2d", "Ks Qh 9s");
+        for seed in 0..20 {
+            assert_eq!(RuleBot::new(seed).act(&obs), Command::Fold);
+        }
+    }
+
+    #[test]
+    fn raises_a_set() {
+        let obs = facing_a_pot_bet("Ks Kd", "Kh 7c 2s");
+        match RuleBot::new(1).aggression(1.0).act(&obs) {
+            Command::Raise(x) => assert!(obs.legal.allows(&Command::Raise(x))),
+            cmd => panic!("played {}", cmd),
+        }
+    }
+}
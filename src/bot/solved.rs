@@ -0,0 +1,121 @@
+use game::player::Command;
+use game::table::Street;
+use game::event::Event;
+use game::error::GameError;
+use game::strategy::{Strategy, Observation};
+use solver::SolvedStrategy;
+use solver::limit::{self, Buckets};
+use super::seeded_rng;
+
+use rand::StdRng;
+
+// plays a strategy solved for the limit hold'em abstraction, heads up in a
+// fixed-limit game. Spots the strategy doesn't cover, like a third player
+// or more raises than it allowed for, are checked or called
+pub struct SolvedBot {
+    strategy: SolvedStrategy,
+    buckets: Buckets,
+    rng: StdRng,
+}
+
+impl SolvedBot {
+    pub fn new(strategy: SolvedStrategy, seed: u64) -> Result<SolvedBot, GameError> {
+        let count = match limit::parse_name(&strategy.game) {
+            Some(count) => count,
+            None => {
+                let reason = format!("a {} strategy can't play limit hold'em", strategy.game);
+                return Err(GameError::InvalidStrategy(reason));
+            },
+        };
+        Ok(SolvedBot { strategy, buckets: Buckets::new(count), rng: seeded_rng(seed) })
+    }
+}
+
+// the hand's betting in the letters the solver uses
+fn history(obs: &Observation) -> String {
+    let mut history = String::new();
+    for event in &obs.history {
+        match *event {
+            Event::StreetDealt { street, .. } if street != Street::PreFlop => history.push('/'),
+            Event::Action { ref command, .. } => match *command {
+                Command::Fold => history.push('f'),
+                Command::Check => history.push('k'),
+                Command::Call => history.push('c'),
+                Command::Raise(_) => history.push('r'),
+                _ => {},
+            },
+            _ => {},
+        }
+    }
+    history
+}
+
+impl Strategy for SolvedBot {
+    fn act(&mut self, obs: &Observation) -> Command {
+        let legal = &obs.legal;
+        let buckets = self.buckets.buckets(&obs.cards.0, &obs.cards.1, obs.board());
+        let key = limit::info_key(&buckets, &history(obs));
+        match self.strategy.choose(&key, &mut self.rng) {
+            Some(Command::Fold) if !legal.can_check => Command::Fold,
+            Some(Command::Raise(_)) => legal.raise_to
+                .and_then(|(min, _)| legal.raise(min))
+                .unwrap_or_else(|| legal.check_or_call()),
+            _ => legal.check_or_call(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::betting::BettingStructure;
+    use game::player::Player;
+    use game::table::Table;
+    use game::testing::cards;
+
+    // heads-up fixed limit, a on the button to act first holding `hole`
+    fn button_to_act(hole: &str) -> Observation {
+        let mut table = Table::build_table();
+        table.set_betting_structure(BettingStructure::FixedLimit);
+        for name in &["a", "b"] {
+            table.add_player(Player::with_chips(name.to_string(), false, 1000));
+        }
+        table.set_button(0);
+        table.stack_deck(cards(&format!("{} 2c 3d 4h 5s 6c 7d 8h", hole)));
+        table.start_hand().unwrap();
+        table.observation(0).unwrap()
+    }
+
+    // `strategy` saved and loaded back
+    fn saved(strategy: &SolvedStrategy) -> SolvedStrategy {
+        let mut out = Vec::new();
+        strategy.save(&mut out).unwrap();
+        SolvedStrategy::load(&out[..]).unwrap()
+    }
+
+    #[test]
+    fn plays_a_saved_strategy() {
+        let buckets = Buckets::new(3);
+        let raise = button_to_act("As Ah");
+        let fold = button_to_act("7c 2d");
+        let key = |obs: &Observation| {
+            limit::info_key(&buckets.buckets(&obs.cards.0, &obs.cards.1, obs.board()), "")
+        };
+        assert_ne!(key(&raise), key(&fold));
+        let mut strategy = SolvedStrategy::new("limit 3");
+        strategy.insert(&key(&raise), vec![(Command::Raise(1), 1.0), (Command::Fold, 0.0)]);
+        strategy.insert(&key(&fold), vec![(Command::Raise(1), 0.0), (Command::Fold, 1.0)]);
+
+        let mut bot = SolvedBot::new(saved(&strategy), 1).unwrap();
+        assert_eq!(bot.act(&raise), raise.legal.raise(raise.legal.raise_to.unwrap().0).unwrap());
+        assert_eq!(bot.act(&fold), Command::Fold);
+    }
+
+    #[test]
+    fn only_limit_strategies_can_be_played() {
+        match SolvedBot::new(saved(&SolvedStrategy::new("kuhn")), 1) {
+            Err(GameError::InvalidStrategy(_)) => {},
+            other => panic!("played {:?}", other.map(|_| ())),
+        }
+    }
+}
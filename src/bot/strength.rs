@@ -0,0 +1,136 @@
+use game::card::{Card, Hand, HandCategory};
+
+use std::rc::Rc;
+
+// Bill Chen's preflop score, from -1 for 72 offsuit to 20 for aces
+pub fn chen_score(c1: &Card, c2: &Card) -> i32 {
+    let (high, low) = if c1.val >= c2.val { (c1, c2) } else { (c2, c1) };
+    let mut score = match high.val {
+        14 => 10.0,
+        13 => 8.0,
+        12 => 7.0,
+        11 => 6.0,
+        v => v as f64 / 2.0,
+    };
+    if high.val == low.val {
+        score = (score * 2.0).max(5.0);
+    } else {
+        if high.suit == low.suit {
+            score += 2.0;
+        }
+        let gap = high.val - low.val - 1;
+        score -= match gap {
+            0 => 0.0,
+            1 => 1.0,
+            2 => 2.0,
+            3 => 4.0,
+            _ => 5.0,
+        };
+        if gap <= 1 && high.val < 12 {
+            score += 1.0;
+        }
+    }
+    score.ceil() as i32
+}
+
+// the Chen score scaled to between 0 and 1
+pub fn preflop_strength(c1: &Card, c2: &Card) -> f64 {
+    (chen_score(c1, c2) + 1) as f64 / 21.0
+}
+
+// a rough chance of beating one random hand at showdown, from the made hand
+// and any draws. Hands that only play the board count for little
+pub fn postflop_strength(c1: &Rc<Card>, c2: &Rc<Card>, board: &[Rc<Card>]) -> f64 {
+    let mut cards = vec![c1.clone(), c2.clone()];
+    cards.extend_from_slice(board);
+    let hand = match Hand::make_hand(cards) {
+        Ok(hand) => hand,
+        Err(_) => return 0.5,
+    };
+    let uses_hole = |val: i32| c1.val == val || c2.val == val;
+    let top = board.iter().map(|c| c.val).max().unwrap_or(0);
+    // values making up the pairs, trips or quads of the hand
+    let mut groups: Vec<i32> = hand.cards.iter()
+        .map(|c| c.val)
+        .filter(|&v| hand.cards.iter().filter(|c| c.val == v).count() > 1)
+        .collect();
+    groups.dedup();
+
+    let made: f64 = match hand.category {
+        HandCategory::High_Card => 0.1,
+        HandCategory::Pair => {
+            let val = groups.first().cloned().unwrap_or(0);
+            if c1.val == val && c2.val == val {
+                if val > top { 0.7 } else { 0.45 }
+            } else if uses_hole(val) {
+                if val == top { 0.6 } else { 0.4 }
+            } else {
+                0.15 // the board's pair
+            }
+        },
+        HandCategory::Two_Pair => match groups.iter().filter(|&&v| uses_hole(v)).count() {
+            0 => 0.2,
+            1 => 0.55,
+            _ => 0.75,
+        },
+        HandCategory::Three_of_a_Kind => {
+            let val = groups.first().cloned().unwrap_or(0);
+            if c1.val == val && c2.val == val {
+                0.88 // a set
+            } else if uses_hole(val) {
+                0.78
+            } else {
+                0.3
+            }
+        },
+        HandCategory::Straight => 0.85,
+        HandCategory::Flush => 0.9,
+        HandCategory::Full_House => 0.94,
+        HandCategory::Four_of_a_Kind => 0.98,
+        HandCategory::Straight_Flush => 1.0,
+    };
+    // the whole hand is on the board
+    let made = if board.len() == 5 && !hand.cards.iter().any(|c| c == c1 || c == c2) {
+        made.min(0.2)
+    } else {
+        made
+    };
+
+    let cards_to_come = 5 - board.len().min(5);
+    if cards_to_come == 0 || made >= 0.85 {
+        return made;
+    }
+    // about 2% per out per card to come
+    let outs = draw_outs(c1, c2, board);
+    let draw = (outs as f64 * 0.02 * cards_to_come as f64).min(0.5);
+    made + (1.0 - made) * draw
+}
+
+// outs to a flush or an open-ended straight using at least one hole card
+fn draw_outs(c1: &Card, c2: &Card, board: &[Rc<Card>]) -> u32 {
+    let mut outs = 0;
+    for &suit in &[c1.suit, c2.suit] {
+        let count = board.iter().filter(|c| c.suit == suit).count()
+            + [c1, c2].iter().filter(|c| c.suit == suit).count();
+        if count == 4 {
+            outs += 9;
+            break;
+        }
+    }
+    let mut vals: Vec<i32> = board.iter().map(|c| c.val).chain(vec![c1.val, c2.val]).collect();
+    vals.sort();
+    vals.dedup();
+    let open_ended = vals.windows(4).any(|w| {
+        w[3] - w[0] == 3 && w[3] < 14 && w.iter().any(|&v| v == c1.val || v == c2.val)
+    });
+    if open_ended {
+        outs += 8;
+    }
+    outs
+}
+
+// the chance of beating `opponents` hands at once, given the chance of
+// beating one
+pub fn against(strength: f64, opponents: usize) -> f64 {
+    strength.powf((opponents.max(1) as f64).powf(0.7))
+}
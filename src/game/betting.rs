@@ -0,0 +1,73 @@
+use super::player::Command;
+
+// fixed limit caps each street at a bet and three raises, this many in all
+pub const RAISE_CAP: u32 = 4;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BettingStructure {
+    NoLimit,
+    PotLimit,
+    // bets are one big blind before the turn and two from the turn on
+    FixedLimit,
+}
+
+// what the seat to act may do. Bet sizes are "raise to" amounts, i.e. the
+// seat's total bet on this street after the raise
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegalActions {
+    pub can_fold: bool,
+    pub can_check: bool,
+    pub can_call: bool,
+    pub call_amount: i32,
+    pub current_bet: i32,
+    pub raise_to: Option<(i32, i32)>,
+}
+
+impl LegalActions {
+    // whether a raise is opening the betting on this street
+    pub fn is_bet(&self) -> bool {
+        self.current_bet == 0
+    }
+
+    pub fn can_raise_to(&self, amount: i32) -> bool {
+        self.raise_to.is_some_and(|(min, max)| min <= amount && amount <= max)
+    }
+
+    // the command that raises to `amount`, if that's allowed
+    pub fn raise(&self, amount: i32) -> Option<Command> {
+        if self.can_raise_to(amount) {
+            Some(Command::Raise(amount - self.current_bet))
+        } else {
+            None
+        }
+    }
+
+    // the passive option: check when possible, call otherwise
+    pub fn check_or_call(&self) -> Command {
+        if self.can_check {
+            Command::Check
+        } else {
+            Command::Call
+        }
+    }
+
+    // what a player who doesn't answer does
+    pub fn check_or_fold(&self) -> Command {
+        if self.can_check {
+            Command::Check
+        } else {
+            Command::Fold
+        }
+    }
+
+    pub fn allows(&self, cmd: &Command) -> bool {
+        match *cmd {
+            Command::Fold | Command::Leave => self.can_fold,
+            Command::Check => self.can_check,
+            Command::Call => self.can_call,
+            Command::Raise(x) => self.can_raise_to(self.current_bet + x),
+            Command::PostBlind => false,
+        }
+    }
+}
@@ -3,8 +3,12 @@ use std::collections::{HashMap, BTreeMap};
 use std::rc::Rc;
 use std::iter::FromIterator;
 use std::cmp::Ordering;
+use std::str::FromStr;
+
+use super::error::GameError;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Suit {
     Spades,
     Hearts,
@@ -14,7 +18,7 @@ pub enum Suit {
 
 impl fmt::Display for Suit {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", format!("{:?}", *self))
+        write!(f, "{:?}", *self)
     }
 }
 
@@ -40,9 +44,76 @@ fn display_val(val: i32) -> String {
     }
 }
 
+impl Card {
+    // two character name used by hand histories, e.g. "As" or "Td"
+    pub fn short(&self) -> String {
+        let val = match self.val {
+            10 => 'T',
+            11 => 'J',
+            12 => 'Q',
+            13 => 'K',
+            14 => 'A',
+            v => (b'0' + v as u8) as char,
+        };
+        let suit = match self.suit {
+            Suit::Spades => 's',
+            Suit::Hearts => 'h',
+            Suit::Clubs => 'c',
+            Suit::Diamonds => 'd',
+        };
+        format!("{}{}", val, suit)
+    }
+}
+
+// cards are written as their short names, e.g. "As"
+#[cfg(feature = "serde")]
+impl ::serde::Serialize for Card {
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.short())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde::Deserialize<'de> for Card {
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Card, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(::serde::de::Error::custom)
+    }
+}
+
+// the inverse of `Card::short`
+impl FromStr for Card {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Card, GameError> {
+        let mut chars = s.chars();
+        let (val, suit) = match (chars.next(), chars.next(), chars.next()) {
+            (Some(val), Some(suit), None) => (val, suit),
+            _ => return Err(GameError::InvalidCard(s.to_string())),
+        };
+        let val = match val.to_ascii_uppercase() {
+            'T' => 10,
+            'J' => 11,
+            'Q' => 12,
+            'K' => 13,
+            'A' => 14,
+            v @ '2'..='9' => v as i32 - '0' as i32,
+            _ => return Err(GameError::InvalidCard(s.to_string())),
+        };
+        let suit = match suit.to_ascii_lowercase() {
+            's' => Suit::Spades,
+            'h' => Suit::Hearts,
+            'c' => Suit::Clubs,
+            'd' => Suit::Diamonds,
+            _ => return Err(GameError::InvalidCard(s.to_string())),
+        };
+        Ok(Card { suit, val })
+    }
+}
+
 impl PartialOrd for Card {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.val.cmp(&other.val))
+        Some(self.cmp(other))
     }
 }
 
@@ -52,7 +123,8 @@ impl Ord for Card {
     }
 }
 
-#[derive(Debug, PartialOrd, Ord, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialOrd, Ord, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[allow(non_camel_case_types)]
 pub enum HandCategory {
     High_Card,
@@ -73,16 +145,17 @@ impl fmt::Display for HandCategory {
 }
 
 // invariant : hand composed of exactly 5 cards
-#[derive(Debug, PartialOrd, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Hand {
     pub cards: Vec<Rc<Card>>,
     pub category: HandCategory, 
 }
 
 impl Hand {
-    pub fn make_hand(mut cards: Vec<Rc<Card>>) -> Result<Hand, ()> {
+    pub fn make_hand(mut cards: Vec<Rc<Card>>) -> Result<Hand, GameError> {
         if (cards.len() as i32) < 5 {
-            return Err(())
+            return Err(GameError::NotEnoughCards(cards.len()))
         }
 
         // organize cards by suits
@@ -91,8 +164,8 @@ impl Hand {
         let mut vals = BTreeMap::new();
         
         for card in &cards {
-            let ref suit = card.suit;
-            let ref val = card.val;
+            let suit = &card.suit;
+            let val = &card.val;
 
             if !suits.contains_key(suit) {
                 suits.insert(*suit, Vec::new());
@@ -117,33 +190,30 @@ impl Hand {
         }
 
         let mut straight = None;
-        let mut distinct_vals = Vec::from_iter(vals.keys().map(|k| *k));
-        if flush.is_some() {
-            distinct_vals = Vec::from_iter(flush.unwrap().1.iter().map(|c| c.val));
+        let mut distinct_vals = Vec::from_iter(vals.keys().cloned());
+        if let Some(flush) = flush {
+            distinct_vals = Vec::from_iter(flush.1.iter().map(|c| c.val));
         }
         // allow ace to be both 1 and 14
         if distinct_vals[0] == 2 && distinct_vals[distinct_vals.len() - 1] == 14 {
             distinct_vals.insert(0, 1);
         }
         if (distinct_vals.len() as i32) >= 5 {
+            // the highest run of five
             let mut range = None;
             for i in 0..(distinct_vals.len() - 4) {
                 if distinct_vals[i] == distinct_vals[i + 4] - 4 {
-                    if range.is_none() {
-                        range = Some((distinct_vals[i], distinct_vals[i + 4]));
-                    }
-                    range = Some((range.unwrap().0, distinct_vals[i + 4]));
+                    range = Some((distinct_vals[i], distinct_vals[i + 4]));
                 }
             }
-            if range.is_some() {
-                let (start, end) = range.unwrap();
+            if let Some((start, end)) = range {
                 let mut sequence = Vec::new();
                 for mut i in start..(end + 1) {
                     if i == 1 { i = 14; } // ace
                     let mut card = vals[&i][0].clone();
-                    if flush.is_some() {
+                    if let Some(flush) = flush {
                         for c in &vals[&i] {
-                            if c.suit == *flush.unwrap().0 {
+                            if c.suit == *flush.0 {
                                 card = c.clone();
                             }
                         }
@@ -162,42 +232,47 @@ impl Hand {
             if count == 4 {
                 quad = Some(val);
             } else if count == 3 {
+                // a lower set of trips can make the pair of a full house
+                if let Some(lower) = trip {
+                    pairs.push(lower);
+                }
                 trip = Some(val);
             } else if count == 2 {
                 pairs.push(val);
             }
         }
+        pairs.sort_by_key(|p| p.0);
 
         // label hand by category
         let mut hand : Vec<Rc<Card>> = Vec::new();
         let mut category = HandCategory::High_Card;
         let mut seen = Vec::new();
 
-        if straight.is_some() && flush.is_some() {
-            hand.extend_from_slice(&straight.unwrap());
+        if let (Some(straight), Some(_)) = (&straight, flush) {
+            hand.extend_from_slice(straight);
             category = HandCategory::Straight_Flush;
         
-        } else if quad.is_some() {
-            hand.extend_from_slice(quad.unwrap().1);
-            seen.push(*quad.unwrap().0);
+        } else if let Some(quad) = quad {
+            hand.extend_from_slice(quad.1);
+            seen.push(*quad.0);
             category = HandCategory::Four_of_a_Kind;
 
-        } else if trip.is_some() && !pairs.is_empty() {
-            hand.extend_from_slice(trip.unwrap().1);
-            hand.extend_from_slice(pairs[pairs.len()-1].1);
+        } else if let (Some(trip), false) = (trip, pairs.is_empty()) {
+            hand.extend_from_slice(trip.1);
+            hand.extend_from_slice(&pairs[pairs.len()-1].1[..2]);
             category = HandCategory::Full_House;
 
-        } else if flush.is_some() {
-            hand.extend_from_slice(flush.unwrap().1);
+        } else if let Some(flush) = flush {
+            hand.extend_from_slice(&flush.1[flush.1.len()-5..]);
             category = HandCategory::Flush;
 
-        } else if straight.is_some() {
-            hand.extend_from_slice(&straight.unwrap());
+        } else if let Some(straight) = &straight {
+            hand.extend_from_slice(straight);
             category = HandCategory::Straight;
 
-        } else if trip.is_some() {
-            hand.extend_from_slice(trip.unwrap().1);
-            seen.push(*trip.unwrap().0);
+        } else if let Some(trip) = trip {
+            hand.extend_from_slice(trip.1);
+            seen.push(*trip.0);
             category = HandCategory::Three_of_a_Kind;            
 
         } else if pairs.len() as i32 >= 2 {
@@ -212,11 +287,11 @@ impl Hand {
             category = HandCategory::Pair;
 
         } else {
-            cards.truncate(5);
-            hand.extend_from_slice(&cards);
+            hand.extend_from_slice(&cards[cards.len()-5..]);
         }
 
-        for card in &cards {
+        // the best kickers
+        for card in cards.iter().rev() {
             if (hand.len() as i32) >= 5 { break; }
             let mut has_val = false;
             for val in &seen {
@@ -227,7 +302,7 @@ impl Hand {
             }
         }
         hand.sort();
-        Ok(Hand {cards: hand, category: category })
+        Ok(Hand { cards: hand, category })
     }
 }
 
@@ -245,17 +320,187 @@ impl fmt::Display for Hand {
     }
 }
 
+impl PartialOrd for Hand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hand {
+    // card values from the most to the least important for breaking ties:
+    // bigger groups first, then higher values, with a wheel's ace played low
+    fn ranks(&self) -> Vec<i32> {
+        let mut counts: BTreeMap<i32, usize> = BTreeMap::new();
+        for card in &self.cards {
+            *counts.entry(card.val).or_insert(0) += 1;
+        }
+        let mut groups: Vec<(usize, i32)> = counts.into_iter().map(|(v, n)| (n, v)).collect();
+        groups.sort_by(|a, b| b.cmp(a));
+        let mut ranks: Vec<i32> = groups.iter()
+            .flat_map(|&(n, v)| ::std::iter::repeat_n(v, n))
+            .collect();
+        if ranks == [14, 5, 4, 3, 2] {
+            ranks = vec![5, 4, 3, 2, 1];
+        }
+        ranks
+    }
+}
+
 impl Ord for Hand {
     fn cmp(&self, other: &Self) -> Ordering {
-        let ord = self.category.cmp(&other.category);
-        if ord == Ordering::Equal {
-            for i in 0..5 {
-                let card_ord = self.cards[i].cmp(&other.cards[i]);
-                if card_ord != Ordering::Equal {
-                    return card_ord;
-                }
-            }
+        self.category.cmp(&other.category).then_with(|| self.ranks().cmp(&other.ranks()))
+    }
+}
+
+// the best five card hand in `cards` as a number, so that better hands score
+// higher and equal ones the same. Much quicker than making the hands when
+// only comparing them
+pub fn hand_value(cards: &[Rc<Card>]) -> u32 {
+    let mut counts = [0; 15];
+    let mut suits = [0u32; 4];
+    let mut vals = 0u32;
+    for card in cards {
+        counts[card.val as usize] += 1;
+        suits[card.suit as usize] |= 1 << card.val;
+        vals |= 1 << card.val;
+    }
+    // the top `n` values in `mask`, four bits each
+    let top = |mask: u32, n: usize| {
+        (2..15).rev().filter(|v| mask >> v & 1 == 1).take(n).fold(0, |ranks, v| ranks << 4 | v)
+    };
+    // the high card of the best run of five, with the ace also low
+    let straight = |mask: u32| {
+        let mask = mask | (mask >> 14 & 1) << 1;
+        (5..15).rev().find(|high| mask >> (high - 4) & 0b11111 == 0b11111)
+    };
+    let value = |category: HandCategory, ranks: u32| (category as u32) << 20 | ranks;
+    let with = |n: u32| (2..15).rev().filter(|&v| counts[v as usize] >= n).collect::<Vec<u32>>();
+    let (quads, trips, pairs) = (with(4), with(3), with(2));
+
+    let flush = suits.iter().find(|s| s.count_ones() >= 5).cloned();
+    if let Some(high) = flush.and_then(straight) {
+        return value(HandCategory::Straight_Flush, high);
+    }
+    if let Some(&quad) = quads.first() {
+        return value(HandCategory::Four_of_a_Kind, quad << 4 | top(vals & !(1 << quad), 1));
+    }
+    if let Some(&trip) = trips.first() {
+        if let Some(&pair) = pairs.iter().find(|&&p| p != trip) {
+            return value(HandCategory::Full_House, trip << 4 | pair);
+        }
+    }
+    if let Some(flush) = flush {
+        return value(HandCategory::Flush, top(flush, 5));
+    }
+    if let Some(high) = straight(vals) {
+        return value(HandCategory::Straight, high);
+    }
+    if let Some(&trip) = trips.first() {
+        return value(HandCategory::Three_of_a_Kind, trip << 8 | top(vals & !(1 << trip), 2));
+    }
+    match pairs[..] {
+        [high, low, ..] => {
+            let kicker = top(vals & !(1 << high) & !(1 << low), 1);
+            value(HandCategory::Two_Pair, high << 8 | low << 4 | kicker)
+        },
+        [pair] => value(HandCategory::Pair, pair << 12 | top(vals & !(1 << pair), 3)),
+        [] => value(HandCategory::High_Card, top(vals, 5)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a card written like "Ah" or "Tc"
+    fn card(short: &str) -> Rc<Card> {
+        let mut chars = short.chars();
+        let val = match chars.next().unwrap() {
+            'T' => 10,
+            'J' => 11,
+            'Q' => 12,
+            'K' => 13,
+            'A' => 14,
+            digit => digit.to_digit(10).unwrap() as i32,
+        };
+        let suit = match chars.next().unwrap() {
+            's' => Suit::Spades,
+            'h' => Suit::Hearts,
+            'c' => Suit::Clubs,
+            _ => Suit::Diamonds,
+        };
+        Rc::new(Card { suit, val })
+    }
+
+    fn hand(cards: &str) -> Hand {
+        Hand::make_hand(cards.split_whitespace().map(card).collect()).unwrap()
+    }
+
+    fn vals(hand: &Hand) -> Vec<i32> {
+        let mut vals: Vec<i32> = hand.cards.iter().map(|c| c.val).collect();
+        vals.sort_by(|a, b| b.cmp(a));
+        vals
+    }
+
+    #[test]
+    fn wheel_is_the_lowest_straight() {
+        let wheel = hand("Ah 2c 3d 4s 5h Kc Kd");
+        assert_eq!(wheel.category, HandCategory::Straight);
+        assert_eq!(vals(&wheel), vec![14, 5, 4, 3, 2]);
+        assert!(wheel < hand("2h 3c 4d 5s 6h Kc Kd"));
+        assert!(wheel > hand("Ah Ac Ad 9s 5h Kc 2d"));
+    }
+
+    #[test]
+    fn highest_run_makes_the_straight() {
+        let straight = hand("4h 5c 6d 7s 8h 9c 2d");
+        assert_eq!(vals(&straight), vec![9, 8, 7, 6, 5]);
+    }
+
+    #[test]
+    fn two_sets_make_a_full_house() {
+        let boat = hand("9h 9c 9d 4s 4h 4c Kd");
+        assert_eq!(boat.category, HandCategory::Full_House);
+        assert_eq!(vals(&boat), vec![9, 9, 9, 4, 4]);
+        assert!(boat > hand("8h 8c 8d As Ah 2c 3d"));
+    }
+
+    #[test]
+    fn flush_of_six_or_more_plays_the_top_five() {
+        let flush = hand("2h 5h 7h 9h Jh Kh Ac");
+        assert_eq!(flush.category, HandCategory::Flush);
+        assert_eq!(vals(&flush), vec![13, 11, 9, 7, 5]);
+        assert!(flush > hand("3h 5h 7h 9h Jh Qh Ac"));
+    }
+
+    #[test]
+    fn kickers_break_ties() {
+        let ace_kicker = hand("Kh Kc Ad 7s 5h 3c 2d");
+        assert_eq!(vals(&ace_kicker), vec![14, 13, 13, 7, 5]);
+        assert!(ace_kicker > hand("Kd Ks Qd 7c 5d 3s 2h"));
+        // high cards are compared down to the fifth
+        assert!(hand("Ah Qc 9d 7s 4h 3c 2d") < hand("Ad Qs 9c 7d 5h 3s 2h"));
+        // the same five values split the pot
+        let other = hand("Kd Ks Ac 7d 5c 4c 2h");
+        assert_eq!(ace_kicker.cmp(&other), Ordering::Equal);
+    }
+
+    #[test]
+    fn bad_card_names_are_rejected() {
+        assert_eq!("Td".parse::<Card>().ok(), Some(Card { suit: Suit::Diamonds, val: 10 }));
+        for name in &["", "A", "Ax", "1s", "10s", "Ass"] {
+            assert!(name.parse::<Card>().is_err(), "{} parsed", name);
         }
-        ord
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn cards_serialize_as_their_short_names() {
+        let card = Card { suit: Suit::Spades, val: 14 };
+        let json = ::serde_json::to_string(&card).unwrap();
+        assert_eq!(json, "\"As\"");
+        assert_eq!(::serde_json::from_str::<Card>(&json).unwrap(), card);
+        assert!(::serde_json::from_str::<Card>("\"Zz\"").is_err());
+        assert!(::serde_json::from_str::<Card>("14").is_err());
     }
 }
@@ -0,0 +1,42 @@
+use super::error::GameError;
+
+use std::cmp;
+
+const MIN_BUY_IN: i32 = 40;
+const MAX_BUY_IN: i32 = 200;
+
+// limits that apply to a table played for cash rather than as a freezeout
+#[derive(Debug, Clone, PartialEq)]
+pub struct CashGame {
+    pub min_buy_in: i32,
+    pub max_buy_in: i32,
+}
+
+impl CashGame {
+    pub fn new(min_buy_in: i32, max_buy_in: i32) -> Result<CashGame, GameError> {
+        if min_buy_in <= 0 || min_buy_in > max_buy_in {
+            return Err(GameError::InvalidSetup(
+                format!("buy-ins of {} to {} chips", min_buy_in, max_buy_in)));
+        }
+        Ok(CashGame { min_buy_in, max_buy_in })
+    }
+
+    pub fn is_valid_buy_in(&self, amount: i32) -> bool {
+        amount >= self.min_buy_in && amount <= self.max_buy_in
+    }
+
+    // a top up may bring a stack up to, but never over, the maximum buy-in
+    pub fn is_valid_top_up(&self, chips: i32, amount: i32) -> bool {
+        amount > 0 && chips + amount <= self.max_buy_in
+    }
+
+    pub fn max_top_up(&self, chips: i32) -> i32 {
+        cmp::max(0, self.max_buy_in - chips)
+    }
+}
+
+impl Default for CashGame {
+    fn default() -> CashGame {
+        CashGame { min_buy_in: MIN_BUY_IN, max_buy_in: MAX_BUY_IN }
+    }
+}
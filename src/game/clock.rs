@@ -0,0 +1,98 @@
+use std::cell::Cell;
+use std::fmt;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+// tells the time to anything with a time limit
+pub trait Clock {
+    // the time since some fixed moment
+    fn now(&self) -> Duration;
+}
+
+// the real time
+#[derive(Debug, Clone)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock { start: Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+// a clock that only moves when told to, so slow players can be tried out
+// without waiting for them. Clones share the time, so one can be kept to
+// wind on while the dealer reads another
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Rc<Cell<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock::default()
+    }
+
+    pub fn advance(&self, time: Duration) {
+        self.now.set(self.now.get() + time);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+// every decision gets `per_action`, and any time taken over that comes out
+// of the player's bank, which starts at `bank` and isn't topped up. A
+// player who runs out checks or folds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeLimits {
+    pub per_action: Duration,
+    pub bank: Duration,
+}
+
+// when a decision is due, by the dealer's clock, so a player can keep an
+// eye on it while they think
+#[derive(Clone)]
+pub struct Deadline {
+    clock: Rc<dyn Clock>,
+    due: Duration,
+}
+
+impl Deadline {
+    // `left` from now
+    pub fn new(clock: Rc<dyn Clock>, left: Duration) -> Deadline {
+        let due = clock.now() + left;
+        Deadline { clock, due }
+    }
+
+    pub fn left(&self) -> Duration {
+        self.due.saturating_sub(self.clock.now())
+    }
+
+    // an answer now would be too late
+    pub fn passed(&self) -> bool {
+        self.clock.now() > self.due
+    }
+}
+
+impl fmt::Debug for Deadline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Deadline").field("left", &self.left()).finish()
+    }
+}
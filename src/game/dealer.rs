@@ -0,0 +1,429 @@
+use super::table::Table;
+use super::player::{Player, Command};
+use super::error::GameError;
+use super::strategy::Strategy;
+use super::clock::{Clock, Deadline, SystemClock, TimeLimits};
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+
+// runs a table by asking each seat's strategy for its moves, timing them if
+// there are time limits
+pub struct Dealer {
+    table: Table,
+    strategies: HashMap<String, Box<dyn Strategy>>,
+    limits: Option<TimeLimits>,
+    clock: Rc<dyn Clock>,
+}
+
+impl Dealer {
+    pub fn new(table: Table) -> Dealer {
+        Dealer {
+            table,
+            strategies: HashMap::new(),
+            limits: None,
+            clock: Rc::new(SystemClock::new()),
+        }
+    }
+
+    pub fn table(&self) -> &Table {
+        &self.table
+    }
+
+    pub fn table_mut(&mut self) -> &mut Table {
+        &mut self.table
+    }
+
+    pub fn seat(&mut self, player: Player, strategy: Box<dyn Strategy>) {
+        self.strategies.insert(player.name.clone(), strategy);
+        self.table.add_player(player);
+    }
+
+    pub fn buy_in(&mut self, player: Player, amount: i32,
+                  strategy: Box<dyn Strategy>) -> Result<(), GameError> {
+        let name = player.name.clone();
+        self.table.buy_in(player, amount)?;
+        self.strategies.insert(name, strategy);
+        Ok(())
+    }
+
+    // replaces the strategy of a seated player, e.g. after loading a save
+    pub fn set_strategy(&mut self, name: &str, strategy: Box<dyn Strategy>) {
+        self.strategies.insert(name.to_string(), strategy);
+    }
+
+    // banks start full, apart from what's been used of them at the table
+    // so far, as in a resumed game
+    pub fn set_time_limits(&mut self, limits: TimeLimits) {
+        self.limits = Some(limits);
+    }
+
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = Rc::from(clock);
+    }
+
+    // what's left of `name`'s time bank, if there are time limits
+    pub fn time_bank(&self, name: &str) -> Option<Duration> {
+        self.limits.map(|limits| self.table.time_bank(name).unwrap_or(limits.bank))
+    }
+
+    // whether a seat has stopped the game, leaving the hand where it is
+    pub fn has_quit(&self) -> bool {
+        self.strategies.values().any(|strategy| strategy.has_quit())
+    }
+
+    // lets the seat to act make its move. An illegal move folds the hand
+    // and is returned as the error; seats without a strategy check or call.
+    // With time limits the strategy is told when its move is due. Nothing
+    // stops it thinking for longer, but a move that comes after the time
+    // limit and what's left of the player's bank is thrown away and they
+    // check or fold instead
+    pub fn play_turn(&mut self) -> Result<(), GameError> {
+        let seat = match self.table.to_act() {
+            Some(seat) => seat,
+            None => return Ok(()),
+        };
+        let name = self.table.player(seat).name.clone();
+        let mut observation = self.table.observation(seat)?;
+        let bank = self.time_bank(&name);
+        observation.deadline = self.limits.map(|limits| {
+            Deadline::new(self.clock.clone(), limits.per_action + bank.unwrap_or_default())
+        });
+        let started = self.clock.now();
+        let cmd = match self.strategies.get_mut(&name) {
+            Some(strategy) => {
+                let cmd = strategy.act(&observation);
+                if strategy.has_quit() {
+                    return Ok(());
+                }
+                cmd
+            },
+            None => observation.legal.check_or_call(),
+        };
+        if let (Some(limits), Some(bank)) = (self.limits, bank) {
+            let taken = self.clock.now().saturating_sub(started);
+            let over = taken.saturating_sub(limits.per_action);
+            self.table.set_time_bank(&name, bank.saturating_sub(over));
+            if over > bank {
+                return self.table.time_out(seat);
+            }
+        }
+        if let Err(err) = self.table.act(seat, cmd) {
+            self.table.act(seat, Command::Fold)?;
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    // deals a hand and plays it to the end, folding any illegal moves, or
+    // until a seat quits
+    pub fn play_hand(&mut self) -> Result<(), GameError> {
+        self.table.start_hand()?;
+        while self.table.to_act().is_some() && !self.has_quit() {
+            match self.play_turn() {
+                Ok(()) | Err(GameError::IllegalAction(_)) => {},
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    // busted players rebuy if their strategy wants to
+    pub fn offer_rebuys(&mut self) {
+        let cash = match self.table.cash_game() {
+            Some(cash) => cash.clone(),
+            None => return,
+        };
+        let busted: Vec<String> = self.table.players().iter()
+            .filter(|p| p.is_busted())
+            .map(|p| p.name.clone())
+            .collect();
+        for name in busted {
+            let amount = self.strategies.get_mut(&name).and_then(|s| s.rebuy(&cash));
+            if let Some(amount) = amount {
+                let _ = self.table.rebuy(&name, amount);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::cash::CashGame;
+    use game::clock::ManualClock;
+    use game::event::{Event, EventLog, View};
+    use game::strategy::Observation;
+    use game::testing::cash_table;
+
+    use std::cell::RefCell;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    // plays its commands in turn, then checks or calls, keeping what it was
+    // shown each time
+    #[derive(Default)]
+    struct Scripted {
+        commands: Vec<Command>,
+        seen: Rc<RefCell<Vec<Observation>>>,
+        quit: bool,
+        rebuy: Option<i32>,
+    }
+
+    impl Strategy for Scripted {
+        fn act(&mut self, obs: &Observation) -> Command {
+            self.seen.borrow_mut().push(obs.clone());
+            if self.commands.is_empty() {
+                return obs.legal.check_or_call();
+            }
+            self.commands.remove(0)
+        }
+
+        fn rebuy(&mut self, _cash: &CashGame) -> Option<i32> {
+            self.rebuy
+        }
+
+        fn has_quit(&self) -> bool {
+            self.quit
+        }
+    }
+
+    // a, b and c, all playing `Scripted` strategies, and the table's events
+    fn dealer() -> (Dealer, Rc<RefCell<EventLog>>) {
+        let mut dealer = Dealer::new(cash_table(&["a", "b", "c"]));
+        for name in &["a", "b", "c"] {
+            dealer.set_strategy(name, Box::new(Scripted::default()));
+        }
+        let log = Rc::new(RefCell::new(EventLog::default()));
+        dealer.table_mut().add_observer(View::Omniscient, log.clone());
+        (dealer, log)
+    }
+
+    fn actions(log: &RefCell<EventLog>) -> Vec<(String, Command)> {
+        log.borrow().events.iter()
+            .filter_map(|event| match *event {
+                Event::Action { ref name, ref command, .. } => {
+                    Some((name.clone(), command.clone()))
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn timed_out(log: &RefCell<EventLog>) -> Vec<String> {
+        log.borrow().events.iter()
+            .filter_map(|event| match *event {
+                Event::TimedOut { ref name } => Some(name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // the name of the player to act, after giving them `strategy`
+    fn next_plays<S: Strategy + 'static>(dealer: &mut Dealer, strategy: S) -> String {
+        let seat = dealer.table().to_act().unwrap();
+        let name = dealer.table().player(seat).name.clone();
+        dealer.set_strategy(&name, Box::new(strategy));
+        name
+    }
+
+    #[test]
+    fn each_seat_plays_its_strategy() {
+        let (mut dealer, log) = dealer();
+        dealer.table_mut().start_hand().unwrap();
+        let folder = next_plays(&mut dealer, Scripted {
+            commands: vec![Command::Fold],
+            ..Scripted::default()
+        });
+        while dealer.table().to_act().is_some() {
+            dealer.play_turn().unwrap();
+        }
+        let actions = actions(&log);
+        assert_eq!(actions[0], (folder, Command::Fold));
+        assert!(actions[1..].iter().all(|a| a.1 == Command::Call || a.1 == Command::Check));
+    }
+
+    #[test]
+    fn an_illegal_move_folds() {
+        let (mut dealer, log) = dealer();
+        dealer.table_mut().start_hand().unwrap();
+        let name = next_plays(&mut dealer, Scripted {
+            commands: vec![Command::Raise(1)],
+            ..Scripted::default()
+        });
+        match dealer.play_turn() {
+            Err(GameError::IllegalAction(_)) => {},
+            other => panic!("played {:?}", other),
+        }
+        assert_eq!(actions(&log), vec![(name, Command::Fold)]);
+    }
+
+    #[test]
+    fn a_seat_that_quits_leaves_the_hand_where_it_is() {
+        let (mut dealer, log) = dealer();
+        dealer.table_mut().start_hand().unwrap();
+        let seat = dealer.table().to_act();
+        next_plays(&mut dealer, Scripted { quit: true, ..Scripted::default() });
+        dealer.play_turn().unwrap();
+        assert!(dealer.has_quit());
+        assert_eq!(dealer.table().to_act(), seat);
+        assert!(actions(&log).is_empty());
+    }
+
+    #[test]
+    fn strategies_only_see_their_own_cards() {
+        let (mut dealer, _) = dealer();
+        let seen = Rc::default();
+        dealer.table_mut().start_hand().unwrap();
+        let strategy = Scripted { seen: Rc::clone(&seen), ..Scripted::default() };
+        let name = next_plays(&mut dealer, strategy);
+        dealer.play_turn().unwrap();
+        let seen = seen.borrow();
+        let dealt: Vec<&String> = seen[0].history.iter()
+            .filter_map(|event| match *event {
+                Event::HoleCards { ref name, .. } => Some(name),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(dealt, vec![&name]);
+        assert_eq!(seen[0].name(), name);
+    }
+
+    #[test]
+    fn busted_players_rebuy_if_their_strategy_wants_to() {
+        let mut dealer = Dealer::new(Table::build_cash_table(CashGame::new(100, 1000).unwrap()));
+        for &(name, rebuy) in &[("a", Some(500)), ("b", None)] {
+            let strategy = Scripted { rebuy, ..Scripted::default() };
+            dealer.seat(Player::with_chips(name.to_string(), false, 0), Box::new(strategy));
+        }
+        dealer.offer_rebuys();
+        assert_eq!(dealer.table().find_player("a").unwrap().chips, 500);
+        assert_eq!(dealer.table().find_player("b").unwrap().chips, 0);
+    }
+
+    // takes the next of its thinking times over each move by the clock,
+    // then checks or calls, noting how long it was given
+    struct Slow {
+        clock: ManualClock,
+        thinking: Vec<Duration>,
+        given: Rc<RefCell<Vec<Duration>>>,
+    }
+
+    impl Slow {
+        fn new(clock: &ManualClock, thinking: Vec<Duration>,
+               given: &Rc<RefCell<Vec<Duration>>>) -> Slow {
+            Slow { clock: clock.clone(), thinking, given: given.clone() }
+        }
+    }
+
+    impl Strategy for Slow {
+        fn act(&mut self, obs: &Observation) -> Command {
+            let left = obs.deadline.as_ref().map(|deadline| deadline.left());
+            self.given.borrow_mut().push(left.expect("a deadline"));
+            if !self.thinking.is_empty() {
+                self.clock.advance(self.thinking.remove(0));
+            }
+            obs.legal.check_or_call()
+        }
+    }
+
+    // two players with a second a move and five in the bank, in a hand
+    fn timed_dealer(clock: &ManualClock) -> (Dealer, Rc<RefCell<EventLog>>) {
+        let mut dealer = Dealer::new(Table::build_table());
+        for name in &["a", "b"] {
+            let strategy = Slow::new(clock, Vec::new(), &Rc::default());
+            dealer.seat(Player::with_chips(name.to_string(), false, 1000), Box::new(strategy));
+        }
+        dealer.set_clock(Box::new(clock.clone()));
+        dealer.set_time_limits(TimeLimits { per_action: SECOND, bank: 5 * SECOND });
+        let log = Rc::new(RefCell::new(EventLog::default()));
+        dealer.table_mut().add_observer(View::Omniscient, log.clone());
+        dealer.table_mut().start_hand().unwrap();
+        (dealer, log)
+    }
+
+    #[test]
+    fn time_over_the_limit_comes_out_of_the_bank() {
+        let clock = ManualClock::new();
+        let (mut dealer, log) = timed_dealer(&clock);
+        let given = Rc::default();
+        let name = next_plays(&mut dealer, Slow::new(&clock, vec![3 * SECOND], &given));
+        dealer.play_turn().unwrap();
+        assert_eq!(dealer.time_bank(&name), Some(3 * SECOND));
+        assert_eq!(*given.borrow(), vec![6 * SECOND]);
+        assert_eq!(actions(&log), vec![(name, Command::Call)]);
+        assert!(timed_out(&log).is_empty());
+    }
+
+    #[test]
+    fn a_move_within_the_limit_costs_nothing() {
+        let clock = ManualClock::new();
+        let (mut dealer, _) = timed_dealer(&clock);
+        let name = next_plays(&mut dealer, Slow::new(&clock, vec![SECOND], &Rc::default()));
+        dealer.play_turn().unwrap();
+        assert_eq!(dealer.time_bank(&name), Some(5 * SECOND));
+    }
+
+    #[test]
+    fn running_out_of_time_folds_to_a_bet() {
+        let clock = ManualClock::new();
+        let (mut dealer, log) = timed_dealer(&clock);
+        // it would have called, but too late
+        let name = next_plays(&mut dealer, Slow::new(&clock, vec![7 * SECOND], &Rc::default()));
+        dealer.play_turn().unwrap();
+        assert_eq!(timed_out(&log), vec![name.clone()]);
+        assert_eq!(actions(&log), vec![(name.clone(), Command::Fold)]);
+        assert_eq!(dealer.time_bank(&name), Some(Duration::from_secs(0)));
+    }
+
+    #[test]
+    fn running_out_of_time_checks_when_it_can() {
+        let clock = ManualClock::new();
+        let (mut dealer, log) = timed_dealer(&clock);
+        let first = next_plays(&mut dealer, Slow::new(&clock, Vec::new(), &Rc::default()));
+        dealer.play_turn().unwrap();
+        // the big blind has the option to check
+        let second = next_plays(&mut dealer, Slow::new(&clock, vec![7 * SECOND], &Rc::default()));
+        dealer.play_turn().unwrap();
+        assert_eq!(timed_out(&log), vec![second.clone()]);
+        assert_eq!(actions(&log), vec![(first, Command::Call), (second, Command::Check)]);
+    }
+
+    #[test]
+    fn the_bank_never_goes_below_empty() {
+        let clock = ManualClock::new();
+        let (mut dealer, log) = timed_dealer(&clock);
+        let given = Rc::default();
+        let first = next_plays(&mut dealer, Slow::new(&clock, Vec::new(), &given));
+        dealer.play_turn().unwrap();
+        // using the whole bank exactly is still in time
+        let thinking = vec![6 * SECOND, 4 * SECOND];
+        let second = next_plays(&mut dealer, Slow::new(&clock, thinking, &given));
+        dealer.play_turn().unwrap();
+        assert!(timed_out(&log).is_empty());
+        assert_eq!(dealer.time_bank(&second), Some(Duration::from_secs(0)));
+        // on the flop the big blind acts first and has only the second a move
+        while dealer.table().player(dealer.table().to_act().unwrap()).name != second {
+            dealer.play_turn().unwrap();
+        }
+        dealer.play_turn().unwrap();
+        assert_eq!(timed_out(&log), vec![second.clone()]);
+        assert_eq!(dealer.time_bank(&second), Some(Duration::from_secs(0)));
+        assert_eq!(*given.borrow(), vec![6 * SECOND, 6 * SECOND, SECOND]);
+        assert_eq!(dealer.time_bank(&first), Some(5 * SECOND));
+    }
+
+    #[test]
+    fn time_banks_are_saved_with_the_table() {
+        let clock = ManualClock::new();
+        let (mut dealer, _) = timed_dealer(&clock);
+        let name = next_plays(&mut dealer, Slow::new(&clock, vec![3 * SECOND], &Rc::default()));
+        dealer.play_turn().unwrap();
+        let mut saved = Vec::new();
+        dealer.table().save(&mut saved).unwrap();
+        let mut resumed = Dealer::new(Table::load(&saved[..]).unwrap());
+        resumed.set_time_limits(TimeLimits { per_action: SECOND, bank: 5 * SECOND });
+        assert_eq!(resumed.time_bank(&name), Some(3 * SECOND));
+    }
+}
@@ -0,0 +1,67 @@
+use super::player::Command;
+
+use std::error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameError {
+    NotEnoughCards(usize),
+    InvalidCard(String),
+    NoCards(String),
+    DeckEmpty,
+    UnknownPlayer(String),
+    NotYourTurn(usize),
+    IllegalAction(Command),
+    HandInProgress,
+    NotEnoughPlayers,
+    NotACashGame,
+    InvalidBuyIn(i32),
+    InvalidTopUp(i32),
+    NotBusted(String),
+    Busted(String),
+    InvalidSetup(String),
+    InvalidSave(String),
+    InvalidProfile(String),
+    InvalidStrategy(String),
+    InvalidHandClass(String),
+    InvalidChart(String),
+    InvalidStats(String),
+    BotFailed(String),
+    InvalidRatings(String),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GameError::NotEnoughCards(n) => write!(f, "A hand needs 5 cards, got {}", n),
+            GameError::InvalidCard(ref card) => write!(f, "{} isn't a card", card),
+            GameError::NoCards(ref name) => write!(f, "{} has no cards", name),
+            GameError::DeckEmpty => write!(f, "The deck ran out of cards"),
+            GameError::UnknownPlayer(ref name) => write!(f, "No player named {} is seated", name),
+            GameError::NotYourTurn(seat) => write!(f, "It isn't seat {}'s turn", seat),
+            GameError::IllegalAction(ref cmd) => write!(f, "{:?} isn't allowed right now", cmd),
+            GameError::HandInProgress => write!(f, "A hand is in progress"),
+            GameError::NotEnoughPlayers => write!(f, "Not enough players to deal a hand"),
+            GameError::NotACashGame => write!(f, "This isn't a cash game"),
+            GameError::InvalidBuyIn(x) => write!(f, "Invalid buy-in of {}", x),
+            GameError::InvalidTopUp(x) => write!(f, "Invalid top up of {}", x),
+            GameError::NotBusted(ref name) => write!(f, "{} still has chips", name),
+            GameError::Busted(ref name) => write!(f, "{} is out of chips", name),
+            GameError::InvalidSetup(ref reason) => write!(f, "Invalid setup: {}", reason),
+            GameError::InvalidSave(ref reason) => write!(f, "Can't load the save: {}", reason),
+            GameError::InvalidProfile(ref profile) => write!(f, "Invalid bot profile: {}", profile),
+            GameError::InvalidStrategy(ref reason) => {
+                write!(f, "Can't load the strategy: {}", reason)
+            },
+            GameError::InvalidHandClass(ref class) => write!(f, "{} isn't a hand class", class),
+            GameError::InvalidChart(ref reason) => write!(f, "Invalid preflop chart: {}", reason),
+            GameError::InvalidStats(ref reason) => write!(f, "Can't load the stats: {}", reason),
+            GameError::BotFailed(ref reason) => write!(f, "Can't run the bot: {}", reason),
+            GameError::InvalidRatings(ref reason) => {
+                write!(f, "Can't load the ratings: {}", reason)
+            },
+        }
+    }
+}
+
+impl error::Error for GameError {}
@@ -0,0 +1,82 @@
+use super::card::{Card, Hand};
+use super::player::Command;
+use super::table::Street;
+
+use std::rc::Rc;
+
+// everything the table reports about a hand; front-ends decide what to show
+#[derive(Debug, Clone)]
+pub enum Event {
+    // (seat, name, chips) for everyone dealt in, in seat order
+    HandStarted { hand_number: u64, button: String, seats: Vec<(usize, String, i32)> },
+    BlindPosted { name: String, amount: i32 },
+    DeadBlindPosted { name: String, amount: i32 },
+    HoleCards { name: String, cards: (Rc<Card>, Rc<Card>) },
+    // took too long to act, and checks or folds
+    TimedOut { name: String },
+    Action { name: String, command: Command, amount: i32 },
+    // the whole board so far
+    StreetDealt { street: Street, cards: Vec<Rc<Card>> },
+    UncalledBet { name: String, amount: i32 },
+    Showdown { name: String, hand: Hand },
+    // pot 0 is the main pot, the others are side pots
+    PotAwarded { name: String, amount: i32, pot: usize },
+    Rake { amount: i32, total: i32 },
+    HandFinished { hand_number: u64 },
+    Rebuy { name: String, amount: i32 },
+    PlayerLeft { name: String, chips: i32 },
+}
+
+impl Event {
+    // the only player allowed to see this event, if it is private
+    pub fn owner(&self) -> Option<&str> {
+        match *self {
+            Event::HoleCards { ref name, .. } => Some(name),
+            _ => None,
+        }
+    }
+}
+
+// which events an observer gets to see
+#[derive(Debug, Clone, PartialEq)]
+pub enum View {
+    Spectator,
+    Player(String),
+    Omniscient,
+}
+
+impl View {
+    pub fn can_see(&self, event: &Event) -> bool {
+        match (self, event.owner()) {
+            (_, None) => true,
+            (View::Omniscient, _) => true,
+            (View::Player(name), Some(owner)) => name == owner,
+            (View::Spectator, Some(_)) => false,
+        }
+    }
+
+    // whether `name`'s hole cards are visible from this view
+    pub fn can_see_cards(&self, name: &str) -> bool {
+        match self {
+            View::Omniscient => true,
+            View::Player(player) => player == name,
+            View::Spectator => false,
+        }
+    }
+}
+
+pub trait TableObserver {
+    fn notify(&mut self, event: &Event);
+}
+
+// keeps every event it is shown, oldest first
+#[derive(Debug, Default)]
+pub struct EventLog {
+    pub events: Vec<Event>,
+}
+
+impl TableObserver for EventLog {
+    fn notify(&mut self, event: &Event) {
+        self.events.push(event.clone());
+    }
+}
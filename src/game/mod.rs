@@ -1,3 +1,15 @@
 pub mod table;
 pub mod player;
 pub mod card;
+pub mod cash;
+pub mod rake;
+pub mod event;
+pub mod error;
+pub mod betting;
+pub mod save;
+pub mod snapshot;
+pub mod strategy;
+pub mod dealer;
+pub mod clock;
+#[cfg(test)]
+pub mod testing;
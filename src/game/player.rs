@@ -1,83 +1,69 @@
 use super::card::Card;
-use super::table::Table;
-use ui;
+use super::error::GameError;
 
 use std::rc::Rc;
 use std::fmt;
 
 const CHIPS_AT_START: i32 = 100;
 
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Player {
     pub name: String,
     pub is_human: bool,
     pub chips: i32,
-    pub chips_in_play: i32,
+    pub chips_in_play: i32, // bet on the current street
+    pub chips_in_pot: i32, // put in over the whole hand
     pub cards: Option<(Rc<Card>, Rc<Card>)>,
+    pub folded: bool,
+    pub has_acted: bool,
+    pub sitting_out: bool,
+    pub missed_small_blind: bool,
+    pub missed_big_blind: bool,
+    pub leaving: bool,
 }
 
 impl Player {
     pub fn new(name: String, is_human: bool) -> Player {
+        Player::with_chips(name, is_human, CHIPS_AT_START)
+    }
+
+    pub fn with_chips(name: String, is_human: bool, chips: i32) -> Player {
         Player {
-            name: name,
-            is_human: is_human,
-            chips: CHIPS_AT_START,
+            name,
+            is_human,
+            chips,
             chips_in_play: 0,
+            chips_in_pot: 0,
             cards: None,
+            folded: false,
+            has_acted: false,
+            sitting_out: false,
+            missed_small_blind: false,
+            missed_big_blind: false,
+            leaving: false,
         }
     }
 
-    pub fn get_cards(&self) -> (Rc<Card>, Rc<Card>) {
-        self.cards.as_ref().unwrap().clone()
+    pub fn is_busted(&self) -> bool {
+        self.chips <= 0
     }
 
-    fn get_options(&self, table: &Table) -> Vec<Command> {
-        let largest_bet = table.largest_bet.clone();
-        let chips = self.chips.clone();
-        let chips_in_play = self.chips_in_play.clone();
-        assert!(chips > largest_bet);
-
-        let mut options = if table.get_betting_round() == 1 {
-            vec![Command::PostBlind]
-        
-        } else {
-            vec![Command::Check]
-            // // let mut options = vec![Command::Raise(chips - largest_bet)];
-            // if largest_bet > chips_in_play {
-            //     options.push(Command::Call);
-
-            // } else if largest_bet == chips_in_play {
-            //     options.push(Command::Check);
-            // }
-            // options
-        };
-        options.extend_from_slice(&[Command::Fold, Command::Leave]);
-        options
+    // dealt into the current hand and hasn't folded
+    pub fn is_in_hand(&self) -> bool {
+        self.cards.is_some() && !self.folded
     }
-}
 
-pub trait ComputerPlayer {
-    fn act(&self, table: &Table) -> Command;
-}
-
-pub trait HumanPlayer {
-    fn act(&self, table: &Table) -> Command;
-}
-
-impl ComputerPlayer for Player {
-    fn act(&self, table: &Table) -> Command {
-        // TODO
-        Command::Check
+    pub fn is_all_in(&self) -> bool {
+        self.is_in_hand() && self.chips == 0
     }
-}
 
-impl HumanPlayer for Player {
-    fn act(&self, table: &Table) -> Command {
-        let options = self.get_options(table);
-        ui::get_player_action(options)
+    pub fn get_cards(&self) -> Result<(Rc<Card>, Rc<Card>), GameError> {
+        self.cards.clone().ok_or_else(|| GameError::NoCards(self.name.clone()))
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Command {
     PostBlind,
     Fold,
@@ -91,8 +77,8 @@ impl fmt::Display for Command {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             &Command::PostBlind => write!(f, "Post Blind"),
-            &Command::Raise(x) => write!(f, "Raise _ (max is {})", x),
-            cmd => write!(f, "{}", format!("{:?}", cmd)),
+            &Command::Raise(x) => write!(f, "Raise {}", x),
+            cmd => write!(f, "{:?}", cmd),
         }
     }
 }
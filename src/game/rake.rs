@@ -0,0 +1,86 @@
+use super::error::GameError;
+
+use std::cmp;
+
+const DEFAULT_PERCENT: f64 = 5.0;
+
+// the house's cut of each pot. `caps` holds (minimum players dealt in, cap)
+// pairs, so the cap can grow as the table fills up
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rake {
+    pub percent: f64,
+    pub caps: Vec<(usize, i32)>,
+    pub no_flop_no_drop: bool,
+}
+
+impl Rake {
+    pub fn new(percent: f64) -> Result<Rake, GameError> {
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(GameError::InvalidSetup(format!("a rake of {}%", percent)));
+        }
+        Ok(Rake { percent, caps: Vec::new(), no_flop_no_drop: true })
+    }
+
+    pub fn cap(mut self, min_players: usize, cap: i32) -> Rake {
+        self.caps.push((min_players, cap));
+        self.caps.sort();
+        self
+    }
+
+    pub fn no_flop_no_drop(mut self, enabled: bool) -> Rake {
+        self.no_flop_no_drop = enabled;
+        self
+    }
+
+    // the cap for the largest player count that was reached, if any
+    pub fn cap_for(&self, players: usize) -> Option<i32> {
+        self.caps.iter().rev().find(|c| c.0 <= players).map(|c| c.1)
+    }
+
+    pub fn take(&self, pot: i32, players: usize, saw_flop: bool) -> i32 {
+        if self.no_flop_no_drop && !saw_flop {
+            return 0;
+        }
+        let rake = (pot as f64 * self.percent / 100.0).floor() as i32; // round in players' favour
+        match self.cap_for(players) {
+            Some(cap) => cmp::min(rake, cap),
+            None => rake,
+        }
+    }
+}
+
+impl Default for Rake {
+    fn default() -> Rake {
+        let rake = Rake { percent: DEFAULT_PERCENT, caps: Vec::new(), no_flop_no_drop: true };
+        rake.cap(2, 5).cap(4, 10)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn takes_its_percent_rounded_down() {
+        let rake = Rake::new(5.0).unwrap();
+        assert_eq!(rake.take(199, 2, true), 9);
+        assert_eq!(rake.take(19, 2, true), 0);
+    }
+
+    #[test]
+    fn caps_grow_with_the_players_dealt_in() {
+        let rake = Rake::default();
+        assert_eq!(rake.take(1000, 2, true), 5);
+        assert_eq!(rake.take(1000, 3, true), 5);
+        assert_eq!(rake.take(1000, 4, true), 10);
+        assert_eq!(rake.take(1000, 9, true), 10);
+        // under the cap it's just the percent
+        assert_eq!(rake.take(100, 9, true), 5);
+    }
+
+    #[test]
+    fn no_flop_no_drop() {
+        assert_eq!(Rake::default().take(1000, 4, false), 0);
+        assert_eq!(Rake::default().no_flop_no_drop(false).take(1000, 4, false), 10);
+    }
+}
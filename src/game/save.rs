@@ -0,0 +1,333 @@
+use super::card::Card;
+use super::player::{Player, Command};
+use super::table::Street;
+use super::event::Event;
+use super::betting::BettingStructure;
+
+use std::rc::Rc;
+
+// first line of every save file, bumped whenever the format changes
+pub const SAVE_HEADER: &str = "rust-poker save 3";
+
+// cards as "As Kd", or "-" for none
+pub fn write_cards(cards: &[Rc<Card>]) -> String {
+    if cards.is_empty() {
+        return "-".to_string();
+    }
+    cards.iter().map(|c| c.short()).collect::<Vec<_>>().join(" ")
+}
+
+pub fn read_cards(words: &[&str]) -> Option<Vec<Rc<Card>>> {
+    if words == ["-"] {
+        return Some(Vec::new());
+    }
+    words.iter().map(|w| w.parse().ok().map(Rc::new)).collect()
+}
+
+// every field of a player, the name last since it may contain spaces
+pub fn write_player(player: &Player) -> String {
+    let cards = match player.cards {
+        Some((ref c1, ref c2)) => format!("{},{}", c1.short(), c2.short()),
+        None => "-".to_string(),
+    };
+    format!("{} {} {} {} {} {} {} {} {} {} {} {}",
+            player.is_human, player.chips, player.chips_in_play, player.chips_in_pot,
+            player.folded, player.has_acted, player.sitting_out, player.missed_small_blind,
+            player.missed_big_blind, player.leaving, cards, player.name)
+}
+
+pub fn read_player(line: &str) -> Option<Player> {
+    let fields: Vec<&str> = line.splitn(12, ' ').collect();
+    if fields.len() != 12 {
+        return None;
+    }
+    let mut player = Player::with_chips(fields[11].to_string(), fields[0].parse().ok()?,
+                                        fields[1].parse().ok()?);
+    player.chips_in_play = fields[2].parse().ok()?;
+    player.chips_in_pot = fields[3].parse().ok()?;
+    player.folded = fields[4].parse().ok()?;
+    player.has_acted = fields[5].parse().ok()?;
+    player.sitting_out = fields[6].parse().ok()?;
+    player.missed_small_blind = fields[7].parse().ok()?;
+    player.missed_big_blind = fields[8].parse().ok()?;
+    player.leaving = fields[9].parse().ok()?;
+    if fields[10] != "-" {
+        let cards = read_cards(&fields[10].split(',').collect::<Vec<_>>())?;
+        if cards.len() != 2 {
+            return None;
+        }
+        player.cards = Some((cards[0].clone(), cards[1].clone()));
+    }
+    Some(player)
+}
+
+pub fn read_street(word: &str) -> Option<Street> {
+    match word {
+        "PreFlop" => Some(Street::PreFlop),
+        "Flop" => Some(Street::Flop),
+        "Turn" => Some(Street::Turn),
+        "River" => Some(Street::River),
+        _ => None,
+    }
+}
+
+pub fn read_betting(word: &str) -> Option<BettingStructure> {
+    match word {
+        "NoLimit" => Some(BettingStructure::NoLimit),
+        "PotLimit" => Some(BettingStructure::PotLimit),
+        "FixedLimit" => Some(BettingStructure::FixedLimit),
+        _ => None,
+    }
+}
+
+// a command as one word, e.g. "Raise:40"
+pub fn write_command(cmd: &Command) -> String {
+    match *cmd {
+        Command::Raise(x) => format!("Raise:{}", x),
+        ref cmd => format!("{:?}", cmd),
+    }
+}
+
+pub fn read_command(word: &str) -> Option<Command> {
+    match word {
+        "PostBlind" => Some(Command::PostBlind),
+        "Fold" => Some(Command::Fold),
+        "Check" => Some(Command::Check),
+        "Call" => Some(Command::Call),
+        "Leave" => Some(Command::Leave),
+        raise if raise.starts_with("Raise:") => raise[6..].parse().ok().map(Command::Raise),
+        _ => None,
+    }
+}
+
+// the lines of an event of the hand in play, names last. The events that
+// only come once the hand is over are never saved
+pub fn write_event(event: &Event) -> Vec<String> {
+    match *event {
+        Event::HandStarted { hand_number, ref button, ref seats } => {
+            let mut lines = vec![format!("HandStarted {} {}", hand_number, button)];
+            lines.extend(seats.iter().map(|s| format!("Seat {} {} {}", s.0, s.2, s.1)));
+            lines
+        },
+        Event::BlindPosted { ref name, amount } => vec![format!("BlindPosted {} {}", amount, name)],
+        Event::DeadBlindPosted { ref name, amount } => {
+            vec![format!("DeadBlindPosted {} {}", amount, name)]
+        },
+        Event::HoleCards { ref name, cards: (ref c1, ref c2) } => {
+            vec![format!("HoleCards {},{} {}", c1.short(), c2.short(), name)]
+        },
+        Event::TimedOut { ref name } => vec![format!("TimedOut {}", name)],
+        Event::Action { ref name, ref command, amount } => {
+            vec![format!("Action {} {} {}", write_command(command), amount, name)]
+        },
+        Event::StreetDealt { ref street, ref cards } => {
+            vec![format!("StreetDealt {:?} {}", street, write_cards(cards))]
+        },
+        Event::UncalledBet { ref name, amount } => vec![format!("UncalledBet {} {}", amount, name)],
+        Event::Rebuy { ref name, amount } => vec![format!("Rebuy {} {}", amount, name)],
+        Event::PlayerLeft { ref name, chips } => vec![format!("PlayerLeft {} {}", chips, name)],
+        Event::Showdown { .. } | Event::PotAwarded { .. } | Event::Rake { .. }
+        | Event::HandFinished { .. } => Vec::new(),
+    }
+}
+
+// reads a line of `write_event` onto the events read so far, a seat onto
+// the hand started before it
+pub fn read_event(line: &str, events: &mut Vec<Event>) -> Option<()> {
+    let (kind, rest) = line.split_once(' ')?;
+    // the fields before the name
+    let fields = |n: usize| -> Option<(Vec<&str>, String)> {
+        let split: Vec<&str> = rest.splitn(n + 1, ' ').collect();
+        if split.len() != n + 1 {
+            return None;
+        }
+        Some((split[..n].to_vec(), split[n].to_string()))
+    };
+    let event = match kind {
+        "HandStarted" => {
+            let (words, button) = fields(1)?;
+            Event::HandStarted { hand_number: words[0].parse().ok()?, button, seats: Vec::new() }
+        },
+        "Seat" => {
+            let (words, name) = fields(2)?;
+            match events.last_mut() {
+                Some(Event::HandStarted { ref mut seats, .. }) => {
+                    seats.push((words[0].parse().ok()?, name, words[1].parse().ok()?));
+                },
+                _ => return None,
+            }
+            return Some(());
+        },
+        "BlindPosted" => {
+            let (words, name) = fields(1)?;
+            Event::BlindPosted { name, amount: words[0].parse().ok()? }
+        },
+        "DeadBlindPosted" => {
+            let (words, name) = fields(1)?;
+            Event::DeadBlindPosted { name, amount: words[0].parse().ok()? }
+        },
+        "HoleCards" => {
+            let (words, name) = fields(1)?;
+            let cards = read_cards(&words[0].split(',').collect::<Vec<_>>())?;
+            if cards.len() != 2 {
+                return None;
+            }
+            Event::HoleCards { name, cards: (cards[0].clone(), cards[1].clone()) }
+        },
+        "TimedOut" => Event::TimedOut { name: fields(0)?.1 },
+        "Action" => {
+            let (words, name) = fields(2)?;
+            Event::Action { name, command: read_command(words[0])?,
+                            amount: words[1].parse().ok()? }
+        },
+        "StreetDealt" => {
+            let words: Vec<&str> = rest.split_whitespace().collect();
+            let street = read_street(words.first()?)?;
+            Event::StreetDealt { street, cards: read_cards(&words[1..])? }
+        },
+        "UncalledBet" => {
+            let (words, name) = fields(1)?;
+            Event::UncalledBet { name, amount: words[0].parse().ok()? }
+        },
+        "Rebuy" => {
+            let (words, name) = fields(1)?;
+            Event::Rebuy { name, amount: words[0].parse().ok()? }
+        },
+        "PlayerLeft" => {
+            let (words, name) = fields(1)?;
+            Event::PlayerLeft { name, chips: words[0].parse().ok()? }
+        },
+        _ => return None,
+    };
+    events.push(event);
+    Some(())
+}
+
+// a seat number, or "-" for none
+pub fn write_seat(seat: Option<usize>) -> String {
+    seat.map_or("-".to_string(), |s| s.to_string())
+}
+
+pub fn read_seat(word: &str) -> Option<Option<usize>> {
+    if word == "-" {
+        Some(None)
+    } else {
+        word.parse().ok().map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use game::table::Table;
+    use game::rake::Rake;
+    use game::error::GameError;
+    use game::testing::cash_table;
+
+    fn save(table: &Table) -> String {
+        let mut out = Vec::new();
+        table.save(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn load(text: &str) -> Result<Table, GameError> {
+        Table::load(text.as_bytes())
+    }
+
+    fn play_out(table: &mut Table) {
+        while let Some(seat) = table.to_act() {
+            let cmd = table.legal_actions(seat).unwrap().check_or_call();
+            table.act(seat, cmd).unwrap();
+        }
+    }
+
+    // a raked hand stopped after two actions, d having come back after
+    // missing both blinds
+    fn stopped_hand() -> Table {
+        let mut table = cash_table(&["a", "b", "c", "d"]);
+        table.set_rake(Rake::default());
+        table.sit_out("d").unwrap();
+        for _ in 0..3 {
+            table.start_hand().unwrap();
+            play_out(&mut table);
+        }
+        table.sit_in("d").unwrap();
+        table.start_hand().unwrap();
+        for _ in 0..2 {
+            let seat = table.to_act().unwrap();
+            table.act(seat, table.legal_actions(seat).unwrap().check_or_call()).unwrap();
+        }
+        table
+    }
+
+    #[test]
+    fn a_saved_hand_carries_on_where_it_stopped() {
+        let mut table = stopped_hand();
+        let text = save(&table);
+        let mut loaded = load(&text).unwrap();
+        assert_eq!(save(&loaded), text);
+
+        play_out(&mut table);
+        play_out(&mut loaded);
+        assert_eq!(save(&loaded), save(&table));
+        let chips = |t: &Table| t.players().iter().map(|p| p.chips).collect::<Vec<_>>();
+        assert_eq!(chips(&loaded), chips(&table));
+        assert!(loaded.total_rake() > 0);
+    }
+
+    #[test]
+    fn the_hand_so_far_is_saved() {
+        let table = stopped_hand();
+        let loaded = load(&save(&table)).unwrap();
+        let seat = table.to_act().unwrap();
+        let history = |t: &Table| format!("{:?}", t.observation(seat).unwrap().history);
+        assert_eq!(history(&loaded), history(&table));
+        assert!(!table.observation(seat).unwrap().history.is_empty());
+    }
+
+    #[test]
+    fn missed_blinds_are_saved() {
+        let mut table = cash_table(&["a", "b", "c", "d"]);
+        table.sit_out("d").unwrap();
+        for _ in 0..3 {
+            table.start_hand().unwrap();
+            play_out(&mut table);
+        }
+        let loaded = load(&save(&table)).unwrap();
+        let d = loaded.find_player("d").unwrap();
+        assert!(d.missed_small_blind && d.missed_big_blind);
+    }
+
+    #[test]
+    fn a_truncated_save_is_invalid() {
+        let text = save(&stopped_hand());
+        // cut off in the middle of a player's fields
+        let cut = text.find("\nplayer ").unwrap() + 12;
+        match load(&text[..cut]) {
+            Err(GameError::InvalidSave(_)) => {},
+            other => panic!("loaded {:?}", other.map(|_| ())),
+        }
+        assert_eq!(load("").err(), Some(GameError::InvalidSave("not a save file".to_string())));
+    }
+
+    #[test]
+    fn a_corrupt_save_is_invalid() {
+        let text = save(&stopped_hand());
+        let corrupt = [
+            text.replacen("deck ", "deck Xx ", 1),
+            text.replacen("betting NoLimit", "betting Omaha", 1),
+            text.replacen("cash 100 1000", "cash 1000 100", 1),
+            text.replacen("rake_taken", "rake_given", 1),
+            text.lines()
+                .map(|line| if line.starts_with("button ") { "button 9" } else { line })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            text.replacen(super::SAVE_HEADER, "rust-poker save 0", 1),
+        ];
+        for text in &corrupt {
+            match load(text) {
+                Err(GameError::InvalidSave(_)) => {},
+                other => panic!("loaded {:?}", other.map(|_| ())),
+            }
+        }
+    }
+}
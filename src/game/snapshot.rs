@@ -0,0 +1,72 @@
+use super::card::Card;
+use super::table::{Table, Street};
+use super::betting::BettingStructure;
+use super::event::View;
+
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SeatSnapshot {
+    pub seat: usize,
+    pub name: String,
+    pub chips: i32,
+    pub bet: i32, // on the current street
+    pub in_pot: i32,
+    pub in_hand: bool,
+    pub all_in: bool,
+    pub sitting_out: bool,
+    pub cards: Option<(Rc<Card>, Rc<Card>)>, // only if the view can see them
+}
+
+// the public state of a table, as seen from one view
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TableSnapshot {
+    pub hand_number: u64,
+    pub hand_in_progress: bool,
+    pub betting: BettingStructure,
+    pub small_blind: i32,
+    pub big_blind: i32,
+    pub button: Option<usize>,
+    pub street: Street,
+    pub board: Vec<Rc<Card>>,
+    pub pot: i32,
+    pub current_bet: i32,
+    pub min_raise: i32,
+    pub to_act: Option<usize>,
+    pub seats: Vec<SeatSnapshot>,
+}
+
+impl TableSnapshot {
+    pub fn new(table: &Table, view: &View) -> TableSnapshot {
+        let seats = table.players().iter().enumerate()
+            .map(|(seat, p)| SeatSnapshot {
+                seat,
+                name: p.name.clone(),
+                chips: p.chips,
+                bet: p.chips_in_play,
+                in_pot: p.chips_in_pot,
+                in_hand: p.is_in_hand(),
+                all_in: p.is_all_in(),
+                sitting_out: p.sitting_out,
+                cards: if view.can_see_cards(&p.name) { p.cards.clone() } else { None },
+            })
+            .collect();
+        TableSnapshot {
+            hand_number: table.hand_number(),
+            hand_in_progress: table.is_hand_in_progress(),
+            betting: table.betting_structure(),
+            small_blind: table.small_blind,
+            big_blind: table.big_blind,
+            button: table.button(),
+            street: table.street(),
+            board: table.community_cards().to_vec(),
+            pot: table.pot(),
+            current_bet: table.largest_bet,
+            min_raise: table.min_raise(),
+            to_act: table.to_act(),
+            seats,
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use super::card::Card;
+use super::cash::CashGame;
+use super::player::Command;
+use super::event::Event;
+use super::betting::LegalActions;
+use super::snapshot::TableSnapshot;
+use super::clock::Deadline;
+
+use std::rc::Rc;
+
+// everything a seat gets to know when it's their turn
+#[derive(Debug, Clone)]
+pub struct Observation {
+    pub seat: usize,
+    pub cards: (Rc<Card>, Rc<Card>),
+    pub table: TableSnapshot, // as seen from this seat
+    pub history: Vec<Event>, // the hand so far, as this seat saw it
+    pub legal: LegalActions,
+    pub deadline: Option<Deadline>, // to decide by, if there's a limit
+}
+
+impl Observation {
+    pub fn name(&self) -> &str {
+        &self.table.seats[self.seat].name
+    }
+
+    pub fn board(&self) -> &[Rc<Card>] {
+        &self.table.board
+    }
+
+    pub fn pot(&self) -> i32 {
+        self.table.pot
+    }
+
+    pub fn stack(&self) -> i32 {
+        self.table.seats[self.seat].chips
+    }
+
+    // players still in the hand besides this one
+    pub fn opponents(&self) -> usize {
+        self.table.seats.iter().filter(|s| s.in_hand && s.seat != self.seat).count()
+    }
+}
+
+// decides what a seat does. The terminal's human player is just one of these
+pub trait Strategy {
+    fn act(&mut self, observation: &Observation) -> Command;
+
+    // the amount to rebuy for after busting in a cash game, if any
+    fn rebuy(&mut self, cash: &CashGame) -> Option<i32> {
+        Some(cash.max_buy_in)
+    }
+
+    // true once the seat wants the game stopped where it is, like a human
+    // quitting. The dealer then leaves the turn unplayed
+    fn has_quit(&self) -> bool {
+        false
+    }
+}
+
+// never bets, never folds
+pub struct CheckCall;
+
+impl Strategy for CheckCall {
+    fn act(&mut self, observation: &Observation) -> Command {
+        observation.legal.check_or_call()
+    }
+}
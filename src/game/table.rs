@@ -1,202 +1,1231 @@
-
-use super::player::{Player, ComputerPlayer, HumanPlayer, Command};
+use super::player::{Player, Command};
 use super::card::{Card, Suit, Hand};
+use super::cash::CashGame;
+use super::rake::Rake;
+use super::event::{Event, View, TableObserver};
+use super::error::GameError;
+use super::betting::{BettingStructure, LegalActions, RAISE_CAP};
+use super::snapshot::TableSnapshot;
+use super::strategy::Observation;
+use super::save::{self, SAVE_HEADER};
 
-use rand::{thread_rng, Rng};
+use rand::{thread_rng, Rng, SeedableRng, StdRng};
 use std::rc::Rc;
-use std::cmp::Ordering;
-use std::process::exit;
+use std::cell::RefCell;
+use std::cmp::{self, Ordering};
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+const SMALL_BLIND: i32 = 5;
+const BIG_BLIND: i32 = 10;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Street {
+    PreFlop,
+    Flop,
+    Turn,
+    River,
+}
 
 pub struct Table {
-    players: Vec<Player>,
-    active_players: Vec<Player>,
+    players: Vec<Player>, // in seat order
+    departed: Vec<Player>,
+    button: Option<usize>,
+    pub small_blind: i32,
+    pub big_blind: i32,
     deck: Vec<Rc<Card>>,
+    stacked_deck: Option<Vec<Rc<Card>>>,
     community_cards: Vec<Rc<Card>>,
     pot: i32,
     pub largest_bet: i32,
+    min_raise: i32,
+    raises: u32, // bets and raises on this street
+    betting: BettingStructure,
+    street: Street,
+    hand_in_progress: bool,
+    to_act: Option<usize>,
+    seed: u64,
+    hand_number: u64,
+    observers: Vec<(View, Rc<RefCell<dyn TableObserver>>)>,
+    hand_events: Vec<Event>, // everything emitted since the hand started
+    cash_game: Option<CashGame>,
+    rake: Option<Rake>,
+    players_dealt: usize,
+    last_rake: i32,
+    total_rake: i32,
+    // what's left of each player's time bank, if they've dipped into it
+    time_banks: BTreeMap<String, Duration>,
 }
 
 impl Table {
     pub fn build_table() -> Table {
-        let mut deck = Vec::new();
-        let suits = vec![Suit::Spades, Suit::Hearts, Suit::Clubs, Suit::Diamonds];
-        for suit in suits {
-            for val in 2..15 {
-                deck.push(Rc::new(Card { suit: suit, val: val }));
-            }
-        }
-        let players = Vec::new();
-        Table { players: players, active_players: Vec::new(), deck: deck, 
-                community_cards: Vec::new(), pot: 0, largest_bet: 0 }
+        Table { players: Vec::new(), departed: Vec::new(), button: None,
+                small_blind: SMALL_BLIND, big_blind: BIG_BLIND, deck: Vec::new(),
+                stacked_deck: None, community_cards: Vec::new(), pot: 0, largest_bet: 0,
+                min_raise: BIG_BLIND, raises: 0, betting: BettingStructure::NoLimit,
+                street: Street::PreFlop, hand_in_progress: false, to_act: None,
+                seed: thread_rng().gen(), hand_number: 0, observers: Vec::new(),
+                hand_events: Vec::new(), cash_game: None, rake: None, players_dealt: 0,
+                last_rake: 0, total_rake: 0, time_banks: BTreeMap::new() }
+    }
+
+    pub fn build_cash_table(cash_game: CashGame) -> Table {
+        let mut table = Table::build_table();
+        table.cash_game = Some(cash_game);
+        table
     }
 
     pub fn add_player(&mut self, player: Player) {
         self.players.push(player);
     }
 
-    fn deal_card(&mut self) -> Rc<Card> {
-        let mut rng = thread_rng();
-        let idx = rng.gen_range(0, self.deck.len());
-        self.deck.remove(idx)
+    // every hand's deck is shuffled from the seed and the hand number, so a
+    // table built with the same seed deals the same cards
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // the next hand deals these cards instead of shuffling: two to each player
+    // starting from the button, then the board
+    pub fn stack_deck(&mut self, cards: Vec<Rc<Card>>) {
+        self.stacked_deck = Some(cards.into_iter().rev().collect());
+    }
+
+    // the next hand's button goes to the first player dealt in from `seat`
+    pub fn set_button(&mut self, seat: usize) {
+        let n = self.players.len();
+        self.button = if n == 0 { None } else { Some((seat + n - 1) % n) };
+    }
+
+    pub fn set_blinds(&mut self, small_blind: i32, big_blind: i32) {
+        self.small_blind = small_blind;
+        self.big_blind = big_blind;
+    }
+
+    pub fn big_blind(&self) -> i32 {
+        self.big_blind
+    }
+
+    pub fn set_betting_structure(&mut self, betting: BettingStructure) {
+        self.betting = betting;
+    }
+
+    pub fn betting_structure(&self) -> BettingStructure {
+        self.betting
+    }
+
+    pub fn cash_game(&self) -> Option<&CashGame> {
+        self.cash_game.as_ref()
+    }
+
+    pub fn set_rake(&mut self, rake: Rake) {
+        self.rake = Some(rake);
+    }
+
+    // rake taken from the last pot awarded
+    pub fn last_rake(&self) -> i32 {
+        self.last_rake
+    }
+
+    // rake taken since the table was built
+    pub fn total_rake(&self) -> i32 {
+        self.total_rake
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
     }
 
-    pub fn deal_cards(&mut self) {
-        while !self.players.is_empty() {
-            let mut player = self.players.pop().unwrap();
-            let (c1, c2) = (self.deal_card(), self.deal_card());
-            player.cards = Some((c1.clone(), c2.clone()));
-            
-            if player.is_human {
-                println!("Here are your cards: [{}, {}]", c1, c2);
+    pub fn player(&self, seat: usize) -> &Player {
+        &self.players[seat]
+    }
+
+    pub fn find_player(&self, name: &str) -> Option<&Player> {
+        self.players.iter().find(|p| p.name == name)
+    }
+
+    pub fn seat_of(&self, name: &str) -> Option<usize> {
+        self.players.iter().position(|p| p.name == name)
+    }
+
+    pub fn community_cards(&self) -> &[Rc<Card>] {
+        &self.community_cards
+    }
+
+    pub fn pot(&self) -> i32 {
+        self.pot
+    }
+
+    pub fn min_raise(&self) -> i32 {
+        self.min_raise
+    }
+
+    pub fn street(&self) -> Street {
+        self.street
+    }
+
+    pub fn button(&self) -> Option<usize> {
+        self.button
+    }
+
+    pub fn hand_number(&self) -> u64 {
+        self.hand_number
+    }
+
+    pub fn is_hand_in_progress(&self) -> bool {
+        self.hand_in_progress
+    }
+
+    // the seat whose action the table is waiting on, if any
+    pub fn to_act(&self) -> Option<usize> {
+        self.to_act
+    }
+
+    // the observer is shown every event its view allows, as it happens
+    pub fn add_observer(&mut self, view: View, observer: Rc<RefCell<dyn TableObserver>>) {
+        self.observers.push((view, observer));
+    }
+
+    fn emit(&mut self, event: Event) {
+        for (view, observer) in &self.observers {
+            if view.can_see(&event) {
+                observer.borrow_mut().notify(&event);
             }
-            self.active_players.push(player);
         }
+        if let Event::HandStarted { .. } = event {
+            self.hand_events.clear();
+        }
+        self.hand_events.push(event);
     }
 
-    pub fn reveal_cards(&mut self) {
-        let mut revealed = Vec::new();
-        match self.community_cards.len() as i32 {
-            0 => revealed = vec![self.deal_card(), self.deal_card(), self.deal_card()], // flop
-            3 => revealed = vec![self.deal_card()], // turn
-            4 => revealed = vec![self.deal_card()], // river
-            _ => {}
+    fn find_waiting_player(&mut self, name: &str) -> Result<&mut Player, GameError> {
+        if self.hand_in_progress {
+            return Err(GameError::HandInProgress);
         }
-        self.community_cards.extend_from_slice(&revealed);
-        self.print_community_cards();
+        self.players.iter_mut().find(|p| p.name == name)
+            .ok_or_else(|| GameError::UnknownPlayer(name.to_string()))
     }
 
-    fn print_community_cards(&self) {
-        println!("community cards: {}", display_cards(&self.community_cards));
+    // the operations below are only allowed between hands
+
+    pub fn buy_in(&mut self, mut player: Player, amount: i32) -> Result<(), GameError> {
+        match self.cash_game {
+            Some(ref cash) if cash.is_valid_buy_in(amount) => {},
+            Some(_) => return Err(GameError::InvalidBuyIn(amount)),
+            None => return Err(GameError::NotACashGame),
+        }
+        player.chips = amount;
+        self.add_player(player);
+        Ok(())
     }
 
-    pub fn show_cards(&self) {
-        for i in 0..self.active_players.len() {
-            let ref player = self.active_players[i];
-            let (c1, c2) = player.get_cards();
-            print!("{}: {}", player.name, display_cards(&vec![c1, c2]))
+    pub fn rebuy(&mut self, name: &str, amount: i32) -> Result<(), GameError> {
+        let valid = self.cash_game.as_ref().ok_or(GameError::NotACashGame)?
+            .is_valid_buy_in(amount);
+        let player = self.find_waiting_player(name)?;
+        if !player.is_busted() {
+            return Err(GameError::NotBusted(player.name.clone()));
+        }
+        if !valid {
+            return Err(GameError::InvalidBuyIn(amount));
         }
+        player.chips = amount;
+        player.sitting_out = false;
+        self.emit(Event::Rebuy { name: name.to_string(), amount });
+        Ok(())
     }
 
-    pub fn allow_betting(&mut self) {
-        for i in 0..self.active_players.len() {
-            let mut player = self.active_players.remove(i);
-            let cmd = if player.is_human {
-                HumanPlayer::act(&player, self)
-            } else {
-                ComputerPlayer::act(&player, self)
-            };
-            if cmd == Command::Fold {
-                self.players.push(player);
-            } else {
-                self.process_command(cmd, &mut player);
-                self.active_players.insert(i, player);
-            } 
-        }
-    }
-
-    pub fn process_command(&mut self, cmd: Command, player: &mut Player) {
-        let largest_bet = self.largest_bet;
-        match cmd {
-            Command::PostBlind => self.place_bet(player, 10),
-            Command::Fold => {},
-            Command::Check => {},
-            Command::Call => self.place_bet(player, largest_bet),
+    pub fn top_up(&mut self, name: &str, amount: i32) -> Result<(), GameError> {
+        let cash = self.cash_game.clone().ok_or(GameError::NotACashGame)?;
+        let player = self.find_waiting_player(name)?;
+        if player.is_busted() {
+            return Err(GameError::Busted(player.name.clone()));
+        }
+        if !cash.is_valid_top_up(player.chips, amount) {
+            return Err(GameError::InvalidTopUp(amount));
+        }
+        player.chips += amount;
+        Ok(())
+    }
+
+    pub fn sit_out(&mut self, name: &str) -> Result<(), GameError> {
+        let player = self.find_waiting_player(name)?;
+        player.sitting_out = true;
+        Ok(())
+    }
+
+    pub fn sit_in(&mut self, name: &str) -> Result<(), GameError> {
+        let player = self.find_waiting_player(name)?;
+        if player.is_busted() {
+            return Err(GameError::Busted(player.name.clone()));
+        }
+        player.sitting_out = false;
+        Ok(())
+    }
+
+    pub fn leave(&mut self, name: &str) -> Result<i32, GameError> {
+        let player = self.find_waiting_player(name)?;
+        player.leaving = true;
+        let chips = player.chips;
+        self.remove_leaving_players();
+        Ok(chips)
+    }
+
+    pub fn has_left(&self, name: &str) -> bool {
+        self.departed.iter().any(|p| p.name == name)
+    }
+
+    pub fn can_deal(&self) -> bool {
+        self.players.iter().filter(|p| is_dealt_in(p)).count() >= 2
+    }
+
+    pub fn start_hand(&mut self) -> Result<(), GameError> {
+        if self.hand_in_progress {
+            return Err(GameError::HandInProgress);
+        }
+        if !self.can_deal() {
+            return Err(GameError::NotEnoughPlayers);
+        }
+        self.hand_in_progress = true;
+        self.hand_number += 1;
+        self.shuffle_deck();
+
+        // can_deal guarantees there are at least two seats to find
+        let start = self.button.unwrap_or(self.players.len() - 1);
+        let button = self.next_seat(start, is_dealt_in).unwrap_or(start);
+        self.button = Some(button);
+
+        let mut dealt = vec![button];
+        let mut seat = self.next_seat(button, is_dealt_in).unwrap_or(button);
+        while seat != button {
+            dealt.push(seat);
+            seat = self.next_seat(seat, is_dealt_in).unwrap_or(button);
+        }
+        self.players_dealt = dealt.len();
+        let mut seats: Vec<(usize, String, i32)> = dealt.iter()
+            .map(|&s| (s, self.players[s].name.clone(), self.players[s].chips))
+            .collect();
+        seats.sort();
+        self.emit(Event::HandStarted { hand_number: self.hand_number,
+                                       button: self.players[button].name.clone(), seats });
+        for &seat in &dealt {
+            let c1 = self.deck.pop().ok_or(GameError::DeckEmpty)?;
+            let c2 = self.deck.pop().ok_or(GameError::DeckEmpty)?;
+            self.players[seat].cards = Some((c1.clone(), c2.clone()));
+            self.emit(Event::HoleCards { name: self.players[seat].name.clone(),
+                                         cards: (c1, c2) });
+        }
+
+        // heads up, the button posts the small blind
+        let (sb, bb) = if dealt.len() == 2 { (dealt[0], dealt[1]) } else { (dealt[1], dealt[2]) };
+        // the blinds skip anyone sitting out between the button and the big
+        // blind, who owe the blinds they missed when they come back
+        let mut seat = (button + 1) % self.players.len();
+        let mut past_sb = sb == button;
+        while seat != bb {
+            if seat == sb {
+                past_sb = true;
+            } else if self.players[seat].sitting_out {
+                if past_sb {
+                    self.players[seat].missed_big_blind = true;
+                } else {
+                    self.players[seat].missed_small_blind = true;
+                }
+            }
+            seat = (seat + 1) % self.players.len();
+        }
+        let (small_blind, big_blind) = (self.small_blind, self.big_blind);
+        let amount = self.place_bet(sb, small_blind);
+        self.emit(Event::BlindPosted { name: self.players[sb].name.clone(), amount });
+        let amount = self.place_bet(bb, big_blind);
+        self.emit(Event::BlindPosted { name: self.players[bb].name.clone(), amount });
+        // coming back on either blind settles what was missed
+        for &seat in &[sb, bb] {
+            self.players[seat].missed_small_blind = false;
+            self.players[seat].missed_big_blind = false;
+        }
+
+        for &seat in &dealt {
+            // a returning player owes each blind at most once, however many
+            // hands they sat out: a missed big blind is posted live and a
+            // missed small blind goes dead into the pot
+            if self.players[seat].missed_big_blind {
+                self.players[seat].missed_big_blind = false;
+                let amount = self.place_bet(seat, big_blind);
+                self.emit(Event::BlindPosted { name: self.players[seat].name.clone(), amount });
+            }
+            if self.players[seat].missed_small_blind {
+                let amount = cmp::min(small_blind, self.players[seat].chips);
+                let player = &mut self.players[seat];
+                player.chips -= amount;
+                player.chips_in_pot += amount;
+                player.missed_small_blind = false;
+                let name = player.name.clone();
+                self.pot += amount;
+                self.emit(Event::DeadBlindPosted { name, amount });
+            }
+        }
+
+        self.street = Street::PreFlop;
+        self.largest_bet = cmp::max(self.players[sb].chips_in_play,
+                                    self.players[bb].chips_in_play);
+        self.min_raise = big_blind;
+        self.raises = 1; // the big blind counts as the first bet
+        self.to_act = None;
+        self.advance(bb)
+    }
+
+    // applies the action of the seat whose turn it is
+    pub fn act(&mut self, seat: usize, cmd: Command) -> Result<(), GameError> {
+        let legal = self.legal_actions(seat)?;
+        if !legal.allows(&cmd) {
+            return Err(GameError::IllegalAction(cmd));
+        }
+        let to_call = self.largest_bet - self.players[seat].chips_in_play;
+        let amount = match cmd {
+            Command::Fold | Command::Leave => {
+                self.players[seat].folded = true;
+                self.players[seat].leaving = cmd == Command::Leave;
+                0
+            },
+            Command::Check => 0,
+            Command::Call => self.place_bet(seat, legal.call_amount),
             Command::Raise(x) => {
+                self.raises += 1;
+                // a short all in has to be called, but only a full raise resets the action
+                if x >= self.min_raise {
+                    self.min_raise = x;
+                    for player in &mut self.players {
+                        player.has_acted = false;
+                    }
+                }
                 self.largest_bet += x;
-                self.place_bet(player, largest_bet + x);
+                self.place_bet(seat, to_call + x)
             },
-            Command::Leave => exit(1),
+            _ => return Err(GameError::IllegalAction(cmd)),
+        };
+        self.players[seat].has_acted = true;
+        self.emit(Event::Action { name: self.players[seat].name.clone(), command: cmd, amount });
+        self.advance(seat)
+    }
+
+    // what `seat` gets to know when it's their turn
+    pub fn observation(&self, seat: usize) -> Result<Observation, GameError> {
+        let legal = self.legal_actions(seat)?;
+        let player = &self.players[seat];
+        let cards = player.get_cards()?;
+        let view = View::Player(player.name.clone());
+        let history = self.hand_events.iter().filter(|e| view.can_see(e)).cloned().collect();
+        Ok(Observation {
+            seat,
+            cards,
+            table: TableSnapshot::new(self, &view),
+            history,
+            legal,
+            deadline: None,
+        })
+    }
+
+    // what `name` has left of their time bank, if they've used any of it.
+    // The dealer keeps it here so it's saved with the table
+    pub fn time_bank(&self, name: &str) -> Option<Duration> {
+        self.time_banks.get(name).cloned()
+    }
+
+    pub fn set_time_bank(&mut self, name: &str, left: Duration) {
+        self.time_banks.insert(name.to_string(), left);
+    }
+
+    // `seat` ran out of time, so checks if they can and folds otherwise
+    pub fn time_out(&mut self, seat: usize) -> Result<(), GameError> {
+        let cmd = self.legal_actions(seat)?.check_or_fold();
+        self.emit(Event::TimedOut { name: self.players[seat].name.clone() });
+        self.act(seat, cmd)
+    }
+
+    pub fn legal_actions(&self, seat: usize) -> Result<LegalActions, GameError> {
+        if self.to_act != Some(seat) {
+            return Err(GameError::NotYourTurn(seat));
         }
+        let player = &self.players[seat];
+        let to_call = self.largest_bet - player.chips_in_play;
+        let all_in_to = player.chips_in_play + player.chips;
+        // a short all in doesn't reopen the betting for anyone who has acted
+        let can_raise = player.chips > to_call && !player.has_acted
+            && self.players.iter().enumerate()
+                .any(|(i, p)| i != seat && p.is_in_hand() && !p.is_all_in());
 
+        let min_to = cmp::min(self.largest_bet + self.min_raise, all_in_to);
+        let raise_to = match self.betting {
+            _ if !can_raise => None,
+            BettingStructure::NoLimit => Some((min_to, all_in_to)),
+            BettingStructure::PotLimit => {
+                // call, then raise the size of the pot
+                let pot_to = self.largest_bet + self.pot + to_call;
+                Some((min_to, cmp::min(pot_to, all_in_to)))
+            },
+            BettingStructure::FixedLimit if self.raises >= RAISE_CAP => None,
+            BettingStructure::FixedLimit => Some((min_to, min_to)),
+        };
+        Ok(LegalActions {
+            can_fold: true,
+            can_check: to_call == 0,
+            can_call: to_call > 0,
+            call_amount: cmp::min(to_call, player.chips),
+            current_bet: self.largest_bet,
+            raise_to,
+        })
     }
 
-    pub fn place_bet(&mut self, player: &mut Player, amount: i32) {
+    fn place_bet(&mut self, seat: usize, amount: i32) -> i32 {
+        let player = &mut self.players[seat];
+        let amount = cmp::min(amount, player.chips); // all in
         player.chips -= amount;
+        player.chips_in_play += amount;
+        player.chips_in_pot += amount;
         self.pot += amount;
+        amount
     }
 
-    pub fn evaluate_round(&mut self) {
-        let mut winners = Vec::new();
-        if self.active_players.len() as i32 == 1 { // one person left
-            let winner = &self.active_players[0];
-            let (c1, c2) = winner.get_cards();
-            let mut hand = vec![c1, c2];
-            hand.extend_from_slice(&self.community_cards);
-            let hand = Hand::make_hand(hand).unwrap();
-            
-            winners.push((hand, winner.name.clone()));
+    fn shuffle_deck(&mut self) {
+        if let Some(deck) = self.stacked_deck.take() {
+            self.deck = deck;
+            return;
+        }
+        let seed = [self.seed as usize, self.hand_number as usize];
+        let mut rng: StdRng = SeedableRng::from_seed(&seed[..]);
+        self.deck = build_deck();
+        rng.shuffle(&mut self.deck);
+    }
+
+    fn next_seat<F>(&self, from: usize, pred: F) -> Option<usize>
+        where F: Fn(&Player) -> bool
+    {
+        let n = self.players.len();
+        (1..n + 1).map(|i| (from + i) % n).find(|&seat| pred(&self.players[seat]))
+    }
+
+    fn needs_to_act(&self, seat: usize) -> bool {
+        let player = &self.players[seat];
+        if !player.is_in_hand() || player.is_all_in() {
+            return false;
+        }
+        if player.chips_in_play < self.largest_bet {
+            return true;
+        }
+        // nobody left to bet against
+        let others = self.players.iter().enumerate()
+            .filter(|&(i, p)| i != seat && p.is_in_hand() && !p.is_all_in())
+            .count();
+        !player.has_acted && others > 0
+    }
+
+    // moves the action on from `seat`, dealing the next street or ending the
+    // hand when the betting round is over
+    fn advance(&mut self, seat: usize) -> Result<(), GameError> {
+        if self.players.iter().filter(|p| p.is_in_hand()).count() <= 1 {
+            return self.evaluate_round();
+        }
+        let n = self.players.len();
+        self.to_act = (1..n + 1).map(|i| (seat + i) % n).find(|&s| self.needs_to_act(s));
+        if self.to_act.is_some() {
+            return Ok(());
+        }
+        if self.street == Street::River {
+            return self.evaluate_round();
+        }
+        self.return_uncalled_bet();
+
+        let (street, revealed) = match self.street {
+            Street::PreFlop => (Street::Flop, 3),
+            Street::Flop => (Street::Turn, 1),
+            _ => (Street::River, 1),
+        };
+        for _ in 0..revealed {
+            let card = self.deck.pop().ok_or(GameError::DeckEmpty)?;
+            self.community_cards.push(card);
+        }
+        self.street = street;
+        self.emit(Event::StreetDealt { street, cards: self.community_cards.clone() });
+        self.largest_bet = 0;
+        self.min_raise = match self.betting {
+            BettingStructure::FixedLimit if street >= Street::Turn => 2 * self.big_blind,
+            _ => self.big_blind,
+        };
+        self.raises = 0;
+        for player in &mut self.players {
+            player.chips_in_play = 0;
+            player.has_acted = false;
+        }
+        let button = self.button.unwrap_or(seat);
+        self.advance(button)
+    }
+
+    fn evaluate_round(&mut self) -> Result<(), GameError> {
+        self.to_act = None;
+        self.return_uncalled_bet();
+        let contenders: Vec<usize> = (0..self.players.len())
+            .filter(|&s| self.players[s].is_in_hand())
+            .collect();
+
+        let mut hands = Vec::new();
+        let mut pots = if contenders.len() == 1 { // one person left
+            vec![(self.pot, contenders)]
 
         } else { // contested
-            let mut hands = Vec::new();
-            for player in &self.active_players {
-                let mut hand = Vec::new();
-                let (c1, c2) = player.get_cards();
+            for &seat in &contenders {
+                let player = &self.players[seat];
+                let (c1, c2) = player.get_cards()?;
+                let mut hand = vec![c1, c2];
                 hand.extend_from_slice(&self.community_cards);
-                hand.extend_from_slice(&[c1, c2]);
-                hands.push((Hand::make_hand(hand).unwrap(), player.name.clone()));
+                let hand = Hand::make_hand(hand)?;
+                self.emit(Event::Showdown { name: player.name.clone(), hand: hand.clone() });
+                hands.push((seat, hand));
             }
+            self.side_pots()
+        };
 
-            hands.sort_by(|h1, h2| (*h2).0.cmp(&h1.0)); // sort by hand
-            let (best, winner) = hands.remove(0);
-            for (hand, player) in hands {
-                if hand.cmp(&best) == Ordering::Equal {
-                    winners.push((hand, player));
+        self.take_rake();
+        rake_pots(&mut pots, self.last_rake);
+
+        let mut winnings = Vec::new(); // (pot, seat, amount)
+        let button = self.button.unwrap_or(0);
+        let n = self.players.len();
+        for (pot, (amount, eligible)) in pots.into_iter().enumerate() {
+            let mut best: Vec<usize> = Vec::new();
+            for &(seat, ref hand) in hands.iter().filter(|h| eligible.contains(&h.0)) {
+                let ord = match best.first().and_then(|&b| hands.iter().find(|h| h.0 == b)) {
+                    Some(b) => hand.cmp(&b.1),
+                    None => Ordering::Greater,
+                };
+                if ord == Ordering::Greater {
+                    best.clear();
+                }
+                if ord != Ordering::Less {
+                    best.push(seat);
                 }
             }
-            winners.push((best, winner));
-        };
+            if best.is_empty() {
+                best = eligible;
+            }
+            // odd chips go to the first winners left of the button
+            let share = amount / best.len() as i32;
+            let remainder = amount % best.len() as i32;
+            best.sort_by_key(|&seat| (seat + n - button - 1) % n);
+            for (i, &seat) in best.iter().enumerate() {
+                let odd_chip = if (i as i32) < remainder { 1 } else { 0 };
+                winnings.push((pot, seat, share + odd_chip));
+            }
+        }
+
+        for (pot, seat, amount) in winnings {
+            if amount > 0 {
+                self.players[seat].chips += amount;
+                self.emit(Event::PotAwarded { name: self.players[seat].name.clone(), amount, pot });
+            }
+        }
+        self.pot = 0;
+        self.emit(Event::HandFinished { hand_number: self.hand_number });
         self.reset_table();
-        self.declare_winner(winners);
+        Ok(())
     }
 
-    fn declare_winner(&self, winners: Vec<(Hand, String)>) {
-        print!("The winner(s) is : ");
-        for winner in winners {
-            let (hand, name) = winner;
-            print!("{} with {}", name, hand);
+    // the part of the biggest bet on the street that nobody matched goes
+    // back to its owner. Dead blinds were never bets, so don't count
+    fn return_uncalled_bet(&mut self) {
+        let mut bets: Vec<(i32, usize)> = (0..self.players.len())
+            .map(|s| (self.players[s].chips_in_play, s))
+            .collect();
+        bets.sort_by(|a, b| b.cmp(a));
+        if bets.len() < 2 || bets[0].0 <= bets[1].0 {
+            return;
         }
-        println!("");
+        let (amount, seat) = (bets[0].0 - bets[1].0, bets[0].1);
+        let player = &mut self.players[seat];
+        player.chips += amount;
+        player.chips_in_pot -= amount;
+        player.chips_in_play -= amount;
+        self.pot -= amount;
+        let name = player.name.clone();
+        self.emit(Event::UncalledBet { name, amount });
     }
 
-    fn reset_table(&mut self) {
-        while !self.active_players.is_empty() {
-            let mut player = self.active_players.remove(0);
+    // (amount, eligible seats) for the main pot followed by each side pot
+    fn side_pots(&self) -> Vec<(i32, Vec<usize>)> {
+        let mut levels: Vec<i32> = self.players.iter()
+            .filter(|p| p.is_in_hand())
+            .map(|p| p.chips_in_pot)
+            .collect();
+        levels.sort();
+        levels.dedup();
 
-            let (c1, c2) = player.get_cards();
-            self.deck.extend_from_slice(&[c1, c2]); // return cards to deck
+        let mut pots: Vec<(i32, Vec<usize>)> = Vec::new();
+        let mut prev = 0;
+        for level in levels {
+            let amount = self.players.iter()
+                .map(|p| cmp::max(0, cmp::min(p.chips_in_pot, level) - prev))
+                .sum();
+            let eligible = (0..self.players.len())
+                .filter(|&s| self.players[s].is_in_hand() && self.players[s].chips_in_pot >= level)
+                .collect();
+            pots.push((amount, eligible));
+            prev = level;
+        }
+        // folded players' chips above the last level
+        let leftover = self.pot - pots.iter().map(|p| p.0).sum::<i32>();
+        if let Some(last) = pots.last_mut() {
+            last.0 += leftover;
+        }
+        pots
+    }
+
+    // the rake on the whole pot, which rake_pots shares out between the
+    // main and side pots
+    fn take_rake(&mut self) {
+        self.last_rake = match self.rake {
+            Some(ref rake) => rake.take(self.pot, self.players_dealt,
+                                        !self.community_cards.is_empty()),
+            None => 0,
+        };
+        if self.last_rake > 0 {
+            self.pot -= self.last_rake;
+            self.total_rake += self.last_rake;
+            self.emit(Event::Rake { amount: self.last_rake, total: self.total_rake });
+        }
+    }
+
+    fn reset_table(&mut self) {
+        for player in &mut self.players {
             player.cards = None;
+            player.chips_in_play = 0;
+            player.chips_in_pot = 0;
+            player.folded = false;
+            player.has_acted = false;
+            if self.cash_game.is_some() && player.is_busted() {
+                player.sitting_out = true; // until they rebuy
+            }
+        }
+        self.community_cards.clear();
+        self.largest_bet = 0;
+        self.hand_in_progress = false;
+        self.remove_leaving_players();
+    }
 
-            self.players.push(player); // return player to players
+    fn remove_leaving_players(&mut self) {
+        let mut seat = 0;
+        while seat < self.players.len() {
+            if !self.players[seat].leaving {
+                seat += 1;
+                continue;
+            }
+            let player = self.players.remove(seat);
+            self.emit(Event::PlayerLeft { name: player.name.clone(), chips: player.chips });
+            self.departed.push(player);
+            // the button passes back to the previous seat
+            self.button = match self.button {
+                _ if self.players.is_empty() => None,
+                Some(b) if b >= seat && b > 0 => Some(b - 1),
+                Some(b) if b >= seat => Some(self.players.len() - 1),
+                b => b,
+            };
         }
     }
 
     pub fn get_betting_round(&self) -> i32 {
-        match self.community_cards.len() as i32 {
-            0 => 1, // pre-flop
-            3 => 2, // pre-turn
-            4 => 3, // pre-river
-            5 => 4, // post-river
-            _ => -1, // ~impossible
+        match self.street {
+            Street::PreFlop => 1, // pre-flop
+            Street::Flop => 2, // pre-turn
+            Street::Turn => 3, // pre-river
+            Street::River => 4, // post-river
+        }
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.players.iter().filter(|p| !p.is_busted()).count() <= 1
+    }
+
+    // writes everything but the observers, which the front-end registers
+    // again after loading. A hand in progress is saved as it stands
+    pub fn save<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "{}", SAVE_HEADER)?;
+        writeln!(out, "seed {}", self.seed)?;
+        writeln!(out, "hand_number {}", self.hand_number)?;
+        writeln!(out, "blinds {} {}", self.small_blind, self.big_blind)?;
+        writeln!(out, "betting {:?}", self.betting)?;
+        writeln!(out, "button {}", save::write_seat(self.button))?;
+        if let Some(ref cash) = self.cash_game {
+            writeln!(out, "cash {} {}", cash.min_buy_in, cash.max_buy_in)?;
+        }
+        if let Some(ref rake) = self.rake {
+            let caps: Vec<String> = rake.caps.iter().map(|c| format!("{}:{}", c.0, c.1)).collect();
+            writeln!(out, "rake {} {} {}", rake.percent, rake.no_flop_no_drop, caps.join(" "))?;
+        }
+        writeln!(out, "rake_taken {} {}", self.last_rake, self.total_rake)?;
+        writeln!(out, "hand {} {:?} {} {} {} {} {} {}", self.hand_in_progress, self.street,
+                 self.pot, self.largest_bet, self.min_raise, self.raises, self.players_dealt,
+                 save::write_seat(self.to_act))?;
+        writeln!(out, "board {}", save::write_cards(&self.community_cards))?;
+        writeln!(out, "deck {}", save::write_cards(&self.deck))?;
+        if let Some(ref deck) = self.stacked_deck {
+            writeln!(out, "stacked {}", save::write_cards(deck))?;
+        }
+        for player in &self.players {
+            writeln!(out, "player {}", save::write_player(player))?;
         }
+        for player in &self.departed {
+            writeln!(out, "departed {}", save::write_player(player))?;
+        }
+        for (name, left) in &self.time_banks {
+            writeln!(out, "time_bank {} {}", left.as_millis(), name)?;
+        }
+        // what the strategies have seen of the hand in play
+        if self.hand_in_progress {
+            for line in self.hand_events.iter().flat_map(save::write_event) {
+                writeln!(out, "event {}", line)?;
+            }
+        }
+        out.flush()
     }
 
-    pub fn is_playing(&self) -> bool {
-        return self.active_players.len() as i32 >= 2 && self.community_cards.len() as i32 != 5;
+    pub fn load<R: BufRead>(input: R) -> Result<Table, GameError> {
+        let mut lines = input.lines();
+        match lines.next() {
+            Some(Ok(ref header)) if header == SAVE_HEADER => {},
+            _ => return Err(GameError::InvalidSave("not a save file".to_string())),
+        }
+        let mut table = Table::build_table();
+        for line in lines {
+            let line = line.map_err(|err| GameError::InvalidSave(err.to_string()))?;
+            if table.load_line(&line).is_none() {
+                return Err(GameError::InvalidSave(line));
+            }
+        }
+        let n = table.players.len();
+        if table.button.is_some_and(|s| s >= n) || table.to_act.is_some_and(|s| s >= n) {
+            return Err(GameError::InvalidSave("seat out of range".to_string()));
+        }
+        Ok(table)
     }
 
-    pub fn is_game_over(&self) -> bool {
-        return self.players.len() as i32 == 1;
+    fn load_line(&mut self, line: &str) -> Option<()> {
+        let (key, rest) = match line.find(' ') {
+            Some(i) => (&line[..i], &line[i + 1..]),
+            None => (line, ""),
+        };
+        let words: Vec<&str> = rest.split_whitespace().collect();
+        let word = |i: usize| words.get(i).cloned();
+        match key {
+            "seed" => self.seed = word(0)?.parse().ok()?,
+            "hand_number" => self.hand_number = word(0)?.parse().ok()?,
+            "blinds" => {
+                self.small_blind = word(0)?.parse().ok()?;
+                self.big_blind = word(1)?.parse().ok()?;
+            },
+            "betting" => self.betting = save::read_betting(word(0)?)?,
+            "button" => self.button = save::read_seat(word(0)?)?,
+            "cash" => {
+                let min_buy_in = word(0)?.parse().ok()?;
+                let max_buy_in = word(1)?.parse().ok()?;
+                self.cash_game = Some(CashGame::new(min_buy_in, max_buy_in).ok()?);
+            },
+            "rake" => {
+                let percent = word(0)?.parse().ok()?;
+                let mut rake = Rake::new(percent).ok()?.no_flop_no_drop(word(1)?.parse().ok()?);
+                for cap in words.iter().skip(2) {
+                    let mut split = cap.split(':');
+                    rake = rake.cap(split.next()?.parse().ok()?, split.next()?.parse().ok()?);
+                }
+                self.rake = Some(rake);
+            },
+            "rake_taken" => {
+                self.last_rake = word(0)?.parse().ok()?;
+                self.total_rake = word(1)?.parse().ok()?;
+            },
+            "hand" => {
+                self.hand_in_progress = word(0)?.parse().ok()?;
+                self.street = save::read_street(word(1)?)?;
+                self.pot = word(2)?.parse().ok()?;
+                self.largest_bet = word(3)?.parse().ok()?;
+                self.min_raise = word(4)?.parse().ok()?;
+                self.raises = word(5)?.parse().ok()?;
+                self.players_dealt = word(6)?.parse().ok()?;
+                self.to_act = save::read_seat(word(7)?)?;
+            },
+            "board" => self.community_cards = save::read_cards(&words)?,
+            "deck" => self.deck = save::read_cards(&words)?,
+            "stacked" => self.stacked_deck = Some(save::read_cards(&words)?),
+            "player" => self.players.push(save::read_player(rest)?),
+            "departed" => self.departed.push(save::read_player(rest)?),
+            "time_bank" => {
+                let (millis, name) = rest.split_once(' ')?;
+                self.set_time_bank(name, Duration::from_millis(millis.parse().ok()?));
+            },
+            "event" => save::read_event(rest, &mut self.hand_events)?,
+            "" => {},
+            _ => return None,
+        }
+        Some(())
+    }
+}
+
+// takes `rake` out of the pots in proportion to their size, so side pots
+// pay their share. Chips left over from rounding come out of the main pot
+fn rake_pots(pots: &mut [(i32, Vec<usize>)], rake: i32) {
+    let total: i32 = pots.iter().map(|p| p.0).sum();
+    if total <= 0 {
+        return;
+    }
+    let mut left = rake;
+    for pot in pots.iter_mut() {
+        let taken = (rake as i64 * pot.0 as i64 / total as i64) as i32;
+        pot.0 -= taken;
+        left -= taken;
+    }
+    for pot in pots.iter_mut() {
+        let taken = cmp::min(left, pot.0);
+        pot.0 -= taken;
+        left -= taken;
     }
 }
 
-fn display_cards(cards: &Vec<Rc<Card>>) -> String {
-    let mut str = format!("[");
+fn is_dealt_in(player: &Player) -> bool {
+    !player.sitting_out && !player.is_busted() && !player.leaving
+}
+
+pub fn build_deck() -> Vec<Rc<Card>> {
+    let mut deck = Vec::new();
+    let suits = vec![Suit::Spades, Suit::Hearts, Suit::Clubs, Suit::Diamonds];
+    for suit in suits {
+        for val in 2..15 {
+            deck.push(Rc::new(Card { suit, val }));
+        }
+    }
+    deck
+}
+
+pub fn display_cards(cards: &[Rc<Card>]) -> String {
+    let mut str = "[".to_string();
     for i in 0..cards.len() {
-        if i != cards.len()-1 { 
-            str = str + &format!("{}, ", cards[i]); 
-        } else { 
-            str = str + &format!("{}]\n", cards[i]); 
+        if i != cards.len()-1 {
+            str = str + &format!("{}, ", cards[i]);
+        } else {
+            str = str + &format!("{}]", cards[i]);
         }
     }
     str
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::testing::{cash_table, cards};
+    use game::event::EventLog;
+
+    fn log(table: &mut Table, view: View) -> Rc<RefCell<EventLog>> {
+        let log = Rc::new(RefCell::new(EventLog::default()));
+        table.add_observer(view, log.clone());
+        log
+    }
+
+    fn play_hand(table: &mut Table, cmd: Command) {
+        table.start_hand().unwrap();
+        play_out(table, cmd);
+    }
+
+    // every seat left to act does `cmd`, checking or calling as needed
+    fn play_out(table: &mut Table, cmd: Command) {
+        while let Some(seat) = table.to_act() {
+            let to_call = table.largest_bet - table.player(seat).chips_in_play;
+            let cmd = match cmd {
+                Command::Check | Command::Call if to_call > 0 => Command::Call,
+                Command::Check | Command::Call => Command::Check,
+                ref cmd => cmd.clone(),
+            };
+            table.act(seat, cmd).unwrap();
+        }
+    }
+
+    // the (name, amount) of each live or each dead blind posted
+    fn blinds(log: &RefCell<EventLog>, dead: bool) -> Vec<(String, i32)> {
+        log.borrow().events.iter()
+            .filter_map(|event| match *event {
+                Event::BlindPosted { ref name, amount } if !dead => Some((name.clone(), amount)),
+                Event::DeadBlindPosted { ref name, amount } if dead => {
+                    Some((name.clone(), amount))
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn buy_ins_must_be_within_the_limits() {
+        let mut table = cash_table(&[]);
+        let player = |name: &str| Player::new(name.to_string(), false);
+        assert!(table.buy_in(player("a"), 99).is_err());
+        assert!(table.buy_in(player("a"), 1001).is_err());
+        assert!(table.buy_in(player("a"), 100).is_ok());
+        assert_eq!(table.find_player("a").map(|p| p.chips), Some(100));
+        // a freezeout has no buy-ins
+        assert!(Table::build_table().buy_in(player("b"), 100).is_err());
+    }
+
+    #[test]
+    fn only_a_busted_player_rebuys() {
+        let mut table = cash_table(&["a", "b"]);
+        assert!(table.rebuy("a", 500).is_err());
+        table.players[0].chips = 0;
+        table.players[0].sitting_out = true;
+        assert!(table.rebuy("a", 50).is_err());
+        assert!(table.rebuy("a", 500).is_ok());
+        assert_eq!(table.players[0].chips, 500);
+        assert!(!table.players[0].sitting_out);
+    }
+
+    #[test]
+    fn top_ups_stop_at_the_maximum_buy_in() {
+        let mut table = cash_table(&["a"]);
+        table.players[0].chips = 600;
+        assert!(table.top_up("a", 401).is_err());
+        assert!(table.top_up("a", 0).is_err());
+        assert!(table.top_up("a", 400).is_ok());
+        assert_eq!(table.players[0].chips, 1000);
+    }
+
+    #[test]
+    fn sitting_out_players_are_not_dealt_in() {
+        let mut table = cash_table(&["a", "b"]);
+        assert!(table.can_deal());
+        table.sit_out("b").unwrap();
+        assert!(!table.can_deal());
+        table.sit_in("b").unwrap();
+        assert!(table.can_deal());
+        assert_eq!(table.leave("b"), Ok(1000));
+        assert!(table.has_left("b"));
+        assert!(!table.can_deal());
+    }
+
+    #[test]
+    fn a_hand_plays_out_without_a_terminal() {
+        let mut table = cash_table(&["a", "b", "c", "d"]);
+        let log = log(&mut table, View::Omniscient);
+        play_hand(&mut table, Command::Call);
+        assert!(!table.is_hand_in_progress());
+        assert_eq!(table.players().iter().map(|p| p.chips).sum::<i32>(), 4000);
+        let events = &log.borrow().events;
+        assert_eq!(events.iter().filter(|e| matches!(e, Event::Showdown { .. })).count(), 4);
+        assert!(events.iter().any(|e| matches!(e, Event::PotAwarded { .. })));
+    }
+
+    #[test]
+    fn sitting_out_away_from_the_blinds_costs_nothing() {
+        let mut table = cash_table(&["a", "b", "c", "d"]);
+        let log = log(&mut table, View::Omniscient);
+        // a deals first, so d is after the big blind
+        table.sit_out("d").unwrap();
+        play_hand(&mut table, Command::Fold);
+        table.sit_in("d").unwrap();
+        play_hand(&mut table, Command::Fold);
+        assert!(blinds(&log, true).is_empty());
+    }
+
+    #[test]
+    fn coming_back_on_the_small_blind_posts_only_the_small_blind() {
+        let mut table = cash_table(&["a", "b", "c", "d"]);
+        table.sit_out("d").unwrap();
+        play_hand(&mut table, Command::Fold);
+        // the big blind skips d in the second hand
+        play_hand(&mut table, Command::Fold);
+        table.sit_in("d").unwrap();
+        let log = log(&mut table, View::Omniscient);
+        play_hand(&mut table, Command::Fold);
+        let (live, dead) = (blinds(&log, false), blinds(&log, true));
+        assert_eq!(live, vec![("d".to_string(), 5), ("a".to_string(), 10)]);
+        assert!(dead.is_empty());
+    }
+
+    #[test]
+    fn missing_only_the_small_blind_costs_a_dead_small_blind() {
+        let mut table = cash_table(&["a", "b", "c", "d"]);
+        play_hand(&mut table, Command::Fold);
+        play_hand(&mut table, Command::Fold);
+        // the small blind skips d in the third hand
+        table.sit_out("d").unwrap();
+        play_hand(&mut table, Command::Fold);
+        table.sit_in("d").unwrap();
+        let log = log(&mut table, View::Omniscient);
+        play_hand(&mut table, Command::Fold);
+        let (live, dead) = (blinds(&log, false), blinds(&log, true));
+        assert_eq!(live.len(), 2);
+        assert_eq!(dead, vec![("d".to_string(), 5)]);
+    }
+
+    #[test]
+    fn missing_both_blinds_costs_a_live_big_blind_and_a_dead_small_blind() {
+        let mut table = cash_table(&["a", "b", "c", "d"]);
+        table.sit_out("d").unwrap();
+        // the big blind skips d in the second hand and the small blind in
+        // the third
+        for _ in 0..3 {
+            play_hand(&mut table, Command::Fold);
+        }
+        table.sit_in("d").unwrap();
+        let log = log(&mut table, View::Omniscient);
+        play_hand(&mut table, Command::Fold);
+        let (live, dead) = (blinds(&log, false), blinds(&log, true));
+        assert_eq!(live.last(), Some(&("d".to_string(), 10)));
+        assert_eq!(dead, vec![("d".to_string(), 5)]);
+    }
+
+    // the legal actions of the seat to act, after the `actions` (seat,
+    // command) pairs from the start of a hand
+    fn legal_after(table: &mut Table, actions: &[(usize, Command)]) -> LegalActions {
+        table.start_hand().unwrap();
+        for &(seat, ref cmd) in actions {
+            table.act(seat, cmd.clone()).unwrap();
+        }
+        table.legal_actions(table.to_act().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn a_raise_must_be_at_least_the_last_raise() {
+        let mut table = cash_table(&["a", "b", "c", "d"]);
+        // d raises to 30, 20 more than the big blind
+        let legal = legal_after(&mut table, &[(3, Command::Raise(20))]);
+        assert_eq!(legal.current_bet, 30);
+        assert_eq!(legal.call_amount, 30);
+        assert_eq!(legal.raise_to, Some((50, 1000)));
+        assert!(!legal.can_raise_to(49));
+    }
+
+    #[test]
+    fn a_short_all_in_doesnt_reopen_the_betting() {
+        let mut table = cash_table(&["a", "b", "c", "d"]);
+        table.players[1].chips = 40;
+        // b's all in to 40 is only 10 more than d's raise to 30
+        let actions = [(3, Command::Raise(20)), (0, Command::Call), (1, Command::Raise(10))];
+        let legal = legal_after(&mut table, &actions);
+        // c hasn't acted yet, so may still raise
+        assert_eq!(legal.raise_to, Some((60, 1000)));
+        table.act(2, Command::Call).unwrap();
+        let legal = table.legal_actions(3).unwrap();
+        assert!(legal.can_call);
+        assert_eq!(legal.call_amount, 10);
+        assert_eq!(legal.raise_to, None);
+    }
+
+    #[test]
+    fn pot_limit_raises_up_to_the_pot_after_calling() {
+        let mut table = cash_table(&["a", "b", "c", "d"]);
+        table.set_betting_structure(BettingStructure::PotLimit);
+        // d calls 10 into 15, making 25, and raises that much
+        let legal = legal_after(&mut table, &[]);
+        assert_eq!(legal.raise_to, Some((20, 35)));
+        table.act(3, Command::Raise(25)).unwrap();
+        // a calls 35 into 50 and raises 85
+        assert_eq!(table.legal_actions(0).unwrap().raise_to, Some((60, 120)));
+    }
+
+    #[test]
+    fn fixed_limit_bets_double_on_the_turn_and_are_capped() {
+        let mut table = cash_table(&["a", "b", "c", "d"]);
+        table.set_betting_structure(BettingStructure::FixedLimit);
+        // the big blind and three raises cap the betting
+        let raise = Command::Raise(10);
+        let legal = legal_after(&mut table, &[(3, raise.clone()), (0, raise.clone())]);
+        assert_eq!(legal.raise_to, Some((40, 40)));
+        table.act(1, raise).unwrap();
+        let legal = table.legal_actions(2).unwrap();
+        assert_eq!(legal.raise_to, None);
+        assert!(legal.can_call);
+
+        for seat in &[2, 3, 0] {
+            table.act(*seat, Command::Call).unwrap();
+        }
+        assert_eq!(table.legal_actions(1).unwrap().raise_to, Some((10, 10)));
+        for seat in &[1, 2, 3, 0] {
+            table.act(*seat, Command::Check).unwrap();
+        }
+        assert_eq!(table.legal_actions(1).unwrap().raise_to, Some((20, 20)));
+    }
+
+    #[test]
+    fn the_big_blind_checks_a_limped_pot() {
+        let mut table = cash_table(&["a", "b", "c", "d"]);
+        let actions = [(3, Command::Call), (0, Command::Call), (1, Command::Call)];
+        let legal = legal_after(&mut table, &actions);
+        assert_eq!(table.to_act(), Some(2));
+        assert!(legal.can_check);
+        assert!(!legal.can_call);
+        assert_eq!(legal.raise_to, Some((20, 1000)));
+        // the small blind still has to complete
+        let mut table = cash_table(&["a", "b", "c", "d"]);
+        let legal = legal_after(&mut table, &[(3, Command::Call), (0, Command::Call)]);
+        assert!(!legal.can_check);
+        assert_eq!(legal.call_amount, 5);
+    }
+
+    #[test]
+    fn side_pots_pay_their_share_of_the_rake() {
+        let mut table = cash_table(&["a", "b", "c"]);
+        table.set_rake(Rake::new(10.0).unwrap());
+        table.players[0].chips = 100;
+        table.start_hand().unwrap();
+        for (seat, hole) in ["As Ah", "Ks Kh", "2c 7d"].iter().enumerate() {
+            let hole = cards(hole);
+            table.players[seat].cards = Some((hole[0].clone(), hole[1].clone()));
+        }
+        // the board comes off the end of the deck
+        table.deck = cards("4d Jh 9c 8d 3s");
+        // a is all in and b bets on the flop, making a side pot of 400
+        let actions = [(0, Command::Raise(90)), (1, Command::Call), (2, Command::Call),
+                       (1, Command::Raise(200)), (2, Command::Call),
+                       (1, Command::Check), (2, Command::Check),
+                       (1, Command::Check), (2, Command::Check)];
+        for &(seat, ref cmd) in &actions {
+            assert_eq!(table.to_act(), Some(seat));
+            table.act(seat, cmd.clone()).unwrap();
+        }
+        // 70 in rake, 30 from the main pot of 300 and 40 from the side pot
+        assert_eq!(table.last_rake(), 70);
+        let chips: Vec<i32> = table.players().iter().map(|p| p.chips).collect();
+        assert_eq!(chips, vec![270, 1060, 700]);
+    }
+
+    #[test]
+    fn rake_left_over_from_rounding_comes_from_the_main_pot() {
+        let mut pots = vec![(100, vec![0, 1, 2]), (50, vec![1, 2])];
+        rake_pots(&mut pots, 10);
+        assert_eq!(pots[0].0 + pots[1].0, 140);
+        assert_eq!(pots[1].0, 47);
+    }
+
+    #[test]
+    fn hole_cards_stay_hidden_until_showdown() {
+        let mut table = cash_table(&["a", "b", "c"]);
+        let a = log(&mut table, View::Player("a".to_string()));
+        let spectator = log(&mut table, View::Spectator);
+        let names = |log: &RefCell<EventLog>, showdown: bool| -> Vec<String> {
+            log.borrow().events.iter()
+                .filter_map(|event| match *event {
+                    Event::HoleCards { ref name, .. } if !showdown => Some(name.clone()),
+                    Event::Showdown { ref name, .. } if showdown => Some(name.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+        table.start_hand().unwrap();
+        assert_eq!(names(&a, false), vec!["a".to_string()]);
+        assert!(names(&spectator, false).is_empty());
+        assert!(names(&a, true).is_empty());
+        play_out(&mut table, Command::Call);
+        assert_eq!(names(&a, true).len(), 3);
+        assert_eq!(names(&spectator, true).len(), 3);
+    }
+}
@@ -0,0 +1,38 @@
+use super::table::Table;
+use super::cash::CashGame;
+use super::player::Player;
+use super::card::{Card, Suit};
+
+use std::rc::Rc;
+
+// a cash table with buy-ins from 100 to 1000 and `names` bought in for
+// 1000 each, in seats from 0
+pub fn cash_table(names: &[&str]) -> Table {
+    let mut table = Table::build_cash_table(CashGame::new(100, 1000).unwrap());
+    for name in names {
+        table.buy_in(Player::new(name.to_string(), false), 1000).unwrap();
+    }
+    table
+}
+
+// cards written like "As Td 9c"
+pub fn cards(short: &str) -> Vec<Rc<Card>> {
+    short.split_whitespace().map(|card| {
+        let (val, suit) = card.split_at(1);
+        let val = match val {
+            "T" => 10,
+            "J" => 11,
+            "Q" => 12,
+            "K" => 13,
+            "A" => 14,
+            digit => digit.parse().unwrap(),
+        };
+        let suit = match suit {
+            "s" => Suit::Spades,
+            "h" => Suit::Hearts,
+            "c" => Suit::Clubs,
+            _ => Suit::Diamonds,
+        };
+        Rc::new(Card { suit, val })
+    }).collect()
+}
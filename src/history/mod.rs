@@ -0,0 +1,141 @@
+use game::card::Card;
+use game::player::Command;
+use game::table::Street;
+use game::betting::BettingStructure;
+
+use std::rc::Rc;
+
+pub mod writer;
+pub mod parser;
+pub mod replay;
+pub mod stats;
+#[cfg(test)]
+mod samples;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Seat {
+    pub number: usize, // counting from 1, as hand histories do
+    pub name: String,
+    pub chips: i32,
+    pub cards: Option<(Rc<Card>, Rc<Card>)>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Blind {
+    Small,
+    Big,
+    // a missed small blind, which goes into the pot without counting as a bet
+    Dead,
+    Ante,
+    Straddle, // a live bet posted before the cards, raising the big blind
+}
+
+impl Blind {
+    // whether the post counts towards the player's bet, rather than going
+    // into the pot dead
+    pub fn is_live(self) -> bool {
+        match self {
+            Blind::Small | Blind::Big | Blind::Straddle => true,
+            Blind::Dead | Blind::Ante => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Action {
+    pub street: Street,
+    pub name: String,
+    pub command: Command,
+    pub amount: i32, // chips put in by the action
+}
+
+// one hand as it is written to (or read from) a hand history
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HandHistory {
+    pub hand_number: u64,
+    pub betting: BettingStructure,
+    pub small_blind: i32,
+    pub big_blind: i32,
+    pub time: String,
+    pub table_name: String,
+    pub max_seats: usize,
+    pub button: usize, // seat number
+    pub seats: Vec<Seat>,
+    pub blinds: Vec<(String, Blind, i32)>,
+    pub dealt_to: Vec<String>, // whose hole cards the history shows from the start
+    pub actions: Vec<Action>,
+    pub board: Vec<Rc<Card>>,
+    pub uncalled: Vec<(String, i32)>,
+    pub shown: Vec<String>,
+    pub winnings: Vec<(String, i32, usize)>, // (name, amount, pot) with pot 0 the main pot
+    pub rake: i32,
+}
+
+impl HandHistory {
+    pub fn new(hand_number: u64, betting: BettingStructure, small_blind: i32,
+               big_blind: i32) -> HandHistory {
+        HandHistory {
+            hand_number,
+            betting,
+            small_blind,
+            big_blind,
+            time: String::new(),
+            table_name: String::new(),
+            max_seats: 0,
+            button: 0,
+            seats: Vec::new(),
+            blinds: Vec::new(),
+            dealt_to: Vec::new(),
+            actions: Vec::new(),
+            board: Vec::new(),
+            uncalled: Vec::new(),
+            shown: Vec::new(),
+            winnings: Vec::new(),
+            rake: 0,
+        }
+    }
+
+    pub fn seat(&self, name: &str) -> Option<&Seat> {
+        self.seats.iter().find(|s| s.name == name)
+    }
+
+    pub fn seat_mut(&mut self, name: &str) -> Option<&mut Seat> {
+        self.seats.iter_mut().find(|s| s.name == name)
+    }
+
+    pub fn actions_on(&self, street: Street) -> Vec<&Action> {
+        self.actions.iter().filter(|a| a.street == street).collect()
+    }
+
+    // the amount in each pot before rake, main pot first
+    pub fn pots(&self) -> Vec<i32> {
+        let count = self.winnings.iter().map(|w| w.2 + 1).max().unwrap_or(0);
+        let mut pots = vec![0; count];
+        for &(_, amount, pot) in &self.winnings {
+            pots[pot] += amount;
+        }
+        if let Some(main) = pots.first_mut() {
+            *main += self.rake;
+        }
+        pots
+    }
+
+    pub fn total_pot(&self) -> i32 {
+        self.pots().iter().sum()
+    }
+
+    pub fn won_by(&self, name: &str) -> i32 {
+        self.winnings.iter().filter(|w| w.0 == name).map(|w| w.1).sum()
+    }
+
+    // the street on which the player folded, if they did
+    pub fn folded_on(&self, name: &str) -> Option<Street> {
+        self.actions.iter()
+            .find(|a| a.name == name && (a.command == Command::Fold || a.command == Command::Leave))
+            .map(|a| a.street)
+    }
+}
@@ -0,0 +1,530 @@
+use game::card::Card;
+use game::player::Command;
+use game::table::Street;
+use game::betting::BettingStructure;
+use super::{HandHistory, Seat, Blind, Action};
+
+use std::cmp;
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    MissingHeader,
+    UnsupportedGame(String),
+    // (line number counting from 1, line)
+    InvalidLine(usize, String),
+    // (line number counting from 1, reason)
+    InvalidJson(usize, String),
+    NoJsonSupport,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::MissingHeader => write!(f, "No hand header found"),
+            ParseError::UnsupportedGame(ref game) => write!(f, "Unsupported game: {}", game),
+            ParseError::InvalidLine(line, ref text) => {
+                write!(f, "Line {}: can't read \"{}\"", line, text)
+            },
+            ParseError::InvalidJson(line, ref reason) => write!(f, "Line {}: {}", line, reason),
+            ParseError::NoJsonSupport => {
+                write!(f, "JSON logs can only be read with the serde feature")
+            },
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+// reads every hand in a PokerStars-format hand history. Amounts in real money
+// games are converted to cents so they fit the engine's whole chips
+pub fn parse_hands(text: &str) -> Result<Vec<HandHistory>, ParseError> {
+    let mut hands = Vec::new();
+    let mut lines: Vec<(usize, &str)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim_start_matches('\u{feff}').trim();
+        if is_header(line) && !lines.is_empty() {
+            hands.push(parse_lines(&lines)?);
+            lines.clear();
+        }
+        if !line.is_empty() {
+            lines.push((i + 1, line));
+        }
+    }
+    if !lines.is_empty() {
+        hands.push(parse_lines(&lines)?);
+    }
+    Ok(hands)
+}
+
+// our own JSON log or a PokerStars hand history, whichever `text` is
+pub fn parse_log(text: &str) -> Result<Vec<HandHistory>, ParseError> {
+    if text.trim_start().starts_with('{') {
+        parse_json(text)
+    } else {
+        parse_hands(text)
+    }
+}
+
+// reads our own JSON log, one hand to a line
+#[cfg(feature = "serde")]
+pub fn parse_json(text: &str) -> Result<Vec<HandHistory>, ParseError> {
+    text.lines()
+        .enumerate()
+        .filter(|&(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            ::serde_json::from_str(line)
+                .map_err(|err| ParseError::InvalidJson(i + 1, err.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "serde"))]
+pub fn parse_json(_text: &str) -> Result<Vec<HandHistory>, ParseError> {
+    Err(ParseError::NoJsonSupport)
+}
+
+pub fn parse_hand(text: &str) -> Result<HandHistory, ParseError> {
+    match parse_hands(text)?.pop() {
+        Some(hand) => Ok(hand),
+        None => Err(ParseError::MissingHeader),
+    }
+}
+
+fn is_header(line: &str) -> bool {
+    line.starts_with("PokerStars ") && line.contains(" #")
+}
+
+#[derive(PartialEq)]
+enum Section {
+    Seats,
+    Streets,
+    Summary,
+}
+
+fn parse_lines(lines: &[(usize, &str)]) -> Result<HandHistory, ParseError> {
+    let (first, header) = lines[0];
+    if !is_header(header) {
+        return Err(ParseError::MissingHeader);
+    }
+    let invalid = |n: usize, line: &str| ParseError::InvalidLine(n, line.to_string());
+    let (mut hand, scale) = parse_header(header).ok_or_else(|| invalid(first, header))??;
+
+    let mut section = Section::Seats;
+    let mut street = Street::PreFlop;
+    let mut street_bets: HashMap<String, i32> = HashMap::new();
+    for &(n, line) in &lines[1..] {
+        if line.starts_with("Table '") {
+            parse_table(&mut hand, line).ok_or_else(|| invalid(n, line))?;
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("*** ") {
+            if rest.starts_with("HOLE CARDS") {
+                section = Section::Streets;
+            } else if rest.starts_with("SUMMARY") {
+                section = Section::Summary;
+            } else if let Some(next) = parse_street(rest) {
+                section = Section::Streets;
+                street = next;
+                street_bets.clear();
+                let cards = bracketed(line).iter()
+                    .flat_map(|group| group.split_whitespace())
+                    .map(|card| parse_card(card).ok_or_else(|| invalid(n, line)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                hand.board = cards;
+            }
+            continue;
+        }
+
+        match section {
+            Section::Seats => {
+                if line.starts_with("Seat ") {
+                    parse_seat(&mut hand, line, scale).ok_or_else(|| invalid(n, line))?;
+                } else if let Some((name, rest)) = split_name(&hand, line, ": ") {
+                    if let Some(blind) = parse_blind(&rest, scale) {
+                        let (blind, amount) = blind.ok_or_else(|| invalid(n, line))?;
+                        push_blind(&mut hand, &mut street_bets, name, blind, amount);
+                    }
+                }
+            },
+            Section::Streets => {
+                parse_street_line(&mut hand, line, street, &mut street_bets, scale)
+                    .ok_or_else(|| invalid(n, line))?;
+            },
+            Section::Summary => {
+                parse_summary_line(&mut hand, line, scale).ok_or_else(|| invalid(n, line))?;
+            },
+        }
+    }
+
+    if hand.max_seats == 0 {
+        hand.max_seats = hand.seats.len();
+    }
+    if hand.small_blind == 0 {
+        hand.small_blind = hand.blinds.iter()
+            .find(|b| b.1 == Blind::Small)
+            .map_or(hand.big_blind / 2, |b| b.2);
+    }
+    Ok(hand)
+}
+
+// "PokerStars Hand #1: Hold'em No Limit (5/10) - 2026/10/19 08:26:07 UTC"
+// gives the hand and the factor that turns amounts into whole chips
+fn parse_header(line: &str) -> Option<Result<(HandHistory, i32), ParseError>> {
+    let start = line.find('#')? + 1;
+    let end = start + line[start..].find(':')?;
+    let hand_number = line[start..end].trim().parse().ok()?;
+    let rest = &line[end + 1..];
+
+    if !rest.contains("Hold'em") {
+        let game = rest.split(" - ").next().unwrap_or(rest).trim();
+        return Some(Err(ParseError::UnsupportedGame(game.to_string())));
+    }
+    let betting = if rest.contains("No Limit") {
+        BettingStructure::NoLimit
+    } else if rest.contains("Pot Limit") {
+        BettingStructure::PotLimit
+    } else if rest.contains("Limit") {
+        BettingStructure::FixedLimit
+    } else {
+        return None;
+    };
+
+    // the stakes are the parenthesised pair with a slash in it
+    let stakes = bracketed_by(rest, '(', ')').into_iter().find(|s| s.contains('/'))?;
+    let stakes = stakes.split_whitespace().next()?;
+    let scale = if stakes.contains(|c| "$€£".contains(c)) { 100 } else { 1 };
+    let mut split = stakes.split('/');
+    let low = parse_amount(split.next()?, scale)?;
+    let high = parse_amount(split.next()?, scale)?;
+    let (small_blind, big_blind) = match betting {
+        // limit games name their bet sizes; the blinds come from the posts
+        BettingStructure::FixedLimit => (0, low),
+        _ => (low, high),
+    };
+
+    let mut hand = HandHistory::new(hand_number, betting, small_blind, big_blind);
+    if let Some(i) = rest.rfind(" - ") {
+        hand.time = rest[i + 3..].trim().to_string();
+    }
+    Some(Ok((hand, scale)))
+}
+
+// "Table 'Rust-Poker' 5-max Seat #1 is the button"
+fn parse_table(hand: &mut HandHistory, line: &str) -> Option<()> {
+    let name = bracketed_by(line, '\'', '\'').into_iter().next()?;
+    hand.table_name = name.to_string();
+    let rest = &line[line.rfind('\'')? + 1..];
+    for word in rest.split_whitespace() {
+        if let Some(max) = word.strip_suffix("-max") {
+            hand.max_seats = max.parse().ok()?;
+        } else if let Some(button) = word.strip_prefix('#') {
+            hand.button = button.parse().ok()?;
+        }
+    }
+    Some(())
+}
+
+// "Seat 1: Santi (100 in chips)"; players sitting out weren't dealt in
+fn parse_seat(hand: &mut HandHistory, line: &str, scale: i32) -> Option<()> {
+    if line.ends_with("is sitting out") || line.ends_with("out of hand") {
+        return Some(());
+    }
+    let colon = line.find(": ")?;
+    let number = line["Seat ".len()..colon].parse().ok()?;
+    let rest = &line[colon + 2..];
+    let open = rest.rfind(" (")?;
+    let name = rest[..open].to_string();
+    let chips = rest[open + 2..].split(" in chips").next()?;
+    let chips = parse_amount(chips, scale)?;
+    hand.seats.push(Seat { number, name, chips, cards: None });
+    Some(())
+}
+
+// the blind named by "posts small blind 5" and the like, if it is one.
+// "small & big blinds" comes back as `Dead`, for `push_blind` to split up
+fn parse_blind(rest: &str, scale: i32) -> Option<Option<(Blind, i32)>> {
+    let kinds = [
+        ("posts small & big blinds ", Blind::Dead),
+        ("posts small blind ", Blind::Small),
+        ("posts big blind ", Blind::Big),
+        ("posts the ante ", Blind::Ante),
+        ("posts straddle ", Blind::Straddle),
+    ];
+    let &(prefix, blind) = kinds.iter().find(|k| rest.starts_with(k.0))?;
+    let amount = rest[prefix.len()..].split_whitespace().next();
+    Some(amount.and_then(|a| parse_amount(a, scale)).map(|a| (blind, a)))
+}
+
+// a returning player's post: a second small blind is a missed one posted
+// dead, and "small & big blinds" is a live big blind with the rest dead.
+// Any other big blind, such as a new player's, is live
+fn push_blind(hand: &mut HandHistory, street_bets: &mut HashMap<String, i32>, name: String,
+              blind: Blind, amount: i32) {
+    let posts = match blind {
+        Blind::Small if hand.blinds.iter().any(|b| b.1 == Blind::Small) => {
+            vec![(Blind::Dead, amount)]
+        },
+        Blind::Dead => {
+            let live = cmp::min(amount, hand.big_blind);
+            vec![(Blind::Big, live), (Blind::Dead, amount - live)]
+        },
+        _ => vec![(blind, amount)],
+    };
+    for (blind, amount) in posts {
+        if blind.is_live() {
+            *street_bets.entry(name.clone()).or_insert(0) += amount;
+        }
+        if amount > 0 {
+            hand.blinds.push((name.clone(), blind, amount));
+        }
+    }
+}
+
+fn parse_street(header: &str) -> Option<Street> {
+    if header.starts_with("FLOP") {
+        Some(Street::Flop)
+    } else if header.starts_with("TURN") {
+        Some(Street::Turn)
+    } else if header.starts_with("RIVER") {
+        Some(Street::River)
+    } else {
+        None
+    }
+}
+
+fn parse_street_line(hand: &mut HandHistory, line: &str, street: Street,
+                     street_bets: &mut HashMap<String, i32>, scale: i32) -> Option<()> {
+    if let Some(rest) = line.strip_prefix("Dealt to ") {
+        let name = hand.seats.iter()
+            .map(|s| s.name.clone())
+            .filter(|name| rest == name || rest.starts_with(&format!("{} [", name)))
+            .max_by_key(|name| name.len())?;
+        if let Some(cards) = bracketed(rest).first() {
+            let cards = parse_hole_cards(cards)?;
+            hand.seat_mut(&name)?.cards = Some(cards);
+            hand.dealt_to.push(name);
+        }
+        return Some(());
+    }
+    if let Some(rest) = line.strip_prefix("Uncalled bet (") {
+        let close = rest.find(')')?;
+        let amount = parse_amount(&rest[..close], scale)?;
+        let name = rest[close..].strip_prefix(") returned to ")?;
+        hand.uncalled.push((name.to_string(), amount));
+        return Some(());
+    }
+    if let Some((name, rest)) = split_name(hand, line, " collected ") {
+        let mut words = rest.splitn(2, " from ");
+        let amount = parse_amount(words.next()?, scale)?;
+        let pot = match words.next()?.trim() {
+            "pot" | "main pot" => 0,
+            "side pot" => 1,
+            from => from.strip_prefix("side pot-")?.parse().ok()?,
+        };
+        hand.winnings.push((name, amount, pot));
+        return Some(());
+    }
+    if let Some((name, rest)) = split_name(hand, line, " ") {
+        // the writer follows a fold with this line when the player leaves
+        if rest == "leaves the table" {
+            if let Some(last) = hand.actions.last_mut() {
+                if last.name == name && last.command == Command::Fold {
+                    last.command = Command::Leave;
+                }
+            }
+            return Some(());
+        }
+    }
+
+    let (name, rest) = match split_name(hand, line, ": ") {
+        Some(split) => split,
+        None => return Some(()), // chat, disconnections and so on
+    };
+    let rest = rest.trim_end_matches(" and is all-in");
+    let bet = street_bets.get(&name).cloned().unwrap_or(0);
+    let (command, amount) = if rest == "folds" || rest.starts_with("folds [") {
+        (Command::Fold, 0)
+    } else if rest == "checks" {
+        (Command::Check, 0)
+    } else if let Some(amount) = rest.strip_prefix("calls ") {
+        (Command::Call, parse_amount(amount, scale)?)
+    } else if let Some(amount) = rest.strip_prefix("bets ") {
+        let amount = parse_amount(amount, scale)?;
+        (Command::Raise(amount), amount)
+    } else if let Some(raise) = rest.strip_prefix("raises ") {
+        let mut amounts = raise.splitn(2, " to ");
+        let by = parse_amount(amounts.next()?, scale)?;
+        let to = parse_amount(amounts.next()?, scale)?;
+        (Command::Raise(by), to - bet)
+    } else if let Some(cards) = rest.strip_prefix("shows ") {
+        let cards = parse_hole_cards(bracketed(cards).first()?)?;
+        hand.seat_mut(&name)?.cards = Some(cards);
+        if !hand.shown.contains(&name) {
+            hand.shown.push(name);
+        }
+        return Some(());
+    } else if let Some(blind) = parse_blind(rest, scale) {
+        // a blind posted late, after the hole cards
+        let (blind, amount) = blind?;
+        push_blind(hand, street_bets, name, blind, amount);
+        return Some(());
+    } else {
+        return Some(()); // mucks, doesn't show, sits out, ...
+    };
+    street_bets.insert(name.clone(), bet + amount);
+    hand.actions.push(Action { street, name, command, amount });
+    Some(())
+}
+
+fn parse_summary_line(hand: &mut HandHistory, line: &str, scale: i32) -> Option<()> {
+    if line.starts_with("Total pot ") {
+        if let Some(i) = line.find("| Rake ") {
+            let rake = line[i + "| Rake ".len()..].split_whitespace().next()?;
+            hand.rake = parse_amount(rake, scale)?;
+        }
+    } else if line.starts_with("Board ") {
+        if hand.board.is_empty() {
+            let cards = bracketed(line).first()?.split_whitespace()
+                .map(parse_card)
+                .collect::<Option<Vec<_>>>()?;
+            hand.board = cards;
+        }
+    } else if line.starts_with("Seat ") {
+        // cards only shown here, e.g. "Seat 2: CPU_1 mucked [7c 2s]"
+        let colon = line.find(": ")?;
+        let rest = &line[colon + 2..];
+        let name = hand.seats.iter()
+            .map(|s| s.name.clone())
+            .filter(|name| rest.starts_with(&format!("{} ", name)))
+            .max_by_key(|name| name.len())?;
+        if let Some(cards) = bracketed(rest).first() {
+            let cards = parse_hole_cards(cards)?;
+            let seat = hand.seat_mut(&name)?;
+            if seat.cards.is_none() {
+                seat.cards = Some(cards);
+            }
+        }
+    }
+    Some(())
+}
+
+// splits "<name><separator><rest>" for a seated player, preferring the
+// longest name in case one name starts with another
+fn split_name(hand: &HandHistory, line: &str, separator: &str) -> Option<(String, String)> {
+    hand.seats.iter()
+        .filter(|s| line.starts_with(&format!("{}{}", s.name, separator)))
+        .max_by_key(|s| s.name.len())
+        .map(|s| (s.name.clone(), line[s.name.len() + separator.len()..].to_string()))
+}
+
+// "1,500", "$0.25" and "€3" as whole chips
+fn parse_amount(text: &str, scale: i32) -> Option<i32> {
+    let text: String = text.chars().filter(|c| c.is_ascii_digit() || *c == '.').collect();
+    if text.contains('.') {
+        text.parse::<f64>().ok().map(|x| (x * scale as f64).round() as i32)
+    } else {
+        text.parse::<i32>().ok().map(|x| x * scale)
+    }
+}
+
+fn parse_card(text: &str) -> Option<Rc<Card>> {
+    text.parse().ok().map(Rc::new)
+}
+
+fn parse_hole_cards(text: &str) -> Option<(Rc<Card>, Rc<Card>)> {
+    let mut cards = text.split_whitespace().map(parse_card);
+    match (cards.next(), cards.next(), cards.next()) {
+        (Some(c1), Some(c2), None) => Some((c1?, c2?)),
+        _ => None,
+    }
+}
+
+fn bracketed(text: &str) -> Vec<&str> {
+    bracketed_by(text, '[', ']')
+}
+
+// the contents of each open..close group in `text`
+fn bracketed_by(text: &str, open: char, close: char) -> Vec<&str> {
+    let mut groups = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(open) {
+        let after = &rest[start + open.len_utf8()..];
+        match after.find(close) {
+            Some(end) => {
+                groups.push(&after[..end]);
+                rest = &after[end + close.len_utf8()..];
+            },
+            None => break,
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use history::samples;
+
+    fn blinds(hand: &HandHistory) -> Vec<(&str, Blind, i32)> {
+        hand.blinds.iter().map(|b| (b.0.as_str(), b.1, b.2)).collect()
+    }
+
+    fn amounts(hand: &HandHistory) -> Vec<(&str, i32)> {
+        hand.actions.iter().map(|a| (a.name.as_str(), a.amount)).collect()
+    }
+
+    #[test]
+    fn reads_a_tournament_hand_with_antes() {
+        let hand = parse_hand(samples::ANTES).unwrap();
+        assert_eq!((hand.small_blind, hand.big_blind), (30, 60));
+        assert_eq!(blinds(&hand), vec![("Alice", Blind::Ante, 10), ("Bob", Blind::Ante, 10),
+                                       ("Carol", Blind::Ante, 10), ("Bob", Blind::Small, 30),
+                                       ("Carol", Blind::Big, 60)]);
+        // the antes don't count towards the calls
+        assert_eq!(amounts(&hand), vec![("Alice", 180), ("Bob", 0), ("Carol", 120),
+                                        ("Carol", 0), ("Alice", 200), ("Carol", 0)]);
+        assert_eq!(hand.winnings, vec![("Alice".to_string(), 420, 0)]);
+    }
+
+    #[test]
+    fn splits_returning_players_blinds_into_live_and_dead() {
+        let hand = parse_hand(samples::RETURNING_BLINDS).unwrap();
+        // a new player's big blind (tiltboy's) is live like any other
+        assert_eq!(blinds(&hand), vec![("fishy", Blind::Small, 5), ("regular", Blind::Big, 10),
+                                       ("comeback", Blind::Big, 10), ("comeback", Blind::Dead, 5),
+                                       ("lateguy", Blind::Dead, 5), ("tiltboy", Blind::Big, 10)]);
+        assert_eq!(hand.actions[4].amount, 5);
+        assert_eq!(hand.rake, 2);
+        assert_eq!(hand.uncalled, vec![("comeback".to_string(), 30)]);
+    }
+
+    #[test]
+    fn a_straddle_is_a_live_bet() {
+        let hand = parse_hand(samples::STRADDLE).unwrap();
+        assert_eq!(blinds(&hand)[2], ("Dan", Blind::Straddle, 100));
+        // Dan's raise to 900 puts in 800 on top of the straddle
+        assert_eq!(amounts(&hand)[..5], [("Ann", 300), ("Ben", 0), ("Cat", 0), ("Dan", 800),
+                                         ("Ann", 600)]);
+    }
+
+    #[test]
+    fn reads_back_what_the_writer_writes() {
+        let mut hand = HandHistory::new(7, BettingStructure::NoLimit, 5, 10);
+        hand.table_name = "Rust-Poker".to_string();
+        hand.max_seats = 3;
+        hand.button = 1;
+        for (i, name) in ["a", "b", "c"].iter().enumerate() {
+            hand.seats.push(Seat { number: i + 1, name: name.to_string(), chips: 1000,
+                                   cards: None });
+        }
+        hand.blinds = vec![("b".to_string(), Blind::Small, 5), ("c".to_string(), Blind::Big, 10),
+                           ("a".to_string(), Blind::Big, 10), ("a".to_string(), Blind::Dead, 5)];
+        let text = hand.to_string();
+        assert!(text.contains("a: posts small & big blinds 15\n"));
+        assert_eq!(parse_hand(&text).unwrap().blinds, hand.blinds);
+    }
+}
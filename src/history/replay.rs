@@ -0,0 +1,330 @@
+use game::card::Card;
+use game::player::Player;
+use game::table::{self, Table};
+use game::error::GameError;
+use game::event::{Event, EventLog, View, TableObserver};
+use super::{HandHistory, Action, Blind};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+// where a recorded hand and the engine disagree
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inconsistency {
+    Ante(String), // the engine doesn't play with antes
+    Straddle(String), // or straddles
+    CantDeal(GameError),
+    Blind { name: String, recorded: i32, posted: i32 },
+    IllegalAction { action: Action, error: GameError },
+    Amount { action: Action, engine: i32 },
+    Unfinished, // the history ran out of actions with players still to act
+    Pot { recorded: i32, engine: i32 },
+    Winnings { name: String, recorded: i32, engine: i32 },
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Inconsistency::Ante(ref name) => {
+                write!(f, "{} posts an ante, which isn't supported", name)
+            },
+            Inconsistency::Straddle(ref name) => {
+                write!(f, "{} straddles, which isn't supported", name)
+            },
+            Inconsistency::CantDeal(ref error) => write!(f, "The hand can't be dealt: {}", error),
+            Inconsistency::Blind { ref name, recorded, posted } => {
+                write!(f, "{} posts {} in blinds but the engine posts {}", name, recorded, posted)
+            },
+            Inconsistency::IllegalAction { ref action, ref error } => {
+                write!(f, "{}: {} is illegal ({})", action.name, action.command, error)
+            },
+            Inconsistency::Amount { ref action, engine } => {
+                write!(f, "{}: {} puts in {} but the engine puts in {}",
+                       action.name, action.command, action.amount, engine)
+            },
+            Inconsistency::Unfinished => write!(f, "The hand ends with players still to act"),
+            Inconsistency::Pot { recorded, engine } => {
+                write!(f, "The pot is {} but the engine makes it {}", recorded, engine)
+            },
+            Inconsistency::Winnings { ref name, recorded, engine } => {
+                write!(f, "{} wins {} but the engine awards {}", name, recorded, engine)
+            },
+        }
+    }
+}
+
+// replays a recorded hand on a table dealt the same cards, checking every
+// action against the engine's rules along the way
+pub struct Replay {
+    hand: HandHistory,
+    table: Table,
+    log: Rc<RefCell<EventLog>>,
+    next: usize, // the next recorded action
+    started: bool,
+    done: bool,
+    issues: Vec<Inconsistency>,
+}
+
+impl Replay {
+    pub fn new(hand: HandHistory) -> Replay {
+        let mut table = Table::build_table();
+        table.set_blinds(hand.small_blind, hand.big_blind);
+        table.set_betting_structure(hand.betting);
+        let mut seats: Vec<_> = hand.seats.iter().collect();
+        seats.sort_by_key(|s| s.number);
+        let big_blind = hand.blinds.iter().find(|b| b.1 == Blind::Big).map(|b| &b.0);
+        for seat in &seats {
+            // a big blind from anyone but the big blind, like a new player's,
+            // is owed the same way as a missed one
+            let mut player = Player::with_chips(seat.name.clone(), false, seat.chips);
+            let posted = |blind| hand.blinds.iter().any(|b| b.0 == seat.name && b.1 == blind);
+            player.missed_small_blind = posted(Blind::Dead);
+            player.missed_big_blind = posted(Blind::Big) && big_blind != Some(&seat.name);
+            table.add_player(player);
+        }
+        // with the button on an empty seat, the next player along gets it
+        let button = seats.iter().position(|s| s.number >= hand.button).unwrap_or(0);
+        table.set_button(button);
+        table.stack_deck(recorded_deck(&hand, seats.len(), button));
+
+        let log = Rc::new(RefCell::new(EventLog::default()));
+        table.add_observer(View::Omniscient, log.clone());
+        Replay { hand, table, log, next: 0, started: false, done: false, issues: Vec::new() }
+    }
+
+    // observers added before the first step see the whole hand
+    pub fn add_observer(&mut self, view: View, observer: Rc<RefCell<dyn TableObserver>>) {
+        self.table.add_observer(view, observer);
+    }
+
+    pub fn hand(&self) -> &HandHistory {
+        &self.hand
+    }
+
+    pub fn table(&self) -> &Table {
+        &self.table
+    }
+
+    pub fn issues(&self) -> &[Inconsistency] {
+        &self.issues
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.done
+    }
+
+    // applies the next recorded action, returning it
+    pub fn step(&mut self) -> Option<Action> {
+        if !self.started {
+            self.start();
+        }
+        if self.done {
+            return None;
+        }
+        let action = match self.hand.actions.get(self.next) {
+            Some(action) => action.clone(),
+            None => {
+                self.finish();
+                return None;
+            },
+        };
+        self.next += 1;
+
+        let result = match self.table.seat_of(&action.name) {
+            Some(seat) => self.table.act(seat, action.command.clone()),
+            None => Err(GameError::UnknownPlayer(action.name.clone())),
+        };
+        if let Err(error) = result {
+            // the table can't follow the history any further
+            self.issues.push(Inconsistency::IllegalAction { action: action.clone(), error });
+            self.done = true;
+            return Some(action);
+        }
+        let engine = self.log.borrow().events.iter().rev()
+            .filter_map(|e| match *e {
+                Event::Action { amount, .. } => Some(amount),
+                _ => None,
+            })
+            .next()
+            .unwrap_or(0);
+        if engine != action.amount {
+            self.issues.push(Inconsistency::Amount { action: action.clone(), engine });
+        }
+        Some(action)
+    }
+
+    // steps until the next street is dealt or the hand is over
+    pub fn play_street(&mut self) {
+        let street = self.table.street();
+        while !self.done && self.step().is_some() {
+            if self.table.street() != street || !self.table.is_hand_in_progress() {
+                break;
+            }
+        }
+        if !self.done && self.next == self.hand.actions.len() && !self.table.is_hand_in_progress() {
+            self.finish();
+        }
+    }
+
+    // replays the rest of the hand
+    pub fn run(&mut self) -> &[Inconsistency] {
+        while self.step().is_some() {}
+        &self.issues
+    }
+
+    fn start(&mut self) {
+        self.started = true;
+        for &(ref name, blind, _) in &self.hand.blinds {
+            match blind {
+                Blind::Ante => self.issues.push(Inconsistency::Ante(name.clone())),
+                Blind::Straddle => self.issues.push(Inconsistency::Straddle(name.clone())),
+                _ => {},
+            }
+        }
+        if let Err(error) = self.table.start_hand() {
+            self.issues.push(Inconsistency::CantDeal(error));
+            self.done = true;
+            return;
+        }
+
+        let mut posted: HashMap<String, i32> = HashMap::new();
+        for event in &self.log.borrow().events {
+            match *event {
+                Event::BlindPosted { ref name, amount }
+                | Event::DeadBlindPosted { ref name, amount } => {
+                    *posted.entry(name.clone()).or_insert(0) += amount;
+                },
+                _ => {},
+            }
+        }
+        let mut recorded: HashMap<String, i32> = HashMap::new();
+        for &(ref name, blind, amount) in &self.hand.blinds {
+            if blind != Blind::Ante && blind != Blind::Straddle {
+                *recorded.entry(name.clone()).or_insert(0) += amount;
+            }
+        }
+        let mut names: Vec<&String> = posted.keys().chain(recorded.keys()).collect();
+        names.sort();
+        names.dedup();
+        for name in names {
+            let recorded = recorded.get(name).cloned().unwrap_or(0);
+            let posted = posted.get(name).cloned().unwrap_or(0);
+            if recorded != posted {
+                self.issues.push(Inconsistency::Blind { name: name.clone(), recorded, posted });
+            }
+        }
+    }
+
+    // checks the pots once the recorded actions run out
+    fn finish(&mut self) {
+        self.done = true;
+        if self.table.is_hand_in_progress() {
+            self.issues.push(Inconsistency::Unfinished);
+            return;
+        }
+
+        let mut awarded: HashMap<String, i32> = HashMap::new();
+        for event in &self.log.borrow().events {
+            if let Event::PotAwarded { ref name, amount, .. } = *event {
+                *awarded.entry(name.clone()).or_insert(0) += amount;
+            }
+        }
+        // the engine doesn't rake replays, so its pot is the whole pot
+        let engine = awarded.values().sum();
+        let recorded = self.hand.total_pot();
+        if recorded != engine {
+            self.issues.push(Inconsistency::Pot { recorded, engine });
+        }
+        for seat in &self.hand.seats {
+            let recorded = self.hand.won_by(&seat.name);
+            let engine = awarded.get(&seat.name).cloned().unwrap_or(0);
+            let wrong = if self.hand.rake == 0 {
+                recorded != engine
+            } else {
+                // rake comes out of the winners' share
+                recorded > engine || (engine > 0) != (recorded > 0)
+            };
+            if wrong {
+                let name = seat.name.clone();
+                self.issues.push(Inconsistency::Winnings { name, recorded, engine });
+            }
+        }
+    }
+}
+
+// the cards in the order the table deals them: two to each player from the
+// button round, then the board. Cards the history doesn't show are filled in
+// from the rest of the deck
+fn recorded_deck(hand: &HandHistory, players: usize, button: usize) -> Vec<Rc<Card>> {
+    let mut seats: Vec<_> = hand.seats.iter().collect();
+    seats.sort_by_key(|s| s.number);
+    let known: Vec<Card> = seats.iter()
+        .filter_map(|s| s.cards.as_ref())
+        .flat_map(|(c1, c2)| vec![**c1, **c2])
+        .chain(hand.board.iter().map(|c| **c))
+        .collect();
+    let mut unseen = table::build_deck().into_iter().filter(|c| !known.contains(c));
+
+    let mut deck = Vec::new();
+    for i in 0..players {
+        match seats[(button + i) % players].cards {
+            Some((ref c1, ref c2)) => deck.extend_from_slice(&[c1.clone(), c2.clone()]),
+            None => deck.extend(unseen.by_ref().take(2)),
+        }
+    }
+    deck.extend(hand.board.iter().cloned());
+    deck.extend(unseen.take(5 - hand.board.len().min(5)));
+    deck
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::player::Command;
+    use history::parser;
+    use history::samples;
+
+    #[test]
+    fn returning_and_new_players_blinds_replay_cleanly() {
+        let hand = parser::parse_hand(samples::RETURNING_BLINDS).unwrap();
+        assert_eq!(Replay::new(hand).run(), &[]);
+    }
+
+    #[test]
+    fn antes_are_flagged() {
+        let hand = parser::parse_hand(samples::ANTES).unwrap();
+        let issues = Replay::new(hand).run().to_vec();
+        assert_eq!(issues[..3], [Inconsistency::Ante("Alice".to_string()),
+                                 Inconsistency::Ante("Bob".to_string()),
+                                 Inconsistency::Ante("Carol".to_string())]);
+    }
+
+    #[test]
+    fn a_raise_below_the_minimum_is_illegal() {
+        let mut hand = parser::parse_hand(samples::RETURNING_BLINDS).unwrap();
+        // tiltboy raises 5 over the big blind instead of checking
+        let action = hand.actions[2].clone();
+        assert_eq!(action.name, "tiltboy");
+        hand.actions[2] = Action { command: Command::Raise(5), amount: 5, ..action };
+        let mut replay = Replay::new(hand);
+        let issues = replay.run();
+        assert_eq!(issues.len(), 1);
+        match issues[0] {
+            Inconsistency::IllegalAction { ref action, .. } => {
+                assert_eq!(action.command, Command::Raise(5));
+            },
+            ref issue => panic!("unexpected {:?}", issue),
+        }
+    }
+
+    #[test]
+    fn a_wrong_pot_is_flagged() {
+        let mut hand = parser::parse_hand(samples::RETURNING_BLINDS).unwrap();
+        hand.winnings[0].1 = 60;
+        let issues = Replay::new(hand).run().to_vec();
+        assert!(issues.contains(&Inconsistency::Winnings { name: "comeback".to_string(),
+                                                           recorded: 60, engine: 50 }));
+    }
+}
@@ -0,0 +1,114 @@
+// hand histories as PokerStars writes them
+
+// a tournament hand with antes
+pub const ANTES: &str = "\
+PokerStars Hand #208833496578: Tournament #2982736424, $0.98+$0.12 USD Hold'em No Limit - \
+Level IV (30/60) - 2020/02/03 18:30:45 ET
+Table '2982736424 1' 9-max Seat #1 is the button
+Seat 1: Alice (1500 in chips)
+Seat 2: Bob (1480 in chips)
+Seat 3: Carol (1520 in chips)
+Alice: posts the ante 10
+Bob: posts the ante 10
+Carol: posts the ante 10
+Bob: posts small blind 30
+Carol: posts big blind 60
+*** HOLE CARDS ***
+Dealt to Alice [Ah Kd]
+Alice: raises 120 to 180
+Bob: folds
+Carol: calls 120
+*** FLOP *** [7c 2d Ks]
+Carol: checks
+Alice: bets 200
+Carol: folds
+Uncalled bet (200) returned to Alice
+Alice collected 420 from pot
+Alice: doesn't show hand
+*** SUMMARY ***
+Total pot 420 | Rake 0
+Board [7c 2d Ks]
+Seat 1: Alice (button) collected (420)
+Seat 2: Bob (small blind) folded before Flop
+Seat 3: Carol (big blind) folded on the Flop
+";
+
+// a cash game hand where comeback returns after missing both blinds, lateguy
+// after missing the small blind and tiltboy posts to sit in
+pub const RETURNING_BLINDS: &str = "\
+PokerStars Hand #231412345678:  Hold'em No Limit ($0.05/$0.10 USD) - 2021/11/02 21:14:09 ET
+Table 'Aase III' 6-max Seat #2 is the button
+Seat 1: tiltboy ($10 in chips)
+Seat 2: nitro88 ($9.75 in chips)
+Seat 3: fishy ($10.20 in chips)
+Seat 4: regular ($10 in chips)
+Seat 5: comeback ($8.50 in chips)
+Seat 6: lateguy ($10 in chips)
+fishy: posts small blind $0.05
+regular: posts big blind $0.10
+comeback: posts small & big blinds $0.15
+lateguy: posts small blind $0.05
+tiltboy: posts big blind $0.10
+*** HOLE CARDS ***
+comeback: checks
+lateguy: folds
+tiltboy: checks
+nitro88: folds
+fishy: calls $0.05
+regular: checks
+*** FLOP *** [Qd 8h 3c]
+fishy: checks
+regular: checks
+comeback: checks
+tiltboy: checks
+*** TURN *** [Qd 8h 3c] [2s]
+fishy: checks
+regular: checks
+comeback: bets $0.30
+tiltboy: folds
+fishy: folds
+regular: folds
+Uncalled bet ($0.30) returned to comeback
+comeback collected $0.48 from pot
+comeback: doesn't show hand
+*** SUMMARY ***
+Total pot $0.50 | Rake $0.02
+Board [Qd 8h 3c 2s]
+Seat 1: tiltboy folded on the Turn
+Seat 2: nitro88 (button) folded before Flop (didn't bet)
+Seat 3: fishy (small blind) folded on the Turn
+Seat 4: regular (big blind) folded on the Turn
+Seat 5: comeback collected ($0.48)
+Seat 6: lateguy folded before Flop
+";
+
+// a home game hand with a straddle
+pub const STRADDLE: &str = "\
+PokerStars Hand #245678901234:  Hold'em No Limit (25/50) - 2023/03/12 22:01:13 ET
+Table 'Home Game' 6-max Seat #1 is the button
+Seat 1: Ann (5000 in chips)
+Seat 2: Ben (5000 in chips)
+Seat 3: Cat (5000 in chips)
+Seat 4: Dan (5000 in chips)
+Ben: posts small blind 25
+Cat: posts big blind 50
+Dan: posts straddle 100
+*** HOLE CARDS ***
+Ann: raises 200 to 300
+Ben: folds
+Cat: folds
+Dan: raises 600 to 900
+Ann: calls 600
+*** FLOP *** [Jc 9d 2h]
+Dan: bets 1000
+Ann: folds
+Uncalled bet (1000) returned to Dan
+Dan collected 1875 from pot
+*** SUMMARY ***
+Total pot 1875 | Rake 0
+Board [Jc 9d 2h]
+Seat 1: Ann (button) folded on the Flop
+Seat 2: Ben (small blind) folded before Flop
+Seat 3: Cat (big blind) folded before Flop
+Seat 4: Dan collected (1875)
+";
@@ -0,0 +1,347 @@
+use game::player::Command;
+use game::table::Street;
+use game::event::{Event, TableObserver};
+use game::error::GameError;
+
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, BufRead, Write};
+
+// first line of every stats file
+pub const STATS_HEADER: &str = "rust-poker stats 1";
+
+// the counts behind one player's statistics, kept rather than the ratios so
+// sessions can be added up
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayerStats {
+    pub hands: u32,
+    pub vpip: u32, // hands they put money in preflop without being made to
+    pub pfr: u32, // hands they raised preflop
+    pub three_bet_chances: u32, // hands they acted facing a single raise
+    pub three_bets: u32,
+    pub bets_and_raises: u32, // after the flop
+    pub calls: u32, // after the flop
+    pub cbets_faced: u32,
+    pub folds_to_cbet: u32,
+    pub flops_seen: u32,
+    pub showdowns: u32,
+    pub showdowns_won: u32,
+}
+
+fn ratio(count: u32, out_of: u32) -> Option<f64> {
+    if out_of == 0 { None } else { Some(count as f64 / out_of as f64) }
+}
+
+impl PlayerStats {
+    pub fn vpip(&self) -> Option<f64> {
+        ratio(self.vpip, self.hands)
+    }
+
+    pub fn pfr(&self) -> Option<f64> {
+        ratio(self.pfr, self.hands)
+    }
+
+    pub fn three_bet(&self) -> Option<f64> {
+        ratio(self.three_bets, self.three_bet_chances)
+    }
+
+    // bets and raises for every call after the flop
+    pub fn aggression(&self) -> Option<f64> {
+        ratio(self.bets_and_raises, self.calls)
+    }
+
+    pub fn fold_to_cbet(&self) -> Option<f64> {
+        ratio(self.folds_to_cbet, self.cbets_faced)
+    }
+
+    // went to showdown, out of the flops they saw
+    pub fn wtsd(&self) -> Option<f64> {
+        ratio(self.showdowns, self.flops_seen)
+    }
+
+    // won money at showdown, out of the showdowns they went to
+    pub fn won_at_showdown(&self) -> Option<f64> {
+        ratio(self.showdowns_won, self.showdowns)
+    }
+
+    fn counts(&self) -> [u32; 12] {
+        [self.hands, self.vpip, self.pfr, self.three_bet_chances, self.three_bets,
+         self.bets_and_raises, self.calls, self.cbets_faced, self.folds_to_cbet,
+         self.flops_seen, self.showdowns, self.showdowns_won]
+    }
+
+    fn from_counts(counts: &[u32]) -> PlayerStats {
+        PlayerStats {
+            hands: counts[0],
+            vpip: counts[1],
+            pfr: counts[2],
+            three_bet_chances: counts[3],
+            three_bets: counts[4],
+            bets_and_raises: counts[5],
+            calls: counts[6],
+            cbets_faced: counts[7],
+            folds_to_cbet: counts[8],
+            flops_seen: counts[9],
+            showdowns: counts[10],
+            showdowns_won: counts[11],
+        }
+    }
+}
+
+// what's happened so far in the hand being tracked
+#[derive(Debug)]
+struct HandState {
+    street: Street,
+    players: Vec<String>,
+    folded: HashSet<String>,
+    vpip: HashSet<String>,
+    pfr: HashSet<String>,
+    three_bet_chances: HashSet<String>,
+    raises: usize, // on this street
+    preflop_raiser: Option<String>,
+    // whether the flop's only bet so far is the preflop raiser's c-bet
+    cbet: bool,
+    cbets_faced: HashSet<String>,
+    shown: HashSet<String>,
+    won: HashSet<String>,
+}
+
+impl HandState {
+    fn new(players: Vec<String>) -> HandState {
+        HandState {
+            street: Street::PreFlop,
+            players,
+            folded: HashSet::new(),
+            vpip: HashSet::new(),
+            pfr: HashSet::new(),
+            three_bet_chances: HashSet::new(),
+            raises: 0,
+            preflop_raiser: None,
+            cbet: false,
+            cbets_faced: HashSet::new(),
+            shown: HashSet::new(),
+            won: HashSet::new(),
+        }
+    }
+}
+
+// keeps every player's statistics from the events of the hands they play.
+// Only public events are needed, so it can watch as a spectator
+#[derive(Debug, Default)]
+pub struct StatsTracker {
+    players: BTreeMap<String, PlayerStats>,
+    hand: Option<HandState>,
+}
+
+impl StatsTracker {
+    pub fn new() -> StatsTracker {
+        StatsTracker::default()
+    }
+
+    pub fn player(&self, name: &str) -> Option<&PlayerStats> {
+        self.players.get(name)
+    }
+
+    pub fn players(&self) -> &BTreeMap<String, PlayerStats> {
+        &self.players
+    }
+
+    fn stats(&mut self, name: &str) -> &mut PlayerStats {
+        self.players.entry(name.to_string()).or_default()
+    }
+
+    // one line per player of their counts, the name last since it may
+    // contain spaces
+    pub fn save<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "{}", STATS_HEADER)?;
+        for (name, stats) in &self.players {
+            let counts: Vec<String> = stats.counts().iter().map(|n| n.to_string()).collect();
+            writeln!(out, "{} {}", counts.join(" "), name)?;
+        }
+        out.flush()
+    }
+
+    pub fn load<R: BufRead>(input: R) -> Result<StatsTracker, GameError> {
+        let invalid = |reason: &str| GameError::InvalidStats(reason.to_string());
+        let mut lines = input.lines();
+        match lines.next() {
+            Some(Ok(ref header)) if header == STATS_HEADER => {},
+            _ => return Err(invalid("not a stats file")),
+        }
+        let mut tracker = StatsTracker::new();
+        for line in lines {
+            let line = line.map_err(|err| invalid(&err.to_string()))?;
+            let fields: Vec<&str> = line.splitn(13, ' ').collect();
+            let counts: Option<Vec<u32>> = fields.iter().take(12).map(|n| n.parse().ok()).collect();
+            match (counts, fields.get(12)) {
+                (Some(ref counts), Some(name)) if counts.len() == 12 => {
+                    tracker.players.insert(name.to_string(), PlayerStats::from_counts(counts));
+                },
+                _ => return Err(invalid(&format!("bad line \"{}\"", line))),
+            }
+        }
+        Ok(tracker)
+    }
+
+    fn action(&mut self, name: &str, command: &Command) {
+        let mut hand = match self.hand.take() {
+            Some(hand) => hand,
+            None => return,
+        };
+        let street = hand.street;
+        let facing_one_raise = hand.raises == 1;
+        let raiser = hand.preflop_raiser.as_ref().map(|r| r == name);
+        let raised = matches!(*command, Command::Raise(_));
+        let folded = matches!(*command, Command::Fold | Command::Leave);
+        if folded {
+            hand.folded.insert(name.to_string());
+        }
+        if street == Street::PreFlop {
+            let voluntary = raised || *command == Command::Call;
+            if voluntary && hand.vpip.insert(name.to_string()) {
+                self.stats(name).vpip += 1;
+            }
+            if raised && hand.pfr.insert(name.to_string()) {
+                self.stats(name).pfr += 1;
+            }
+            if facing_one_raise && raiser != Some(true)
+                && hand.three_bet_chances.insert(name.to_string()) {
+                let stats = self.stats(name);
+                stats.three_bet_chances += 1;
+                stats.three_bets += raised as u32;
+            }
+            if raised {
+                hand.preflop_raiser = Some(name.to_string());
+            }
+        } else {
+            let stats = self.stats(name);
+            stats.bets_and_raises += raised as u32;
+            stats.calls += (*command == Command::Call) as u32;
+            if street == Street::Flop {
+                if hand.cbet && hand.cbets_faced.insert(name.to_string()) {
+                    let stats = self.stats(name);
+                    stats.cbets_faced += 1;
+                    stats.folds_to_cbet += folded as u32;
+                }
+                // a c-bet is the preflop raiser opening the betting on the flop
+                if raised {
+                    hand.cbet = hand.raises == 0 && raiser == Some(true);
+                }
+            }
+        }
+        if raised {
+            hand.raises += 1;
+        }
+        self.hand = Some(hand);
+    }
+
+    fn finish_hand(&mut self) {
+        let hand = match self.hand.take() {
+            Some(hand) => hand,
+            None => return,
+        };
+        for name in &hand.shown {
+            let stats = self.stats(name);
+            stats.showdowns += 1;
+            stats.showdowns_won += hand.won.contains(name) as u32;
+        }
+    }
+}
+
+impl TableObserver for StatsTracker {
+    fn notify(&mut self, event: &Event) {
+        match *event {
+            Event::HandStarted { ref seats, .. } => {
+                self.finish_hand();
+                for seat in seats {
+                    self.stats(&seat.1).hands += 1;
+                }
+                self.hand = Some(HandState::new(seats.iter().map(|s| s.1.clone()).collect()));
+            },
+            Event::Action { ref name, ref command, .. } => self.action(name, command),
+            Event::StreetDealt { street, .. } => {
+                let mut hand = match self.hand.take() {
+                    Some(hand) => hand,
+                    None => return,
+                };
+                if street == Street::Flop {
+                    for name in hand.players.iter().filter(|name| !hand.folded.contains(*name)) {
+                        self.stats(name).flops_seen += 1;
+                    }
+                }
+                hand.street = street;
+                hand.raises = 0;
+                self.hand = Some(hand);
+            },
+            Event::Showdown { ref name, .. } => {
+                if let Some(ref mut hand) = self.hand {
+                    hand.shown.insert(name.clone());
+                }
+            },
+            Event::PotAwarded { ref name, .. } => {
+                if let Some(ref mut hand) = self.hand {
+                    if hand.shown.contains(name) {
+                        hand.won.insert(name.clone());
+                    }
+                }
+            },
+            Event::HandFinished { .. } => self.finish_hand(),
+            _ => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn start(tracker: &mut StatsTracker, names: &[&str]) {
+        let seats = names.iter().enumerate().map(|(i, name)| (i, name.to_string(), 1000)).collect();
+        tracker.notify(&Event::HandStarted { hand_number: 1, button: names[0].to_string(), seats });
+    }
+
+    fn act(tracker: &mut StatsTracker, name: &str, command: Command) {
+        tracker.notify(&Event::Action { name: name.to_string(), command, amount: 0 });
+    }
+
+    #[test]
+    fn counts_vpip_and_pfr() {
+        let mut tracker = StatsTracker::new();
+        // a raises, b calls, c folds, then d three-bets and everyone folds
+        start(&mut tracker, &["a", "b", "c", "d"]);
+        act(&mut tracker, "a", Command::Raise(20));
+        act(&mut tracker, "b", Command::Call);
+        act(&mut tracker, "c", Command::Fold);
+        act(&mut tracker, "d", Command::Raise(60));
+        act(&mut tracker, "a", Command::Fold);
+        act(&mut tracker, "b", Command::Fold);
+        tracker.notify(&Event::HandFinished { hand_number: 1 });
+        // a checked big blind isn't voluntary
+        start(&mut tracker, &["a", "b", "c", "d"]);
+        act(&mut tracker, "a", Command::Call);
+        act(&mut tracker, "b", Command::Fold);
+        act(&mut tracker, "c", Command::Fold);
+        act(&mut tracker, "d", Command::Check);
+        tracker.notify(&Event::HandFinished { hand_number: 2 });
+
+        let stats = |name| tracker.player(name).unwrap();
+        assert_eq!((stats("a").vpip(), stats("a").pfr()), (Some(1.0), Some(0.5)));
+        assert_eq!((stats("b").vpip(), stats("b").pfr()), (Some(0.5), Some(0.0)));
+        assert_eq!((stats("c").vpip(), stats("c").pfr()), (Some(0.0), Some(0.0)));
+        assert_eq!((stats("d").vpip(), stats("d").pfr()), (Some(0.5), Some(0.5)));
+        assert_eq!(stats("d").three_bet(), Some(1.0));
+        assert_eq!(stats("b").three_bet(), Some(0.0));
+        assert_eq!(stats("a").three_bet(), None);
+    }
+
+    #[test]
+    fn saved_stats_load_back() {
+        let mut tracker = StatsTracker::new();
+        start(&mut tracker, &["a", "the big fish"]);
+        act(&mut tracker, "a", Command::Raise(20));
+        act(&mut tracker, "the big fish", Command::Fold);
+        tracker.notify(&Event::HandFinished { hand_number: 1 });
+        let mut saved = Vec::new();
+        tracker.save(&mut saved).unwrap();
+        assert_eq!(StatsTracker::load(&saved[..]).unwrap().players(), tracker.players());
+    }
+}
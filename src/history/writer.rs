@@ -0,0 +1,494 @@
+use game::card::{Card, Hand};
+use game::player::Command;
+use game::table::{Table, Street};
+use game::betting::BettingStructure;
+use game::event::{Event, TableObserver};
+use super::{HandHistory, Seat, Blind, Action};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const TABLE_NAME: &str = "Rust-Poker";
+
+// writes every hand the table plays in PokerStars' text format, or as our
+// own JSON log. It should be registered with an omniscient view; only the
+// hero's hole cards are written up front, everyone else's are written if
+// they reach showdown
+pub struct HandHistoryWriter<W: Write> {
+    out: W,
+    hero: Option<String>,
+    table_name: String,
+    max_seats: usize,
+    betting: BettingStructure,
+    blinds: (i32, i32),
+    hand: Option<HandHistory>,
+    street: Street,
+    #[cfg(feature = "serde")]
+    json: bool,
+    error: Option<io::Error>,
+}
+
+impl HandHistoryWriter<File> {
+    // appends to the file at `path`, creating it if needed
+    pub fn create<P: AsRef<Path>>(path: P, table: &Table) -> io::Result<HandHistoryWriter<File>> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(HandHistoryWriter::new(file, table))
+    }
+}
+
+impl<W: Write> HandHistoryWriter<W> {
+    pub fn new(out: W, table: &Table) -> HandHistoryWriter<W> {
+        HandHistoryWriter {
+            out,
+            hero: None,
+            table_name: TABLE_NAME.to_string(),
+            max_seats: table.players().len(),
+            betting: table.betting_structure(),
+            blinds: (table.small_blind, table.big_blind),
+            hand: None,
+            street: Street::PreFlop,
+            #[cfg(feature = "serde")]
+            json: false,
+            error: None,
+        }
+    }
+
+    // without a hero, every player's hole cards are written
+    pub fn hero(mut self, name: &str) -> HandHistoryWriter<W> {
+        self.hero = Some(name.to_string());
+        self
+    }
+
+    pub fn table_name(mut self, name: &str) -> HandHistoryWriter<W> {
+        self.table_name = name.to_string();
+        self
+    }
+
+    // one hand to a line of JSON, which `parser::parse_json` reads back
+    #[cfg(feature = "serde")]
+    pub fn json(mut self) -> HandHistoryWriter<W> {
+        self.json = true;
+        self
+    }
+
+    fn format(&self, hand: &HandHistory) -> io::Result<String> {
+        #[cfg(feature = "serde")]
+        {
+            if self.json {
+                // the same hole cards as the text shows
+                let mut hand = hand.clone();
+                for seat in &mut hand.seats {
+                    if !hand.dealt_to.contains(&seat.name) && !hand.shown.contains(&seat.name) {
+                        seat.cards = None;
+                    }
+                }
+                let json = ::serde_json::to_string(&hand).map_err(io::Error::other)?;
+                return Ok(json + "\n");
+            }
+        }
+        Ok(format!("{}\n\n\n", hand))
+    }
+
+    // the first write that failed; nothing more is written after it
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    fn write_hand(&mut self, hand: &HandHistory) {
+        if self.error.is_some() {
+            return;
+        }
+        let result = self.format(hand)
+            .and_then(|text| self.out.write_all(text.as_bytes()))
+            .and_then(|_| self.out.flush());
+        if let Err(err) = result {
+            self.error = Some(err);
+        }
+    }
+}
+
+impl<W: Write> TableObserver for HandHistoryWriter<W> {
+    fn notify(&mut self, event: &Event) {
+        if let Event::HandStarted { hand_number, ref button, ref seats } = *event {
+            let (small_blind, big_blind) = self.blinds;
+            let mut hand = HandHistory::new(hand_number, self.betting, small_blind, big_blind);
+            hand.time = format_time(SystemTime::now());
+            hand.table_name = self.table_name.clone();
+            hand.max_seats = self.max_seats.max(seats.len());
+            for &(seat, ref name, chips) in seats {
+                hand.seats.push(Seat { number: seat + 1, name: name.clone(), chips, cards: None });
+            }
+            hand.button = hand.seat(button).map_or(1, |s| s.number);
+            self.hand = Some(hand);
+            self.street = Street::PreFlop;
+            return;
+        }
+
+        let hand = match self.hand {
+            Some(ref mut hand) => hand,
+            None => return,
+        };
+        match *event {
+            Event::BlindPosted { ref name, amount } => {
+                let blind = if hand.blinds.is_empty() { Blind::Small } else { Blind::Big };
+                hand.blinds.push((name.clone(), blind, amount));
+            },
+            Event::DeadBlindPosted { ref name, amount } => {
+                hand.blinds.push((name.clone(), Blind::Dead, amount));
+            },
+            Event::HoleCards { ref name, ref cards } => {
+                if let Some(seat) = hand.seat_mut(name) {
+                    seat.cards = Some(cards.clone());
+                }
+                if self.hero.as_ref().is_none_or(|hero| hero == name) {
+                    hand.dealt_to.push(name.clone());
+                }
+            },
+            Event::Action { ref name, ref command, amount } => {
+                hand.actions.push(Action { street: self.street, name: name.clone(),
+                                           command: command.clone(), amount });
+            },
+            Event::StreetDealt { street, ref cards } => {
+                self.street = street;
+                hand.board = cards.clone();
+            },
+            Event::UncalledBet { ref name, amount } => hand.uncalled.push((name.clone(), amount)),
+            Event::Showdown { ref name, .. } => hand.shown.push(name.clone()),
+            Event::PotAwarded { ref name, amount, pot } => {
+                hand.winnings.push((name.clone(), amount, pot));
+            },
+            Event::Rake { amount, .. } => hand.rake = amount,
+            Event::HandFinished { .. } => {
+                if let Some(hand) = self.hand.take() {
+                    self.write_hand(&hand);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+impl fmt::Display for HandHistory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (game, stakes) = match self.betting {
+            BettingStructure::NoLimit => ("No Limit", (self.small_blind, self.big_blind)),
+            BettingStructure::PotLimit => ("Pot Limit", (self.small_blind, self.big_blind)),
+            BettingStructure::FixedLimit => ("Limit", (self.big_blind, 2 * self.big_blind)),
+        };
+        writeln!(f, "PokerStars Hand #{}: Hold'em {} ({}/{}) - {}",
+                 self.hand_number, game, stakes.0, stakes.1, self.time)?;
+        writeln!(f, "Table '{}' {}-max Seat #{} is the button",
+                 self.table_name, self.max_seats, self.button)?;
+        for seat in &self.seats {
+            writeln!(f, "Seat {}: {} ({} in chips)", seat.number, seat.name, seat.chips)?;
+        }
+
+        let mut stacks: HashMap<&str, i32> = self.seats.iter()
+            .map(|s| (s.name.as_str(), s.chips))
+            .collect();
+        let mut street_bets: HashMap<&str, i32> = HashMap::new();
+        let mut blinds = self.blinds.iter().peekable();
+        while let Some(&(ref name, blind, amount)) = blinds.next() {
+            *stacks.entry(name).or_insert(0) -= amount;
+            if blind.is_live() {
+                *street_bets.entry(name).or_insert(0) += amount;
+            }
+            // a player coming back after missing both blinds posts the big
+            // one live and the small one dead, which PokerStars writes as one
+            let dead = match blinds.peek() {
+                Some(&&(ref next, Blind::Dead, dead)) if blind == Blind::Big && next == name => {
+                    dead
+                },
+                _ => 0,
+            };
+            let posted = match blind {
+                Blind::Big if dead > 0 => {
+                    blinds.next();
+                    *stacks.entry(name).or_insert(0) -= dead;
+                    "small & big blinds"
+                },
+                Blind::Small | Blind::Dead => "small blind",
+                Blind::Big => "big blind",
+                Blind::Ante => "the ante",
+                Blind::Straddle => "straddle",
+            };
+            writeln!(f, "{}: posts {} {}{}", name, posted, amount + dead, all_in(&stacks, name))?;
+        }
+
+        writeln!(f, "*** HOLE CARDS ***")?;
+        for name in &self.dealt_to {
+            if let Some((c1, c2)) = self.seat(name).and_then(|s| s.cards.as_ref()) {
+                writeln!(f, "Dealt to {} [{} {}]", name, c1.short(), c2.short())?;
+            }
+        }
+
+        let streets = [Street::PreFlop, Street::Flop, Street::Turn, Street::River];
+        for &street in &streets {
+            if street != Street::PreFlop {
+                let shown = match street {
+                    Street::Flop => 3,
+                    Street::Turn => 4,
+                    _ => 5,
+                };
+                if self.board.len() < shown {
+                    break;
+                }
+                street_bets.clear();
+                // the newest card goes in its own brackets after the flop
+                let split = if shown == 3 { 3 } else { shown - 1 };
+                let (before, after) = self.board[..shown].split_at(split);
+                let name = street_name(street).to_uppercase();
+                write!(f, "*** {} *** [{}]", name, short_cards(before))?;
+                if !after.is_empty() {
+                    write!(f, " [{}]", short_cards(after))?;
+                }
+                writeln!(f)?;
+            }
+
+            let mut current_bet = street_bets.values().cloned().max().unwrap_or(0);
+            for action in self.actions_on(street) {
+                let name = action.name.as_str();
+                *stacks.entry(name).or_insert(0) -= action.amount;
+                let bet = street_bets.entry(name).or_insert(0);
+                *bet += action.amount;
+                match action.command {
+                    Command::Fold | Command::Leave => writeln!(f, "{}: folds", name)?,
+                    Command::Check => writeln!(f, "{}: checks", name)?,
+                    Command::Call => {
+                        writeln!(f, "{}: calls {}{}", name, action.amount, all_in(&stacks, name))?;
+                    },
+                    Command::Raise(x) if current_bet == 0 => {
+                        writeln!(f, "{}: bets {}{}", name, x, all_in(&stacks, name))?;
+                    },
+                    Command::Raise(x) => {
+                        writeln!(f, "{}: raises {} to {}{}", name, x, *bet, all_in(&stacks, name))?;
+                    },
+                    Command::PostBlind => {},
+                }
+                current_bet = current_bet.max(*bet);
+                if action.command == Command::Leave {
+                    writeln!(f, "{} leaves the table", name)?;
+                }
+            }
+        }
+
+        for &(ref name, amount) in &self.uncalled {
+            writeln!(f, "Uncalled bet ({}) returned to {}", amount, name)?;
+        }
+        if !self.shown.is_empty() {
+            writeln!(f, "*** SHOW DOWN ***")?;
+            for name in &self.shown {
+                if let Some((cards, hand)) = self.shown_hand(name) {
+                    writeln!(f, "{}: shows [{}] ({})", name, cards, hand.category)?;
+                }
+            }
+        }
+        let side_pots = self.pots().len() > 1;
+        for &(ref name, amount, pot) in &self.winnings {
+            let from = match pot {
+                0 if side_pots => "main pot".to_string(),
+                0 => "pot".to_string(),
+                n => format!("side pot-{}", n),
+            };
+            writeln!(f, "{} collected {} from {}", name, amount, from)?;
+        }
+
+        writeln!(f, "*** SUMMARY ***")?;
+        let pots = self.pots();
+        write!(f, "Total pot {}", self.total_pot())?;
+        if side_pots {
+            write!(f, " Main pot {}.", pots[0])?;
+            for (n, pot) in pots.iter().enumerate().skip(1) {
+                write!(f, " Side pot-{} {}.", n, pot)?;
+            }
+        }
+        writeln!(f, " | Rake {}", self.rake)?;
+        if !self.board.is_empty() {
+            writeln!(f, "Board [{}]", short_cards(&self.board))?;
+        }
+        for seat in &self.seats {
+            let position = if seat.number == self.button {
+                " (button)"
+            } else {
+                // the first big blind is the one posted in position
+                let big = self.blinds.iter().find(|b| b.1 == Blind::Big);
+                if self.blinds.iter().any(|b| b.0 == seat.name && b.1 == Blind::Small) {
+                    " (small blind)"
+                } else if big.is_some_and(|b| b.0 == seat.name) {
+                    " (big blind)"
+                } else {
+                    ""
+                }
+            };
+            write!(f, "Seat {}: {}{} ", seat.number, seat.name, position)?;
+            let won = self.won_by(&seat.name);
+            if let Some((cards, hand)) = self.shown_hand(&seat.name) {
+                if won > 0 {
+                    writeln!(f, "showed [{}] and won ({}) with {}", cards, won, hand.category)?;
+                } else {
+                    writeln!(f, "showed [{}] and lost with {}", cards, hand.category)?;
+                }
+            } else if won > 0 {
+                writeln!(f, "collected ({})", won)?;
+            } else {
+                match self.folded_on(&seat.name) {
+                    Some(Street::PreFlop) => writeln!(f, "folded before Flop")?,
+                    Some(street) => writeln!(f, "folded on the {}", street_name(street))?,
+                    None => writeln!(f, "mucked")?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl HandHistory {
+    fn shown_hand(&self, name: &str) -> Option<(String, Hand)> {
+        if !self.shown.iter().any(|n| n == name) {
+            return None;
+        }
+        let (c1, c2) = self.seat(name)?.cards.clone()?;
+        let cards = format!("{} {}", c1.short(), c2.short());
+        let mut all = vec![c1, c2];
+        all.extend_from_slice(&self.board);
+        Hand::make_hand(all).ok().map(|hand| (cards, hand))
+    }
+}
+
+fn all_in(stacks: &HashMap<&str, i32>, name: &str) -> &'static str {
+    if stacks.get(name).is_some_and(|&chips| chips <= 0) {
+        " and is all-in"
+    } else {
+        ""
+    }
+}
+
+pub fn street_name(street: Street) -> &'static str {
+    match street {
+        Street::PreFlop => "Preflop",
+        Street::Flop => "Flop",
+        Street::Turn => "Turn",
+        Street::River => "River",
+    }
+}
+
+fn short_cards(cards: &[Rc<Card>]) -> String {
+    cards.iter().map(|c| c.short()).collect::<Vec<_>>().join(" ")
+}
+
+// "2026/10/19 20:15:03 UTC"
+fn format_time(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0) as i64;
+    let (days, rem) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+
+    // days since the epoch to a civil date
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{}/{:02}/{:02} {:02}:{:02}:{:02} UTC",
+            year, month, day, rem / 3600, rem % 3600 / 60, rem % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::event::View;
+    use game::testing::cash_table;
+    use std::cell::RefCell;
+
+    // a buffer the test can still read once the table has the writer
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // everything `writer` writes while d sits out for `missed` hands from
+    // hand `from` and comes back for one more
+    fn returning<F>(from: usize, missed: usize, writer: F) -> String
+        where F: FnOnce(HandHistoryWriter<Shared>) -> HandHistoryWriter<Shared>
+    {
+        let mut table = cash_table(&["a", "b", "c", "d"]);
+        table.set_seed(1);
+        let out = Shared::default();
+        let writer = writer(HandHistoryWriter::new(out.clone(), &table));
+        table.add_observer(View::Omniscient, Rc::new(RefCell::new(writer)));
+        for hand in 0..from + missed + 1 {
+            if hand == from {
+                table.sit_out("d").unwrap();
+            } else if hand == from + missed {
+                table.sit_in("d").unwrap();
+            }
+            table.start_hand().unwrap();
+            while let Some(seat) = table.to_act() {
+                let cmd = table.legal_actions(seat).unwrap().check_or_call();
+                table.act(seat, cmd).unwrap();
+            }
+        }
+        let text = String::from_utf8(out.0.borrow().clone()).unwrap();
+        text
+    }
+
+    // the text written for the hand d comes back in
+    fn returning_hand(from: usize, missed: usize) -> String {
+        let text = returning(from, missed, |writer| writer);
+        let start = text.rfind("PokerStars Hand").unwrap();
+        text[start..].to_string()
+    }
+
+    #[test]
+    fn missing_both_blinds_posts_small_and_big_blinds() {
+        // d misses the big blind in the second hand and the small blind in
+        // the third, then comes back on the button
+        let text = returning_hand(1, 2);
+        assert!(text.contains("Seat #4 is the button"));
+        assert!(text.contains("a: posts small blind 5\nb: posts big blind 10\n\
+                               d: posts small & big blinds 15\n*** HOLE CARDS ***"));
+        // only the live 10 is a bet, so d's call of it isn't written
+        assert!(!text.contains("d: calls"));
+        assert!(text.contains("Seat 4: d (button)"));
+    }
+
+    #[test]
+    fn missing_the_small_blind_posts_it_dead() {
+        let text = returning_hand(2, 1);
+        assert!(text.contains("b: posts big blind 10\nd: posts small blind 5\n*** HOLE CARDS ***"));
+        assert!(text.contains("d: calls 10\n"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_log_round_trips() {
+        use history::parser;
+        use history::replay::Replay;
+
+        let hands = parser::parse_log(&returning(1, 2, |writer| writer)).unwrap();
+        let json = parser::parse_log(&returning(1, 2, |writer| writer.json())).unwrap();
+        assert_eq!(json.len(), 4);
+        // PokerStars text doesn't keep the time or the betting structure
+        for (json, hand) in json.iter().zip(&hands) {
+            assert_eq!((&json.blinds, &json.actions, &json.winnings),
+                       (&hand.blinds, &hand.actions, &hand.winnings));
+        }
+        assert_eq!(Replay::new(json[3].clone()).run(), &[]);
+    }
+}
@@ -0,0 +1,85 @@
+use game::table::Table;
+use game::event::{Event, TableObserver};
+
+// the order a freezeout's players finish in, from its events. A player who
+// runs out of chips finishes when the next hand is dealt without them, above
+// anyone who started their last hand with less, and a player who leaves
+// finishes when they go
+#[derive(Debug, Clone, Default)]
+pub struct Finishes {
+    // who was dealt into the last hand, with their chips at its start
+    dealt: Vec<(String, i32)>,
+    // the places decided so far, worst first
+    out: Vec<Vec<String>>,
+}
+
+impl Finishes {
+    // follows the game on from `table` as it stands, so a resumed game can
+    // be rated too. Anyone already out of chips shares last place
+    pub fn new(table: &Table) -> Finishes {
+        let mut finishes = Finishes::default();
+        let mut busted = Vec::new();
+        for player in table.players() {
+            let chips = player.chips + player.chips_in_pot;
+            if chips > 0 {
+                finishes.dealt.push((player.name.clone(), chips));
+            } else {
+                busted.push(player.name.clone());
+            }
+        }
+        if !busted.is_empty() {
+            finishes.out.push(busted);
+        }
+        finishes
+    }
+
+    // the places, best first: whoever still has chips at `table` by how
+    // many, then everyone else in the reverse of the order they went out
+    pub fn places(&self, table: &Table) -> Vec<Vec<String>> {
+        let mut out = self.out.clone();
+        let chips = |name: &str| table.find_player(name).map_or(0, |p| p.chips);
+        let (standing, busted): (Vec<_>, Vec<_>) = self.dealt.iter().cloned()
+            .partition(|player| chips(&player.0) > 0);
+        add_places(&mut out, busted);
+        let standing = standing.into_iter().map(|(name, _)| {
+            let chips = chips(&name);
+            (name, chips)
+        });
+        add_places(&mut out, standing.collect());
+        out.reverse();
+        out
+    }
+}
+
+// adds places for `players`, by their chips, with equal stacks sharing one
+fn add_places(out: &mut Vec<Vec<String>>, mut players: Vec<(String, i32)>) {
+    players.sort_by_key(|player| player.1);
+    let mut last = None;
+    for (name, chips) in players {
+        match out.last_mut() {
+            Some(place) if last == Some(chips) => place.push(name),
+            _ => out.push(vec![name]),
+        }
+        last = Some(chips);
+    }
+}
+
+impl TableObserver for Finishes {
+    fn notify(&mut self, event: &Event) {
+        match *event {
+            Event::HandStarted { ref seats, .. } => {
+                let gone: Vec<(String, i32)> = self.dealt.iter()
+                    .filter(|dealt| !seats.iter().any(|seat| seat.1 == dealt.0))
+                    .cloned()
+                    .collect();
+                add_places(&mut self.out, gone);
+                self.dealt = seats.iter().map(|seat| (seat.1.clone(), seat.2)).collect();
+            },
+            Event::PlayerLeft { ref name, .. } => {
+                self.dealt.retain(|dealt| dealt.0 != *name);
+                self.out.push(vec![name.clone()]);
+            },
+            _ => {},
+        }
+    }
+}
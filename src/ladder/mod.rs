@@ -0,0 +1,177 @@
+use game::error::GameError;
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+
+pub mod finish;
+
+// first line of every ratings file
+pub const RATINGS_HEADER: &str = "rust-poker ratings 1";
+// where new players start
+const START: f64 = 1500.0;
+// the most a heads up game can move a rating
+const K: f64 = 32.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rating {
+    pub rating: f64,
+    pub games: u32,
+    pub wins: u32,
+}
+
+impl Default for Rating {
+    fn default() -> Rating {
+        Rating { rating: START, games: 0, wins: 0 }
+    }
+}
+
+// the score a player rated `rating` is expected to take off one rated `other`
+fn expected(rating: f64, other: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((other - rating) / 400.0))
+}
+
+// Elo ratings of everyone who has finished a game. A game of more than two
+// counts as a match between every pair of players in it, the better placed
+// of each winning, each pair with a share of the usual weight
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ladder {
+    ratings: BTreeMap<String, Rating>,
+}
+
+impl Ladder {
+    pub fn new() -> Ladder {
+        Ladder::default()
+    }
+
+    pub fn rating(&self, name: &str) -> Option<&Rating> {
+        self.ratings.get(name)
+    }
+
+    // updates the ratings from a game's places, best first. Players sharing
+    // a place draw with each other
+    pub fn record(&mut self, places: &[Vec<String>]) {
+        let players: Vec<(&str, usize)> = places.iter().enumerate()
+            .flat_map(|(place, names)| names.iter().map(move |name| (name.as_str(), place)))
+            .collect();
+        if players.len() < 2 {
+            return;
+        }
+        let rating = |name: &str| self.ratings.get(name).map_or(START, |r| r.rating);
+        let weight = K / (players.len() - 1) as f64;
+        let changes: Vec<f64> = players.iter()
+            .map(|&(name, place)| {
+                players.iter()
+                    .filter(|&&(other, _)| other != name)
+                    .map(|&(other, other_place)| {
+                        let score = match place.cmp(&other_place) {
+                            Ordering::Less => 1.0,
+                            Ordering::Equal => 0.5,
+                            Ordering::Greater => 0.0,
+                        };
+                        weight * (score - expected(rating(name), rating(other)))
+                    })
+                    .sum()
+            })
+            .collect();
+        for (&(name, place), change) in players.iter().zip(changes) {
+            let entry = self.ratings.entry(name.to_string()).or_default();
+            entry.rating += change;
+            entry.games += 1;
+            entry.wins += (place == 0) as u32;
+        }
+    }
+
+    // everyone, best rated first
+    pub fn leaderboard(&self) -> Vec<(&str, &Rating)> {
+        let mut board: Vec<(&str, &Rating)> = self.ratings.iter()
+            .map(|(name, rating)| (name.as_str(), rating))
+            .collect();
+        board.sort_by(|a, b| b.1.rating.total_cmp(&a.1.rating));
+        board
+    }
+
+    // one line per player of their rating, games and wins, the name last
+    // since it may contain spaces
+    pub fn save<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "{}", RATINGS_HEADER)?;
+        for (name, rating) in &self.ratings {
+            writeln!(out, "{} {} {} {}", rating.rating, rating.games, rating.wins, name)?;
+        }
+        out.flush()
+    }
+
+    pub fn load<R: BufRead>(input: R) -> Result<Ladder, GameError> {
+        let invalid = |reason: &str| GameError::InvalidRatings(reason.to_string());
+        let mut lines = input.lines();
+        match lines.next() {
+            Some(Ok(ref header)) if header == RATINGS_HEADER => {},
+            _ => return Err(invalid("not a ratings file")),
+        }
+        let mut ladder = Ladder::new();
+        for line in lines {
+            let line = line.map_err(|err| invalid(&err.to_string()))?;
+            let fields: Vec<&str> = line.splitn(4, ' ').collect();
+            let parsed = match fields[..] {
+                [rating, games, wins, name] => {
+                    match (rating.parse(), games.parse(), wins.parse()) {
+                        (Ok(rating), Ok(games), Ok(wins)) => Some((name, rating, games, wins)),
+                        _ => None,
+                    }
+                },
+                _ => None,
+            };
+            let (name, rating, games, wins) = match parsed {
+                Some(parsed) => parsed,
+                None => return Err(invalid(&format!("bad line \"{}\"", line))),
+            };
+            ladder.ratings.insert(name.to_string(), Rating { rating, games, wins });
+        }
+        Ok(ladder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn places(places: &[&[&str]]) -> Vec<Vec<String>> {
+        places.iter().map(|names| names.iter().map(|name| name.to_string()).collect()).collect()
+    }
+
+    #[test]
+    fn a_win_moves_ratings_by_the_elo_formula() {
+        let mut ladder = Ladder::new();
+        // evenly rated players swap half of K
+        ladder.record(&places(&[&["a"], &["b"]]));
+        assert_eq!(ladder.rating("a"), Some(&Rating { rating: 1516.0, games: 1, wins: 1 }));
+        assert_eq!(ladder.rating("b"), Some(&Rating { rating: 1484.0, games: 1, wins: 0 }));
+        // a, 32 points up, was expected to score 0.546
+        ladder.record(&places(&[&["a"], &["b"]]));
+        let a = ladder.rating("a").unwrap().rating;
+        assert!((a - 1530.53).abs() < 0.01, "a {}", a);
+        assert!((ladder.rating("b").unwrap().rating - (3000.0 - a)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bigger_games_are_matches_between_every_pair() {
+        let mut ladder = Ladder::new();
+        ladder.record(&places(&[&["a"], &["b", "c"], &["d"]]));
+        let rating = |name| ladder.rating(name).unwrap().rating;
+        // each pair gets a third of K, so beating everyone is worth half of K
+        assert!((rating("a") - 1516.0).abs() < 1e-9);
+        assert!((rating("b") - 1500.0).abs() < 1e-9);
+        assert!((rating("c") - 1500.0).abs() < 1e-9);
+        assert!((rating("d") - 1484.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn saved_ratings_load_back() {
+        let mut ladder = Ladder::new();
+        ladder.record(&places(&[&["the champ"], &["b"]]));
+        let mut saved = Vec::new();
+        ladder.save(&mut saved).unwrap();
+        assert_eq!(Ladder::load(&saved[..]).unwrap(), ladder);
+        assert_eq!(ladder.leaderboard()[0].0, "the champ");
+    }
+}
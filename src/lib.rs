@@ -0,0 +1,14 @@
+extern crate rand;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+
+pub mod game;
+pub mod history;
+pub mod bot;
+pub mod solver;
+pub mod acpc;
+pub mod arena;
+pub mod ladder;
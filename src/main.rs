@@ -1,28 +1,639 @@
-extern crate rand;
+extern crate poker;
 
-pub mod game;
-pub mod ui;
+mod ui;
 
-#[allow(non_camel_case_types)]
-use game::table::Table;
+use poker::game::table::Table;
+use poker::game::cash::CashGame;
+use poker::game::rake::Rake;
+use poker::game::error::GameError;
+use poker::game::dealer::Dealer;
+use poker::game::clock::TimeLimits;
+use poker::game::strategy::Strategy;
+use poker::bot::profile;
+use poker::bot::chart::PreflopChart;
+use poker::game::event::View;
+use poker::game::betting::BettingStructure;
+use poker::history::writer::HandHistoryWriter;
+use poker::history::parser;
+use poker::history::stats::StatsTracker;
+use poker::ladder::Ladder;
+use poker::ladder::finish::Finishes;
+use poker::acpc::GameDef;
+use poker::acpc::server::{Client, Match};
+use poker::solver::{self, Game, Solver, SolvedStrategy};
+use poker::solver::best_response;
+use poker::solver::kuhn::Kuhn;
+use poker::solver::leduc::Leduc;
+use poker::solver::limit::LimitAbstraction;
+use poker::solver::push_fold::{PushFold, Setup, Charts};
+use ui::{TerminalObserver, TerminalStrategy, Lineup, BotChoice};
+use std::cell::RefCell;
 use std::rc::Rc;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::net::TcpListener;
+use std::time::Duration;
+
+const SAVE_FILE: &str = "rust-poker.save";
+const STATS_FILE: &str = "rust-poker.stats";
+const RATINGS_FILE: &str = "rust-poker.ratings";
+const LIMIT_BUCKETS: usize = 3;
+const LIMIT_DEALS: usize = 2000;
+const PUSH_FOLD_DEALS: usize = 300000;
+// halved for every player past two, as each deal takes twice as long
+const CHART_DEALS: usize = 400000;
+const CHART_ITERATIONS: usize = 100;
+const ACPC_LOG: &str = "acpc.log";
+// seconds a client gets for each action
+const ACPC_TIMEOUT: u64 = 10;
+// seconds everyone has to spare when there's a time limit
+const TIME_BANK: u64 = 60;
 
 fn main() {
-    let mut table = Table::build_table();
-    ui::game_setup(&mut table);
+    match env::args().nth(1).as_deref() {
+        Some("replay") => return replay(),
+        Some("solve") => return solve(),
+        Some("exploit") => return exploit(),
+        Some("pushfold") => return push_fold(),
+        Some("stats") => return show_stats(),
+        Some("ladder") => return show_ladder(),
+        Some("echo-bot") => return echo_bot(),
+        Some("acpc") => return acpc(),
+        _ => {},
+    }
+    // the game is saved before every action, so it can be resumed from
+    // wherever it was stopped
+    let save_path = arg_value("--save").unwrap_or_else(|| SAVE_FILE.to_string());
+    let bots = match bot_choices() {
+        Ok(bots) => bots,
+        Err(err) => return println!("{}", err),
+    };
+    let chart = match preflop_chart() {
+        Ok(chart) => chart,
+        Err(err) => return println!("{}", err),
+    };
+    // everyone's stats carry over from earlier games
+    let stats_path = arg_value("--stats").unwrap_or_else(|| STATS_FILE.to_string());
+    let stats = match load_stats(&stats_path) {
+        Ok(stats) => Rc::new(RefCell::new(stats)),
+        Err(err) => return println!("{}", err),
+    };
+    let lineup = Lineup::new(bots, chart, stats.clone()).external(arg_values("--external"));
+    let (mut dealer, human) = if env::args().any(|arg| arg == "--resume") {
+        match resume(&save_path, &lineup) {
+            Ok(game) => game,
+            Err(err) => return println!("Can't resume from {}: {}", save_path, err),
+        }
+    } else {
+        let table = match new_table() {
+            Ok(table) => table,
+            Err(err) => return println!("{}", err),
+        };
+        let mut dealer = Dealer::new(table);
+        match ui::game_setup(&mut dealer, &lineup) {
+            Some(human) => (dealer, human),
+            None => return println!("Thank you for playing :)"),
+        }
+    };
+    // --time <seconds> for each action, with --time-bank <seconds> to spare
+    if let Some(seconds) = arg_value("--time").and_then(|s| s.parse().ok()) {
+        let bank = arg_value("--time-bank").and_then(|s| s.parse().ok()).unwrap_or(TIME_BANK);
+        dealer.set_time_limits(TimeLimits {
+            per_action: Duration::from_secs(seconds),
+            bank: Duration::from_secs(bank),
+        });
+    }
+    let terminal = TerminalObserver::new(Some(&human));
+    dealer.table_mut().add_observer(View::Player(human.clone()), Rc::new(RefCell::new(terminal)));
+    dealer.table_mut().add_observer(View::Spectator, stats.clone());
+    // the order everyone finishes a freezeout in goes on the ladder
+    let finishes = Rc::new(RefCell::new(Finishes::new(dealer.table())));
+    dealer.table_mut().add_observer(View::Spectator, finishes.clone());
+    let history = arg_value("--history").and_then(|path| {
+        match HandHistoryWriter::create(&path, dealer.table()) {
+            Ok(writer) => {
+                let writer = Rc::new(RefCell::new(json_log(writer.hero(&human), &path)));
+                dealer.table_mut().add_observer(View::Omniscient, writer.clone());
+                Some((path, writer))
+            },
+            Err(err) => {
+                println!("Can't write hand histories to {}: {}", path, err);
+                None
+            },
+        }
+    });
+    if dealer.table().is_hand_in_progress() {
+        ui::show_hand(dealer.table(), &human);
+    }
+    let mut quit = false;
     loop {
-        table.deal_cards();
-        table.allow_betting();
-        while table.is_playing() {
-            table.reveal_cards();
-            table.allow_betting();
+        if !dealer.table().is_hand_in_progress() {
+            save(dealer.table(), &save_path);
+            save_stats(&stats.borrow(), &stats_path);
+            if dealer.table().cash_game().is_some() {
+                if !ui::between_hands(dealer.table_mut(), &human) {
+                    quit = true;
+                    break;
+                }
+                dealer.offer_rebuys();
+            }
+            if dealer.table().has_left(&human) {
+                break;
+            }
+            if dealer.table_mut().start_hand().is_err() {
+                continue;
+            }
+        }
+        if !play_hand(&mut dealer, &save_path) {
+            quit = true;
+            break;
+        }
+        let table = dealer.table();
+        if table.has_left(&human) {
+            break;
+        }
+        if table.cash_game().is_none() && (table.is_game_over() || is_busted(table, &human)) {
+            break; // game over
+        }
+    }
+    if quit {
+        println!("Your game is saved. Start with --resume to pick up where you left off.");
+    } else {
+        // nothing left to resume
+        let _ = fs::remove_file(&save_path);
+    }
+    save_stats(&stats.borrow(), &stats_path);
+    if let Some((path, writer)) = history {
+        if let Some(err) = writer.borrow().error() {
+            println!("Hand histories stopped being written to {}: {}", path, err);
+        }
+    }
+    // a freezeout left to resume isn't over yet
+    if !quit && dealer.table().cash_game().is_none() {
+        rate(&finishes.borrow().places(dealer.table()));
+    }
+    if dealer.table().total_rake() > 0 {
+        println!("Total rake collected: {}", dealer.table().total_rake());
+    }
+    if quit {
+        println!("Thank you for playing :)");
+    } else {
+        println!("The game is over! Thank you for playing :)");
+    }
+}
+
+// --cash plays for cash, with buy-ins of --cash <min>:<max> chips or the
+// usual ones, and --rake takes the usual rake or --rake <percent>
+fn new_table() -> Result<Table, GameError> {
+    let mut table = if env::args().any(|arg| arg == "--cash") {
+        let cash = match option_value("--cash") {
+            Some(buy_ins) => {
+                let mut split = buy_ins.splitn(2, ':').map(|amount| amount.parse().ok());
+                match (split.next().flatten(), split.next().flatten()) {
+                    (Some(min), Some(max)) => CashGame::new(min, max)?,
+                    _ => return Err(GameError::InvalidSetup(format!("buy-ins of {}", buy_ins))),
+                }
+            },
+            None => CashGame::default(),
+        };
+        Table::build_cash_table(cash)
+    } else {
+        Table::build_table()
+    };
+    if env::args().any(|arg| arg == "--rake") {
+        let rake = match option_value("--rake") {
+            Some(percent) => match percent.parse() {
+                Ok(percent) => Rake::new(percent)?.cap(2, 5).cap(4, 10),
+                Err(_) => return Err(GameError::InvalidSetup(format!("a rake of {}", percent))),
+            },
+            None => Rake::default(),
+        };
+        table.set_rake(rake);
+    }
+    if env::args().any(|arg| arg == "--limit") {
+        table.set_betting_structure(BettingStructure::FixedLimit);
+    } else if env::args().any(|arg| arg == "--pot-limit") {
+        table.set_betting_structure(BettingStructure::PotLimit);
+    }
+    Ok(table)
+}
+
+// --history <file>.json writes our own JSON log rather than PokerStars text
+#[cfg(feature = "serde")]
+fn json_log<W: Write>(writer: HandHistoryWriter<W>, path: &str) -> HandHistoryWriter<W> {
+    if path.ends_with(".json") {
+        writer.json()
+    } else {
+        writer
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn json_log<W: Write>(writer: HandHistoryWriter<W>, _path: &str) -> HandHistoryWriter<W> {
+    writer
+}
+
+// the styles picked for the computer players with `--bots tp,lag,montecarlo`,
+// `solved:<file>` for a strategy saved by `solve limit`, or with
+// `--bot-config <file>`
+fn bot_choices() -> Result<Vec<BotChoice>, GameError> {
+    if let Some(names) = arg_value("--bots") {
+        return names.split(',').map(|name| name.parse()).collect();
+    }
+    match arg_value("--bot-config") {
+        Some(path) => {
+            let file = File::open(&path)
+                .map_err(|err| GameError::InvalidProfile(format!("{}: {}", path, err)))?;
+            let profiles = profile::read_profiles(BufReader::new(file))?;
+            Ok(profiles.into_iter().map(BotChoice::Profile).collect())
+        },
+        None => Ok(Vec::new()),
+    }
+}
+
+// the preflop chart the computer players use, from `--chart <file>`
+fn preflop_chart() -> Result<Option<PreflopChart>, GameError> {
+    match arg_value("--chart") {
+        Some(path) => {
+            let file = File::open(&path)
+                .map_err(|err| GameError::InvalidChart(format!("{}: {}", path, err)))?;
+            PreflopChart::load(BufReader::new(file)).map(Some)
+        },
+        None => Ok(None),
+    }
+}
+
+// the saved game and the name of its human player
+fn resume(path: &str, lineup: &Lineup) -> Result<(Dealer, String), String> {
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    let table = Table::load(BufReader::new(file)).map_err(|err| err.to_string())?;
+    let human = match table.players().iter().find(|p| p.is_human) {
+        Some(player) => player.name.clone(),
+        None => return Err("there's no human player at the table".to_string()),
+    };
+    let names: Vec<String> = table.players().iter().map(|p| p.name.clone()).collect();
+    let seed = table.seed().wrapping_add(table.hand_number());
+    let mut dealer = Dealer::new(table);
+    let mut bots = 0;
+    for (i, name) in names.into_iter().enumerate() {
+        let seed = seed.wrapping_add(i as u64);
+        let strategy: Box<dyn Strategy> = if name == human {
+            Box::new(TerminalStrategy::new(lineup.stats.clone()))
+        } else {
+            bots += 1;
+            lineup.bot(bots - 1, seed)
+        };
+        dealer.set_strategy(&name, strategy);
+    }
+    Ok((dealer, human))
+}
+
+fn save(table: &Table, path: &str) {
+    if let Err(err) = File::create(path).and_then(|file| table.save(BufWriter::new(file))) {
+        println!("Can't save the game to {}: {}", path, err);
+    }
+}
+
+// a missing file is a fresh start
+fn load_stats(path: &str) -> Result<StatsTracker, GameError> {
+    match File::open(path) {
+        Ok(file) => StatsTracker::load(BufReader::new(file)),
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(StatsTracker::new()),
+        Err(err) => Err(GameError::InvalidStats(format!("{}: {}", path, err))),
+    }
+}
+
+fn save_stats(stats: &StatsTracker, path: &str) {
+    if let Err(err) = File::create(path).and_then(|file| stats.save(BufWriter::new(file))) {
+        println!("Can't save the stats to {}: {}", path, err);
+    }
+}
+
+// a missing file is an empty ladder
+fn load_ladder(path: &str) -> Result<Ladder, GameError> {
+    match File::open(path) {
+        Ok(file) => Ladder::load(BufReader::new(file)),
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(Ladder::new()),
+        Err(err) => Err(GameError::InvalidRatings(format!("{}: {}", path, err))),
+    }
+}
+
+// updates the ratings in --ratings <file> from a game's places, best first
+fn rate(places: &[Vec<String>]) {
+    let path = arg_value("--ratings").unwrap_or_else(|| RATINGS_FILE.to_string());
+    let mut ladder = match load_ladder(&path) {
+        Ok(ladder) => ladder,
+        Err(err) => return println!("{}", err),
+    };
+    ladder.record(places);
+    if let Err(err) = File::create(&path).and_then(|file| ladder.save(BufWriter::new(file))) {
+        println!("Can't save the ratings to {}: {}", path, err);
+    }
+    println!();
+    ui::print_leaderboard(&ladder);
+}
+
+// false if the human quits, leaving the hand saved to resume
+fn play_hand(dealer: &mut Dealer, save_path: &str) -> bool {
+    while dealer.table().to_act().is_some() {
+        save(dealer.table(), save_path);
+        match dealer.play_turn() {
+            Ok(()) if dealer.has_quit() => return false,
+            Ok(()) => {},
+            Err(err @ GameError::IllegalAction(_)) => println!("{}!", err),
+            Err(err) => {
+                println!("{}!", err);
+                break;
+            },
+        }
+    }
+    true
+}
+
+// replay <file> [hand number] steps through recorded hands street by street,
+// from a PokerStars hand history or our own JSON log
+fn replay() {
+    let path = match env::args().nth(2) {
+        Some(path) => path,
+        None => {
+            return println!("Usage: replay <hand history or JSON log file> [hand number]")
+        },
+    };
+    let hand_number: Option<u64> = env::args().nth(3).and_then(|n| n.parse().ok());
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) => return println!("Can't read {}: {}", path, err),
+    };
+    let hands = match parser::parse_log(&text) {
+        Ok(hands) => hands,
+        Err(err) => return println!("Can't parse {}: {}", path, err),
+    };
+    let hands: Vec<_> = hands.into_iter()
+        .filter(|hand| hand_number.is_none_or(|n| hand.hand_number == n))
+        .collect();
+    if hands.is_empty() {
+        println!("No hands to replay");
+    }
+    for hand in hands {
+        ui::replay_hand(hand);
+    }
+}
+
+// solve <kuhn|leduc|pushfold|limit> [iterations] runs CFR, or CFR+ with
+// --plus, and writes the strategy to --out <file>. Push/fold is played
+// --stack <big blinds> deep. Computer players can only play limit
+// strategies, picked with --bots solved:<file>; the others are for study
+fn solve() {
+    let iterations = env::args().nth(3).and_then(|n| n.parse().ok()).unwrap_or(1000);
+    let plus = env::args().any(|arg| arg == "--plus");
+    match env::args().nth(2).as_deref() {
+        Some("kuhn") => solve_game(Solver::new(Kuhn), iterations, plus, true),
+        Some("leduc") => solve_game(Solver::new(Leduc), iterations, plus, true),
+        Some("pushfold") => {
+            let stack = arg_value("--stack").and_then(|s| s.parse().ok()).unwrap_or(10.0);
+            let game = PushFold::new(stack, PUSH_FOLD_DEALS, 0);
+            solve_game(Solver::new(game), iterations, plus, true)
+        },
+        Some("limit") => {
+            // too many deals to walk them all every iteration, or to measure
+            let game = LimitAbstraction::new(LIMIT_BUCKETS, LIMIT_DEALS, 0);
+            solve_game(Solver::new(game).sampled(0), iterations, plus, false)
+        },
+        _ => println!("Usage: solve <kuhn|leduc|pushfold|limit> [iterations] [--plus] \
+                       [--stack <big blinds>] [--out <file>]"),
+    }
+}
+
+fn solve_game<G: Game>(solver: Solver<G>, iterations: u64, plus: bool, measure: bool) {
+    let mut solver = if plus { solver.plus() } else { solver };
+    solver.run(iterations);
+    let strategy = solver.strategy();
+    println!("{} infosets after {} iterations, worth {:.4} to the first player",
+             strategy.len(), solver.iterations(), solver::expected_value(solver.game(), &strategy));
+    if measure {
+        let exploitability = best_response::exploitability(solver.game(), &strategy);
+        println!("Exploitable for {:.2} mbb a hand", exploitability);
+    }
+    if let Some(path) = arg_value("--out") {
+        if let Err(err) = File::create(&path).and_then(|file| strategy.save(BufWriter::new(file))) {
+            println!("Can't write the strategy to {}: {}", path, err);
         }
-        table.show_cards();
-        table.evaluate_round(); // round over
-        if table.is_game_over() { // game over
-            println!("The game is over! Thank you for playing :)");
+    }
+}
+
+// exploit <strategy file> reports how much a best response wins against a
+// solved Kuhn, Leduc or push/fold strategy
+fn exploit() {
+    let path = match env::args().nth(2) {
+        Some(path) => path,
+        None => return println!("Usage: exploit <strategy file>"),
+    };
+    let strategy = match load_strategy(&path) {
+        Ok(strategy) => strategy,
+        Err(err) => return println!("Can't read {}: {}", path, err),
+    };
+    let words: Vec<&str> = strategy.game.split_whitespace().collect();
+    match words[..] {
+        ["kuhn"] => report_exploitability(&Kuhn, &strategy),
+        ["leduc"] => report_exploitability(&Leduc, &strategy),
+        ["pushfold", stack] => match stack.parse() {
+            Ok(stack) => {
+                report_exploitability(&PushFold::new(stack, PUSH_FOLD_DEALS, 0), &strategy)
+            },
+            Err(_) => println!("{} isn't a stack size", stack),
+        },
+        _ => println!("Can't measure a {} strategy", strategy.game),
+    }
+}
+
+fn load_strategy(path: &str) -> Result<SolvedStrategy, String> {
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    SolvedStrategy::load(BufReader::new(file)).map_err(|err| err.to_string())
+}
+
+fn report_exploitability<G: Game>(game: &G, strategy: &SolvedStrategy) {
+    for player in 0..2 {
+        let response = best_response::best_response(game, strategy, player);
+        println!("A best response in seat {} wins {:.4} a hand", player + 1, response.value);
+    }
+    println!("Exploitable for {:.2} mbb a hand", best_response::exploitability(game, strategy));
+}
+
+// pushfold [players] prints the push/fold equilibrium for equal stacks of
+// --stack <big blinds>, or --stacks <a,b,...> in the order the players act,
+// with optional --ante <big blinds> and --icm <payouts>
+fn push_fold() {
+    let players = env::args().nth(2).and_then(|n| n.parse().ok()).unwrap_or(2);
+    let stack = arg_value("--stack").and_then(|s| s.parse().ok()).unwrap_or(10.0);
+    let mut setup = Setup::new(players, stack);
+    let numbers = |name: &str| {
+        arg_value(name).map(|list| {
+            list.split(',').map(|n| n.trim().parse().ok()).collect::<Option<Vec<f64>>>()
+        })
+    };
+    match numbers("--stacks") {
+        Some(Some(stacks)) => setup = setup.stacks(stacks),
+        Some(None) => return println!("--stacks takes a list like 10,15,8"),
+        None => {},
+    }
+    match numbers("--icm") {
+        Some(Some(payouts)) => setup = setup.icm(payouts),
+        Some(None) => return println!("--icm takes a list of payouts like 50,30,20"),
+        None => {},
+    }
+    if let Some(ante) = arg_value("--ante").and_then(|a| a.parse().ok()) {
+        setup = setup.ante(ante);
+    }
+    let deals = arg_value("--deals").and_then(|n| n.parse().ok())
+        .unwrap_or(CHART_DEALS >> (setup.players().clamp(2, 20) - 2));
+    let iterations = arg_value("--iterations").and_then(|n| n.parse().ok())
+        .unwrap_or(CHART_ITERATIONS);
+    let charts = match Charts::solve(&setup, deals, iterations, 0) {
+        Ok(charts) => charts,
+        Err(err) => return println!("{}", err),
+    };
+    for history in charts.histories() {
+        if let Some(chart) = charts.chart(&history) {
+            ui::print_chart(&charts.title(&history), chart);
         }
     }
+    match setup.payouts {
+        Some(_) => println!("\nNobody can gain more than {:.3}% of the prize pool a hand",
+                            100.0 * charts.gain()),
+        None => println!("\nNobody can gain more than {:.2} mbb a hand", 1000.0 * charts.gain()),
+    }
+}
+
+// stats [file] shows everyone's stats from earlier games
+fn show_stats() {
+    let path = env::args().nth(2).unwrap_or_else(|| STATS_FILE.to_string());
+    let stats = match load_stats(&path) {
+        Ok(stats) => stats,
+        Err(err) => return println!("{}", err),
+    };
+    if stats.players().is_empty() {
+        return println!("No stats in {} yet", path);
+    }
+    let names: Vec<&str> = stats.players().keys().map(|name| name.as_str()).collect();
+    ui::print_stats(&stats, &names);
+}
+
+// ladder [file] shows everyone's ratings from earlier games
+fn show_ladder() {
+    let path = env::args().nth(2).unwrap_or_else(|| RATINGS_FILE.to_string());
+    let ladder = match load_ladder(&path) {
+        Ok(ladder) => ladder,
+        Err(err) => return println!("{}", err),
+    };
+    if ladder.leaderboard().is_empty() {
+        return println!("No ratings in {} yet", path);
+    }
+    ui::print_leaderboard(&ladder);
+}
+
+// acpc <limit|nolimit> <hands> <seed> <name> <name> [name] deals a match to
+// two or three research bots speaking the competition's protocol. Each gets
+// a port, from --port <first port> or any free ones, printed on one line as
+// the competition's dealer does. Hands are logged to --log <file>, and
+// --duplicate plays the match again for every seating
+fn acpc() {
+    let args: Vec<String> = env::args().collect();
+    let betting = match args.get(2).map(|s| s.as_str()) {
+        Some("limit") => BettingStructure::FixedLimit,
+        Some("nolimit") => BettingStructure::NoLimit,
+        _ => return println!("Usage: acpc <limit|nolimit> <hands> <seed> <name> <name> [name] \
+                              [--port <first port>] [--log <file>] [--duplicate] \
+                              [--timeout <seconds>]"),
+    };
+    let hands = args.get(3).and_then(|n| n.parse().ok()).unwrap_or(1000);
+    let seed = args.get(4).and_then(|n| n.parse().ok()).unwrap_or(0);
+    let names: Vec<String> = args.iter().skip(5).take_while(|arg| !arg.starts_with("--"))
+        .cloned()
+        .collect();
+    if names.len() != 2 && names.len() != 3 {
+        return println!("ACPC matches are for two or three players");
+    }
+    let first_port: u16 = arg_value("--port").and_then(|p| p.parse().ok()).unwrap_or(0);
+    let listeners: io::Result<Vec<TcpListener>> = (0..names.len())
+        .map(|i| {
+            let port = if first_port == 0 { 0 } else { first_port + i as u16 };
+            TcpListener::bind(("127.0.0.1", port))
+        })
+        .collect();
+    let listeners = match listeners {
+        Ok(listeners) => listeners,
+        Err(err) => return println!("Can't listen for the players: {}", err),
+    };
+    let ports: Vec<String> = listeners.iter()
+        .filter_map(|listener| listener.local_addr().ok())
+        .map(|addr| addr.port().to_string())
+        .collect();
+    println!("{}", ports.join(" "));
+    let seconds = arg_value("--timeout").and_then(|s| s.parse().ok()).unwrap_or(ACPC_TIMEOUT);
+    let clients: io::Result<Vec<Client>> = listeners.iter().zip(&names)
+        .map(|(listener, name)| Client::accept(listener, name, Duration::from_secs(seconds)))
+        .collect();
+    let mut clients = match clients {
+        Ok(clients) => clients,
+        Err(err) => return println!("A player couldn't connect: {}", err),
+    };
+    let mut game = Match::new(GameDef::new(names.len(), betting), hands, seed);
+    if env::args().any(|arg| arg == "--duplicate") {
+        game = game.duplicate();
+    }
+    let path = arg_value("--log").unwrap_or_else(|| ACPC_LOG.to_string());
+    let played = File::create(&path).and_then(|file| game.play(&mut clients, BufWriter::new(file)));
+    match played {
+        Ok(totals) => {
+            let totals: Vec<String> = totals.iter().map(|t| t.to_string()).collect();
+            println!("SCORE:{}:{}", totals.join("|"), names.join("|"));
+        },
+        Err(err) => println!("The match stopped: {}", err),
+    }
+}
+
+// echo-bot is the simplest external bot, for trying out the protocol with
+// --external "<this program> echo-bot": it checks or calls every time
+fn echo_bot() {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => return,
+        };
+        if line == "QUIT" {
+            return;
+        }
+        if !line.starts_with("STATE ") {
+            continue;
+        }
+        if writeln!(stdout, "c").and_then(|_| stdout.flush()).is_err() {
+            return;
+        }
+    }
+}
+
+// the value following `name` on the command line
+fn arg_value(name: &str) -> Option<String> {
+    let args: Vec<String> = env::args().collect();
+    args.iter().position(|arg| arg == name).and_then(|i| args.get(i + 1).cloned())
+}
+
+// the value following the flag `name`, unless it's left out
+fn option_value(name: &str) -> Option<String> {
+    arg_value(name).filter(|value| !value.starts_with("--"))
+}
+
+// the values following each `name` on the command line
+fn arg_values(name: &str) -> Vec<String> {
+    let args: Vec<String> = env::args().collect();
+    args.windows(2).filter(|pair| pair[0] == name).map(|pair| pair[1].clone()).collect()
+}
+
+fn is_busted(table: &Table, name: &str) -> bool {
+    table.find_player(name).is_none_or(|p| p.is_busted())
 }
 
 /*
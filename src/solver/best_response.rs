@@ -0,0 +1,195 @@
+use game::player::Command;
+use super::{Game, Node, SolvedStrategy};
+
+use std::collections::HashMap;
+
+// the most one player can win against a fixed strategy for the other, and
+// the strategy that wins it
+pub struct BestResponse {
+    pub player: usize,
+    pub value: f64, // to `player`, per hand
+    pub strategy: SolvedStrategy,
+}
+
+// a walk of the tree for the responding player. Infosets at the depth being
+// decided collect the value of each action, weighted by how likely the
+// opponent and the deal are to get there. Deeper ones play their choices
+struct Pass<'a> {
+    player: usize,
+    deciding: Option<usize>, // none once every choice is made
+    strategy: &'a SolvedStrategy,
+    choices: &'a HashMap<String, usize>,
+    sums: HashMap<String, (Vec<Command>, Vec<f64>)>,
+}
+
+// the choices are made from the player's last decisions back to their
+// first, so each one can count on the choices that follow it
+pub fn best_response<G: Game>(game: &G, strategy: &SolvedStrategy, player: usize) -> BestResponse {
+    let mut choices: HashMap<String, usize> = HashMap::new();
+    let mut actions: HashMap<String, Vec<Command>> = HashMap::new();
+    for depth in (0..max_depth(game, &game.root(), player, 0) + 1).rev() {
+        let mut pass = Pass {
+            player,
+            deciding: Some(depth),
+            strategy,
+            choices: &choices,
+            sums: HashMap::new(),
+        };
+        walk(game, &game.root(), &mut pass, 1.0, 0);
+        for (key, (commands, values)) in pass.sums {
+            let best = (0..values.len())
+                .fold(0, |best, a| if values[a] > values[best] { a } else { best });
+            choices.insert(key.clone(), best);
+            actions.insert(key, commands);
+        }
+    }
+    let mut pass = Pass {
+        player,
+        deciding: None,
+        strategy,
+        choices: &choices,
+        sums: HashMap::new(),
+    };
+    let value = walk(game, &game.root(), &mut pass, 1.0, 0);
+
+    let mut response = SolvedStrategy::new(&game.name());
+    for (key, commands) in actions {
+        let best = choices[&key];
+        let probabilities = commands.into_iter().enumerate()
+            .map(|(a, action)| (action, if a == best { 1.0 } else { 0.0 }))
+            .collect();
+        response.insert(&key, probabilities);
+    }
+    BestResponse { player, value, strategy: response }
+}
+
+// how much a best response wins against `strategy`, averaged over both
+// seats, in thousandths of the game's unit (the big blind, or the ante for
+// Kuhn and Leduc) per hand. An equilibrium scores zero
+pub fn exploitability<G: Game>(game: &G, strategy: &SolvedStrategy) -> f64 {
+    let first = best_response(game, strategy, 0).value;
+    let second = best_response(game, strategy, 1).value;
+    1000.0 * (first + second) / 2.0
+}
+
+// the depth of the player's deepest decision, counting their first as 0
+fn max_depth<G: Game>(game: &G, state: &G::State, player: usize, depth: usize) -> usize {
+    match game.node(state) {
+        Node::Terminal(_) => 0,
+        Node::Chance(outcomes) => outcomes.iter()
+            .map(|o| max_depth(game, &o.1, player, depth))
+            .max()
+            .unwrap_or(0),
+        Node::Decision { player: acting, actions, .. } => {
+            let deeper = if acting == player { depth + 1 } else { depth };
+            let below = actions.iter()
+                .map(|a| max_depth(game, &a.1, player, deeper))
+                .max()
+                .unwrap_or(0);
+            if acting == player { below.max(depth) } else { below }
+        },
+    }
+}
+
+// the value of `state` to the responding player, where `reach` is the
+// chance of the opponent and the deal getting here and `depth` the number
+// of decisions the player has made
+fn walk<G: Game>(game: &G, state: &G::State, pass: &mut Pass, reach: f64, depth: usize) -> f64 {
+    match game.node(state) {
+        Node::Terminal(payoff) => if pass.player == 0 { payoff } else { -payoff },
+        Node::Chance(outcomes) => outcomes.iter()
+            .map(|&(p, ref next)| p * walk(game, next, pass, reach * p, depth))
+            .sum(),
+        Node::Decision { player, key, actions } => {
+            let commands: Vec<Command> = actions.iter().map(|a| a.0.clone()).collect();
+            if player != pass.player {
+                let probabilities = pass.strategy.probabilities(&key, &commands);
+                return actions.iter().zip(probabilities)
+                    .filter(|&(_, p)| p > 0.0)
+                    .map(|((_, next), p)| p * walk(game, next, pass, reach * p, depth))
+                    .sum();
+            }
+            match pass.deciding {
+                Some(deciding) if depth < deciding => {
+                    // only on the way to the infosets being decided
+                    for (_, next) in &actions {
+                        walk(game, next, pass, reach, depth + 1);
+                    }
+                    0.0
+                },
+                Some(deciding) if depth == deciding => {
+                    let values: Vec<f64> = actions.iter()
+                        .map(|(_, next)| walk(game, next, pass, reach, depth + 1))
+                        .collect();
+                    let sums = pass.sums.entry(key)
+                        .or_insert_with(|| (commands, vec![0.0; actions.len()]));
+                    for (sum, v) in sums.1.iter_mut().zip(values) {
+                        *sum += reach * v;
+                    }
+                    0.0
+                },
+                _ => {
+                    let choice = pass.choices.get(&key).cloned().unwrap_or(0);
+                    walk(game, &actions[choice].1, pass, reach, depth + 1)
+                },
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solver::Solver;
+    use solver::kuhn::Kuhn;
+
+    // every card plays the same way at every infoset ending in `history`
+    fn always(strategy: &mut SolvedStrategy, history: &str, actions: [Command; 2], pick: usize) {
+        for card in &["J", "Q", "K"] {
+            let probabilities = actions.iter().cloned().enumerate()
+                .map(|(a, action)| (action, if a == pick { 1.0 } else { 0.0 }))
+                .collect();
+            strategy.insert(&format!("{}:{}", card, history), probabilities);
+        }
+    }
+
+    #[test]
+    fn beats_a_calling_station() {
+        // the second player never bets and always calls: bet the king,
+        // check the jack, and the queen breaks even either way
+        let mut strategy = SolvedStrategy::new("kuhn");
+        always(&mut strategy, "k", [Command::Check, Command::Raise(1)], 0);
+        always(&mut strategy, "r", [Command::Fold, Command::Call], 1);
+        let response = best_response(&Kuhn, &strategy, 0);
+        assert!((response.value - 1.0 / 3.0).abs() < 1e-9, "value {}", response.value);
+        assert_eq!(response.strategy.probabilities("K:", &[Command::Check, Command::Raise(1)]),
+                   vec![0.0, 1.0]);
+        assert_eq!(response.strategy.probabilities("J:", &[Command::Check, Command::Raise(1)]),
+                   vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn beats_a_maniac() {
+        // the first player always bets: call with the king and queen,
+        // fold the jack
+        let mut strategy = SolvedStrategy::new("kuhn");
+        always(&mut strategy, "", [Command::Check, Command::Raise(1)], 1);
+        let response = best_response(&Kuhn, &strategy, 1);
+        assert!((response.value - 1.0 / 3.0).abs() < 1e-9, "value {}", response.value);
+        assert_eq!(response.strategy.probabilities("J:r", &[Command::Fold, Command::Call]),
+                   vec![1.0, 0.0]);
+    }
+
+    #[test]
+    fn exploitability_shrinks_with_iterations() {
+        let mut solver = Solver::new(Kuhn).plus();
+        let mut last = exploitability(&Kuhn, &solver.strategy());
+        for _ in 0..3 {
+            solver.run(200);
+            let next = exploitability(&Kuhn, &solver.strategy());
+            assert!(next < last, "{} after {}", next, last);
+            last = next;
+        }
+        assert!(last < 5.0, "exploitability {}", last);
+    }
+}
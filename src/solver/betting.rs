@@ -0,0 +1,97 @@
+use game::player::Command;
+
+// fixed-limit betting between two players, as used by Leduc and the limit
+// hold'em abstraction. The history is one letter per action with a '/'
+// between streets, e.g. "rc/kr"
+#[derive(Debug, Clone, PartialEq)]
+pub struct LimitBetting {
+    pub history: String,
+    pub committed: [f64; 2],
+    pub to_act: usize,
+    pub street: usize,
+    pub folded: Option<usize>,
+    raises: usize, // on this street
+    actions: usize, // on this street
+    street_over: bool,
+}
+
+impl LimitBetting {
+    pub fn new(committed: [f64; 2], first: usize) -> LimitBetting {
+        LimitBetting {
+            history: String::new(),
+            committed,
+            to_act: first,
+            street: 0,
+            folded: None,
+            raises: 0,
+            actions: 0,
+            street_over: false,
+        }
+    }
+
+    // both players have had their say on this street
+    pub fn is_street_over(&self) -> bool {
+        self.street_over
+    }
+
+    pub fn next_street(&self, first: usize) -> LimitBetting {
+        let mut next = self.clone();
+        next.history.push('/');
+        next.street += 1;
+        next.to_act = first;
+        next.raises = 0;
+        next.actions = 0;
+        next.street_over = false;
+        next
+    }
+
+    // what the player to act may do, raising by `size` until the cap
+    pub fn actions(&self, size: f64, cap: usize) -> Vec<Command> {
+        let mut actions = if self.committed[0] != self.committed[1] {
+            vec![Command::Fold, Command::Call]
+        } else {
+            vec![Command::Check]
+        };
+        if self.raises < cap {
+            actions.push(Command::Raise(size as i32));
+        }
+        actions
+    }
+
+    pub fn apply(&self, action: &Command, size: f64) -> LimitBetting {
+        let mut next = self.clone();
+        let player = self.to_act;
+        let other = 1 - player;
+        match *action {
+            Command::Fold => {
+                next.history.push('f');
+                next.folded = Some(player);
+                next.street_over = true;
+            },
+            Command::Check | Command::Call => {
+                next.history.push(if *action == Command::Check { 'k' } else { 'c' });
+                next.committed[player] = next.committed[other];
+                // a small blind's limp leaves the big blind their option
+                next.street_over = self.actions > 0;
+            },
+            _ => {
+                next.history.push('r');
+                next.committed[player] = next.committed[other] + size;
+                next.raises += 1;
+            },
+        }
+        next.actions += 1;
+        next.to_act = other;
+        next
+    }
+
+    // the first player's payoff once the hand is over, with `showdown` 1 if
+    // they win it, -1 if they lose and 0 for a split
+    pub fn payoff(&self, showdown: i32) -> f64 {
+        match self.folded {
+            Some(0) => -self.committed[0],
+            Some(_) => self.committed[1],
+            None => showdown as f64 * self.committed[0],
+        }
+    }
+}
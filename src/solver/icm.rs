@@ -0,0 +1,38 @@
+// each player's share of the prizes by the independent chip model: a player
+// finishes first with the chance of their share of the chips, and the places
+// below are filled the same way from the players left. Busted players split
+// whatever places are left over at the bottom
+pub fn equity(stacks: &[f64], payouts: &[f64]) -> Vec<f64> {
+    let mut equity = vec![0.0; stacks.len()];
+    let mut placed = vec![false; stacks.len()];
+    let chips = stacks.iter().filter(|&&s| s > 0.0).sum();
+    finish(stacks, payouts, 0, chips, 1.0, &mut placed, &mut equity);
+    equity
+}
+
+// hands out `place` and the places below it, given that the `placed`
+// players took the ones above with chance `chance`
+fn finish(stacks: &[f64], payouts: &[f64], place: usize, chips: f64, chance: f64,
+          placed: &mut [bool], equity: &mut [f64]) {
+    if place >= payouts.len() {
+        return;
+    }
+    if (0..stacks.len()).all(|i| placed[i] || stacks[i] <= 0.0) {
+        let busted: Vec<usize> = (0..stacks.len()).filter(|&i| !placed[i]).collect();
+        let prizes: f64 = payouts[place..].iter().take(busted.len()).sum();
+        for &i in &busted {
+            equity[i] += chance * prizes / busted.len() as f64;
+        }
+        return;
+    }
+    for i in 0..stacks.len() {
+        if placed[i] || stacks[i] <= 0.0 {
+            continue;
+        }
+        let p = chance * stacks[i] / chips;
+        equity[i] += p * payouts[place];
+        placed[i] = true;
+        finish(stacks, payouts, place + 1, chips - stacks[i], p, placed, equity);
+        placed[i] = false;
+    }
+}
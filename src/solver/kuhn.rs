@@ -0,0 +1,70 @@
+use game::card::{Card, Suit};
+use game::player::Command;
+use super::{Game, Node};
+
+// Kuhn poker: a deck of a jack, a queen and a king, one card each, antes of
+// one and a single bet of one. The first player's equilibrium value is -1/18
+pub struct Kuhn;
+
+#[derive(Debug, Clone)]
+pub struct KuhnState {
+    cards: Option<(Card, Card)>,
+    history: String,
+}
+
+fn deck() -> Vec<Card> {
+    (11..14).map(|val| Card { suit: Suit::Spades, val }).collect()
+}
+
+impl Game for Kuhn {
+    type State = KuhnState;
+
+    fn name(&self) -> String {
+        "kuhn".to_string()
+    }
+
+    fn root(&self) -> KuhnState {
+        KuhnState { cards: None, history: String::new() }
+    }
+
+    fn node(&self, state: &KuhnState) -> Node<KuhnState> {
+        let (c1, c2) = match state.cards {
+            Some(cards) => cards,
+            None => {
+                let deck = deck();
+                let mut deals = Vec::new();
+                for &c1 in &deck {
+                    for &c2 in deck.iter().filter(|&&c2| c2 != c1) {
+                        let deal = KuhnState { cards: Some((c1, c2)), history: String::new() };
+                        deals.push((1.0 / 6.0, deal));
+                    }
+                }
+                return Node::Chance(deals);
+            },
+        };
+        let showdown = if c1.val > c2.val { 1.0 } else { -1.0 };
+        match state.history.as_str() {
+            "kk" => return Node::Terminal(showdown),
+            "rc" | "krc" => return Node::Terminal(2.0 * showdown),
+            "rf" => return Node::Terminal(1.0),
+            "krf" => return Node::Terminal(-1.0),
+            _ => {},
+        }
+        let player = state.history.len() % 2;
+        let card = if player == 0 { c1 } else { c2 };
+        let actions = if state.history.ends_with('r') {
+            vec![(Command::Fold, 'f'), (Command::Call, 'c')]
+        } else {
+            vec![(Command::Check, 'k'), (Command::Raise(1), 'r')]
+        };
+        let key = format!("{}:{}", &card.short()[..1], state.history);
+        let actions = actions.into_iter()
+            .map(|(action, letter)| {
+                let mut next = state.clone();
+                next.history.push(letter);
+                (action, next)
+            })
+            .collect();
+        Node::Decision { player, key, actions }
+    }
+}
@@ -0,0 +1,110 @@
+use game::card::{Card, Suit};
+use super::{Game, Node};
+use super::betting::LimitBetting;
+
+// raises are 2 on the first street and 4 on the second, at most two a street
+const SIZES: [f64; 2] = [2.0, 4.0];
+const RAISE_CAP: usize = 2;
+
+// Leduc hold'em: two each of a jack, queen and king, one private card each,
+// antes of one, a round of betting, one board card and another round. A
+// pair with the board wins, otherwise the higher card
+pub struct Leduc;
+
+#[derive(Debug, Clone)]
+pub struct LeducState {
+    cards: Option<(Card, Card)>,
+    board: Option<Card>,
+    betting: LimitBetting,
+}
+
+fn deck() -> Vec<Card> {
+    let mut deck = Vec::new();
+    for &suit in &[Suit::Spades, Suit::Hearts] {
+        for val in 11..14 {
+            deck.push(Card { suit, val });
+        }
+    }
+    deck
+}
+
+// 1 if the first player wins, -1 if the second does, 0 for a split
+fn showdown(c1: Card, c2: Card, board: Card) -> i32 {
+    if c1.val == board.val {
+        1
+    } else if c2.val == board.val {
+        -1
+    } else {
+        (c1.val - c2.val).signum()
+    }
+}
+
+impl Game for Leduc {
+    type State = LeducState;
+
+    fn name(&self) -> String {
+        "leduc".to_string()
+    }
+
+    fn root(&self) -> LeducState {
+        LeducState { cards: None, board: None, betting: LimitBetting::new([1.0, 1.0], 0) }
+    }
+
+    fn node(&self, state: &LeducState) -> Node<LeducState> {
+        let deck = deck();
+        let (c1, c2) = match state.cards {
+            Some(cards) => cards,
+            None => {
+                let mut deals = Vec::new();
+                for &c1 in &deck {
+                    for &c2 in deck.iter().filter(|&&c2| c2 != c1) {
+                        let mut deal = state.clone();
+                        deal.cards = Some((c1, c2));
+                        deals.push((1.0 / 30.0, deal));
+                    }
+                }
+                return Node::Chance(deals);
+            },
+        };
+        let betting = &state.betting;
+        if betting.folded.is_some() {
+            return Node::Terminal(betting.payoff(0));
+        }
+        if betting.is_street_over() {
+            match state.board {
+                Some(board) => return Node::Terminal(betting.payoff(showdown(c1, c2, board))),
+                None => {
+                    let boards: Vec<(f64, LeducState)> = deck.iter()
+                        .filter(|&&card| card != c1 && card != c2)
+                        .map(|&card| {
+                            let next = LeducState {
+                                cards: state.cards,
+                                board: Some(card),
+                                betting: betting.next_street(0),
+                            };
+                            (0.25, next)
+                        })
+                        .collect();
+                    return Node::Chance(boards);
+                },
+            }
+        }
+
+        let player = betting.to_act;
+        let card = if player == 0 { c1 } else { c2 };
+        let board = state.board.map_or(String::new(), |b| b.short()[..1].to_string());
+        let key = format!("{}{}:{}", &card.short()[..1], board, betting.history);
+        let size = SIZES[betting.street];
+        let actions = betting.actions(size, RAISE_CAP).into_iter()
+            .map(|action| {
+                let next = LeducState {
+                    cards: state.cards,
+                    board: state.board,
+                    betting: betting.apply(&action, size),
+                };
+                (action, next)
+            })
+            .collect();
+        Node::Decision { player, key, actions }
+    }
+}
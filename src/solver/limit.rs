@@ -0,0 +1,185 @@
+use game::card::{Card, Hand};
+use game::table;
+use bot::seeded_rng;
+use bot::strength::{chen_score, postflop_strength};
+use super::{Game, Node};
+use super::betting::LimitBetting;
+
+use rand::Rng;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+// small bets preflop and on the flop, big bets on the turn and river
+const SIZES: [f64; 4] = [1.0, 1.0, 2.0, 2.0];
+const RAISE_CAP: usize = 3;
+// how much of the board is out on each street
+const BOARD: [usize; 4] = [0, 3, 4, 5];
+
+// sorts hands into strength buckets, the same way for the solver and for a
+// bot playing its strategy
+#[derive(Debug, Clone)]
+pub struct Buckets {
+    count: usize,
+    // the share of starting hands with a lower Chen score, by score
+    preflop: Vec<f64>,
+}
+
+impl Buckets {
+    pub fn new(count: usize) -> Buckets {
+        let deck = table::build_deck();
+        let mut scores = vec![0; 22];
+        for (i, c1) in deck.iter().enumerate() {
+            for c2 in &deck[i + 1..] {
+                scores[(chen_score(c1, c2) + 1) as usize] += 1;
+            }
+        }
+        let total: i32 = scores.iter().sum();
+        let mut below = 0;
+        let mut preflop = Vec::new();
+        for n in scores {
+            preflop.push(below as f64 / total as f64);
+            below += n;
+        }
+        Buckets { count: count.max(1), preflop }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    // preflop hands are bucketed by how many hands they beat, later ones by
+    // their estimated strength
+    pub fn bucket(&self, c1: &Rc<Card>, c2: &Rc<Card>, board: &[Rc<Card>]) -> usize {
+        let strength = if board.is_empty() {
+            self.preflop[(chen_score(c1, c2) + 1) as usize]
+        } else {
+            postflop_strength(c1, c2, board)
+        };
+        ((strength * self.count as f64) as usize).min(self.count - 1)
+    }
+
+    // a player's buckets on every street so far
+    pub fn buckets(&self, c1: &Rc<Card>, c2: &Rc<Card>, board: &[Rc<Card>]) -> Vec<usize> {
+        BOARD.iter()
+            .take_while(|&&n| n <= board.len())
+            .map(|&n| self.bucket(c1, c2, &board[..n]))
+            .collect()
+    }
+}
+
+// the infoset key for a player with these buckets after this betting
+pub fn info_key(buckets: &[usize], history: &str) -> String {
+    let buckets: Vec<String> = buckets.iter().map(|b| b.to_string()).collect();
+    format!("{}:{}", buckets.join("."), history)
+}
+
+// the bucket count of a limit abstraction from its name
+pub fn parse_name(name: &str) -> Option<usize> {
+    let mut words = name.split_whitespace();
+    match (words.next(), words.next().and_then(|n| n.parse().ok()), words.next()) {
+        (Some("limit"), Some(count), None) => Some(count),
+        _ => None,
+    }
+}
+
+// a real deal reduced to each player's buckets and who wins at showdown
+struct Deal {
+    buckets: [[usize; 4]; 2],
+    showdown: i32,
+}
+
+// heads-up limit hold'em with blinds of a half and one, each player's cards
+// replaced by a strength bucket on every street. Deals are sampled once up
+// front, so the game is played over a fixed set of real deals. The first
+// player is the button, who posts the small blind
+pub struct LimitAbstraction {
+    buckets: Buckets,
+    deals: Vec<Deal>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LimitState {
+    deal: Option<usize>,
+    betting: LimitBetting,
+}
+
+impl LimitAbstraction {
+    pub fn new(buckets: usize, deals: usize, seed: u64) -> LimitAbstraction {
+        let buckets = Buckets::new(buckets);
+        let mut rng = seeded_rng(seed);
+        let mut sampled = Vec::with_capacity(deals);
+        for _ in 0..deals {
+            let mut deck = table::build_deck();
+            rng.shuffle(&mut deck);
+            let board = &deck[4..9];
+            let mut hands = Vec::new();
+            let mut deal = Deal { buckets: [[0; 4]; 2], showdown: 0 };
+            for (player, cards) in deck[..4].chunks(2).enumerate() {
+                let streets = buckets.buckets(&cards[0], &cards[1], board);
+                deal.buckets[player].copy_from_slice(&streets);
+                let mut all = cards.to_vec();
+                all.extend_from_slice(board);
+                hands.push(Hand::make_hand(all).ok());
+            }
+            deal.showdown = match hands[0].cmp(&hands[1]) {
+                Ordering::Greater => 1,
+                Ordering::Less => -1,
+                Ordering::Equal => 0,
+            };
+            sampled.push(deal);
+        }
+        LimitAbstraction { buckets, deals: sampled }
+    }
+
+    pub fn buckets(&self) -> &Buckets {
+        &self.buckets
+    }
+}
+
+impl Game for LimitAbstraction {
+    type State = LimitState;
+
+    fn name(&self) -> String {
+        format!("limit {}", self.buckets.count())
+    }
+
+    fn root(&self) -> LimitState {
+        LimitState { deal: None, betting: LimitBetting::new([0.5, 1.0], 0) }
+    }
+
+    fn node(&self, state: &LimitState) -> Node<LimitState> {
+        let deal = match state.deal {
+            Some(deal) => &self.deals[deal],
+            None => {
+                let p = 1.0 / self.deals.len() as f64;
+                let deals = (0..self.deals.len())
+                    .map(|i| (p, LimitState { deal: Some(i), betting: state.betting.clone() }))
+                    .collect();
+                return Node::Chance(deals);
+            },
+        };
+        let betting = &state.betting;
+        if betting.folded.is_some() {
+            return Node::Terminal(betting.payoff(0));
+        }
+        if betting.is_street_over() {
+            if betting.street == 3 {
+                return Node::Terminal(betting.payoff(deal.showdown));
+            }
+            // the big blind acts first after the flop
+            let next = LimitState { deal: state.deal, betting: betting.next_street(1) };
+            return self.node(&next);
+        }
+
+        let player = betting.to_act;
+        let key = info_key(&deal.buckets[player][..betting.street + 1], &betting.history);
+        let size = SIZES[betting.street];
+        let actions = betting.actions(size, RAISE_CAP).into_iter()
+            .map(|action| {
+                let next = LimitState { deal: state.deal, betting: betting.apply(&action, size) };
+                (action, next)
+            })
+            .collect();
+        Node::Decision { player, key, actions }
+    }
+}
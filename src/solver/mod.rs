@@ -0,0 +1,363 @@
+use game::player::Command;
+use game::error::GameError;
+use bot::seeded_rng;
+
+use rand::{Rng, StdRng};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufRead, Write};
+
+pub mod betting;
+pub mod kuhn;
+pub mod leduc;
+pub mod limit;
+pub mod push_fold;
+pub mod icm;
+pub mod best_response;
+
+// first line of every strategy file
+pub const STRATEGY_HEADER: &str = "rust-poker strategy 1";
+
+// a point in a two-player zero-sum game
+pub enum Node<S> {
+    // the payoff to the first player, the second gets the opposite
+    Terminal(f64),
+    // the outcomes of a deal with their probabilities
+    Chance(Vec<(f64, S)>),
+    // `key` names everything the player to act knows, so states they can't
+    // tell apart share it
+    Decision { player: usize, key: String, actions: Vec<(Command, S)> },
+}
+
+pub trait Game {
+    type State;
+
+    // written to strategy files so they can be matched with their game
+    fn name(&self) -> String;
+    fn root(&self) -> Self::State;
+    fn node(&self, state: &Self::State) -> Node<Self::State>;
+}
+
+struct InfoSet {
+    actions: Vec<Command>,
+    regrets: Vec<f64>,
+    strategy_sum: Vec<f64>,
+}
+
+impl InfoSet {
+    // regret matching: each action in proportion to its positive regret
+    fn current(&self) -> Vec<f64> {
+        let positive: Vec<f64> = self.regrets.iter().map(|r| r.max(0.0)).collect();
+        let total: f64 = positive.iter().sum();
+        if total > 0.0 {
+            positive.iter().map(|r| r / total).collect()
+        } else {
+            vec![1.0 / self.actions.len() as f64; self.actions.len()]
+        }
+    }
+
+    fn average(&self) -> Vec<f64> {
+        let total: f64 = self.strategy_sum.iter().sum();
+        if total > 0.0 {
+            self.strategy_sum.iter().map(|s| s / total).collect()
+        } else {
+            vec![1.0 / self.actions.len() as f64; self.actions.len()]
+        }
+    }
+}
+
+// counterfactual regret minimization. The average strategy it plays over
+// all iterations tends to an equilibrium
+pub struct Solver<G: Game> {
+    game: G,
+    plus: bool,
+    // samples one outcome at each chance node rather than walking them all
+    rng: Option<StdRng>,
+    infosets: HashMap<String, InfoSet>,
+    iterations: u64,
+}
+
+impl<G: Game> Solver<G> {
+    pub fn new(game: G) -> Solver<G> {
+        Solver { game, plus: false, rng: None, infosets: HashMap::new(), iterations: 0 }
+    }
+
+    // CFR+: regrets never drop below zero and later iterations count for
+    // more in the average, which converges much faster
+    pub fn plus(mut self) -> Solver<G> {
+        self.plus = true;
+        self
+    }
+
+    // for games with too many deals to walk every iteration
+    pub fn sampled(mut self, seed: u64) -> Solver<G> {
+        self.rng = Some(seeded_rng(seed));
+        self
+    }
+
+    pub fn game(&self) -> &G {
+        &self.game
+    }
+
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    // each iteration updates both players in turn
+    pub fn run(&mut self, iterations: u64) {
+        for _ in 0..iterations {
+            self.iterations += 1;
+            for player in 0..2 {
+                let root = self.game.root();
+                self.walk(&root, player, [1.0, 1.0], 1.0);
+            }
+        }
+    }
+
+    // the average strategy so far
+    pub fn strategy(&self) -> SolvedStrategy {
+        let mut strategy = SolvedStrategy::new(&self.game.name());
+        for (key, infoset) in &self.infosets {
+            let probabilities = infoset.actions.iter().cloned().zip(infoset.average()).collect();
+            strategy.insert(key, probabilities);
+        }
+        strategy
+    }
+
+    // the value of `state` to `player`, updating their regrets and the
+    // opponent's average strategy along the way
+    fn walk(&mut self, state: &G::State, player: usize, reach: [f64; 2], chance: f64) -> f64 {
+        match self.game.node(state) {
+            Node::Terminal(payoff) => if player == 0 { payoff } else { -payoff },
+            Node::Chance(outcomes) => {
+                if let Some(ref mut rng) = self.rng {
+                    let outcome = pick(outcomes.iter().map(|o| o.0), rng.gen::<f64>());
+                    return self.walk(&outcomes[outcome].1, player, reach, chance);
+                }
+                outcomes.iter()
+                    .map(|&(p, ref next)| p * self.walk(next, player, reach, chance * p))
+                    .sum()
+            },
+            Node::Decision { player: acting, key, actions } => {
+                let current = {
+                    let infoset = self.infosets.entry(key.clone()).or_insert_with(|| InfoSet {
+                        actions: actions.iter().map(|a| a.0.clone()).collect(),
+                        regrets: vec![0.0; actions.len()],
+                        strategy_sum: vec![0.0; actions.len()],
+                    });
+                    infoset.current()
+                };
+                let mut values = Vec::with_capacity(actions.len());
+                for (i, (_, next)) in actions.iter().enumerate() {
+                    let mut reach = reach;
+                    reach[acting] *= current[i];
+                    // nothing below a branch the opponent never takes matters
+                    if acting != player && reach[acting] == 0.0 {
+                        values.push(0.0);
+                        continue;
+                    }
+                    values.push(self.walk(next, player, reach, chance));
+                }
+                let value: f64 = current.iter().zip(&values).map(|(p, v)| p * v).sum();
+
+                let weight = if self.plus { self.iterations as f64 } else { 1.0 };
+                let plus = self.plus;
+                let infoset = self.infosets.get_mut(&key).expect("an infoset just visited");
+                if acting == player {
+                    let opponent = reach[1 - player] * chance;
+                    for (regret, v) in infoset.regrets.iter_mut().zip(&values) {
+                        *regret += opponent * (v - value);
+                        if plus {
+                            *regret = regret.max(0.0);
+                        }
+                    }
+                } else {
+                    for (sum, p) in infoset.strategy_sum.iter_mut().zip(&current) {
+                        *sum += weight * reach[acting] * p;
+                    }
+                }
+                value
+            },
+        }
+    }
+}
+
+// the outcome that `roll`, between 0 and 1, lands on
+fn pick<I: Iterator<Item = f64>>(probabilities: I, roll: f64) -> usize {
+    let mut total = 0.0;
+    let mut last = 0;
+    for (i, p) in probabilities.enumerate() {
+        total += p;
+        if roll < total {
+            return i;
+        }
+        last = i;
+    }
+    last
+}
+
+// the probability of each action at every infoset of a game. Infosets it
+// doesn't know are played uniformly
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolvedStrategy {
+    pub game: String,
+    infosets: BTreeMap<String, Vec<(Command, f64)>>,
+}
+
+impl SolvedStrategy {
+    pub fn new(game: &str) -> SolvedStrategy {
+        SolvedStrategy { game: game.to_string(), infosets: BTreeMap::new() }
+    }
+
+    pub fn insert(&mut self, key: &str, probabilities: Vec<(Command, f64)>) {
+        self.infosets.insert(key.to_string(), probabilities);
+    }
+
+    pub fn get(&self, key: &str) -> Option<&[(Command, f64)]> {
+        self.infosets.get(key).map(|p| &p[..])
+    }
+
+    pub fn len(&self) -> usize {
+        self.infosets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.infosets.is_empty()
+    }
+
+    pub fn infosets(&self) -> impl Iterator<Item = (&String, &Vec<(Command, f64)>)> {
+        self.infosets.iter()
+    }
+
+    // the probability of each of `actions` at `key`
+    pub fn probabilities(&self, key: &str, actions: &[Command]) -> Vec<f64> {
+        let known = match self.infosets.get(key) {
+            Some(known) => known,
+            None => return vec![1.0 / actions.len() as f64; actions.len()],
+        };
+        actions.iter()
+            .map(|a| known.iter().find(|k| k.0 == *a).map_or(0.0, |k| k.1))
+            .collect()
+    }
+
+    // an action for `key`, picked by its probability
+    pub fn choose<R: Rng>(&self, key: &str, rng: &mut R) -> Option<Command> {
+        let probabilities = self.infosets.get(key)?;
+        let i = pick(probabilities.iter().map(|p| p.1), rng.gen::<f64>());
+        probabilities.get(i).map(|p| p.0.clone())
+    }
+
+    // the header, the game's name, then a line per infoset like
+    // "K:k c:0.6667 f:0.3333"
+    pub fn save<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "{}", STRATEGY_HEADER)?;
+        writeln!(out, "game {}", self.game)?;
+        for (key, probabilities) in &self.infosets {
+            write!(out, "{}", key)?;
+            for &(ref action, p) in probabilities {
+                write!(out, " {}:{:.4}", action_code(action), p)?;
+            }
+            writeln!(out)?;
+        }
+        out.flush()
+    }
+
+    pub fn load<R: BufRead>(input: R) -> Result<SolvedStrategy, GameError> {
+        let invalid = |reason: &str| GameError::InvalidStrategy(reason.to_string());
+        let mut lines = input.lines();
+        match lines.next() {
+            Some(Ok(ref header)) if header == STRATEGY_HEADER => {},
+            _ => return Err(invalid("not a strategy file")),
+        }
+        let game = match lines.next() {
+            Some(Ok(ref line)) if line.starts_with("game ") => line[5..].to_string(),
+            _ => return Err(invalid("no game")),
+        };
+        let mut strategy = SolvedStrategy::new(&game);
+        for line in lines {
+            let line = line.map_err(|err| invalid(&err.to_string()))?;
+            let mut words = line.split_whitespace();
+            let key = match words.next() {
+                Some(key) => key,
+                None => continue,
+            };
+            let mut probabilities = Vec::new();
+            for word in words {
+                let (action, p) = match word.find(':') {
+                    Some(i) => (read_action(&word[..i]), word[i + 1..].parse().ok()),
+                    None => (None, None),
+                };
+                match (action, p) {
+                    (Some(action), Some(p)) => probabilities.push((action, p)),
+                    _ => return Err(invalid(&line)),
+                }
+            }
+            strategy.insert(key, probabilities);
+        }
+        Ok(strategy)
+    }
+}
+
+// the single letters used in infoset keys, and raises with their size
+pub fn action_code(action: &Command) -> String {
+    match *action {
+        Command::Fold => "f".to_string(),
+        Command::Check => "k".to_string(),
+        Command::Call => "c".to_string(),
+        Command::Raise(x) => format!("r{}", x),
+        ref other => format!("{:?}", other),
+    }
+}
+
+fn read_action(code: &str) -> Option<Command> {
+    match code {
+        "f" => Some(Command::Fold),
+        "k" => Some(Command::Check),
+        "c" => Some(Command::Call),
+        _ if code.starts_with('r') => code[1..].parse().ok().map(Command::Raise),
+        _ => None,
+    }
+}
+
+// the first player's expected payoff when both players follow `strategy`
+pub fn expected_value<G: Game>(game: &G, strategy: &SolvedStrategy) -> f64 {
+    value_of(game, &game.root(), strategy)
+}
+
+fn value_of<G: Game>(game: &G, state: &G::State, strategy: &SolvedStrategy) -> f64 {
+    match game.node(state) {
+        Node::Terminal(payoff) => payoff,
+        Node::Chance(outcomes) => {
+            outcomes.iter().map(|&(p, ref next)| p * value_of(game, next, strategy)).sum()
+        },
+        Node::Decision { key, actions, .. } => {
+            let commands: Vec<Command> = actions.iter().map(|a| a.0.clone()).collect();
+            let probabilities = strategy.probabilities(&key, &commands);
+            actions.iter().zip(probabilities)
+                .filter(|&(_, p)| p > 0.0)
+                .map(|((_, next), p)| p * value_of(game, next, strategy))
+                .sum()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::kuhn::Kuhn;
+
+    #[test]
+    fn cfr_plus_finds_kuhn_value() {
+        let mut solver = Solver::new(Kuhn).plus();
+        solver.run(5000);
+        let value = expected_value(&Kuhn, &solver.strategy());
+        assert!((value + 1.0 / 18.0).abs() < 1e-3, "value {}", value);
+    }
+
+    #[test]
+    fn cfr_finds_kuhn_value() {
+        let mut solver = Solver::new(Kuhn);
+        solver.run(5000);
+        let value = expected_value(&Kuhn, &solver.strategy());
+        assert!((value + 1.0 / 18.0).abs() < 5e-3, "value {}", value);
+    }
+}
@@ -0,0 +1,535 @@
+use game::card::{Hand, hand_value};
+use game::player::Command;
+use game::table;
+use game::error::GameError;
+use bot::seeded_rng;
+use bot::hand_class::HandClass;
+use super::{Game, Node};
+use super::icm;
+
+use rand::Rng;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+// heads-up push or fold: the small blind moves all in for `stack` big blinds
+// or folds, and the big blind calls or folds. Showdowns are settled from
+// deals sampled once up front, grouped by the two players' hand classes
+pub struct PushFold {
+    stack: f64,
+    // each pairing with how often it's dealt and the small blind's average
+    // showdown result, 1 for always winning and -1 for always losing
+    pairings: Vec<(HandClass, HandClass, f64, f64)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PushFoldState {
+    pairing: Option<usize>,
+    history: String,
+}
+
+impl PushFold {
+    pub fn new(stack: f64, deals: usize, seed: u64) -> PushFold {
+        let mut rng = seeded_rng(seed);
+        let mut results: HashMap<(HandClass, HandClass), (usize, i32)> = HashMap::new();
+        for _ in 0..deals {
+            let mut deck = table::build_deck();
+            rng.shuffle(&mut deck);
+            let board = &deck[4..9];
+            let hand = |c1: usize, c2: usize| {
+                let mut cards = vec![deck[c1].clone(), deck[c2].clone()];
+                cards.extend_from_slice(board);
+                Hand::make_hand(cards).ok()
+            };
+            let showdown = match hand(0, 1).cmp(&hand(2, 3)) {
+                Ordering::Greater => 1,
+                Ordering::Less => -1,
+                Ordering::Equal => 0,
+            };
+            let classes = (HandClass::of(&deck[0], &deck[1]), HandClass::of(&deck[2], &deck[3]));
+            let result = results.entry(classes).or_insert((0, 0));
+            result.0 += 1;
+            result.1 += showdown;
+        }
+        let mut pairings: Vec<_> = results.into_iter()
+            .map(|((small, big), (n, total))| {
+                (small, big, n as f64 / deals as f64, total as f64 / n as f64)
+            })
+            .collect();
+        pairings.sort_by_key(|pairing| (pairing.0, pairing.1));
+        PushFold { stack, pairings }
+    }
+
+    pub fn stack(&self) -> f64 {
+        self.stack
+    }
+}
+
+impl Game for PushFold {
+    type State = PushFoldState;
+
+    fn name(&self) -> String {
+        format!("pushfold {}", self.stack)
+    }
+
+    fn root(&self) -> PushFoldState {
+        PushFoldState { pairing: None, history: String::new() }
+    }
+
+    fn node(&self, state: &PushFoldState) -> Node<PushFoldState> {
+        let (small, big, _, showdown) = match state.pairing {
+            Some(pairing) => self.pairings[pairing],
+            None => {
+                let deals = self.pairings.iter().enumerate()
+                    .map(|(i, pairing)| {
+                        (pairing.2, PushFoldState { pairing: Some(i), history: String::new() })
+                    })
+                    .collect();
+                return Node::Chance(deals);
+            },
+        };
+        let (player, class, actions) = match state.history.as_str() {
+            "" => (0, small, vec![(Command::Fold, 'f'), (Command::Raise(self.stack as i32), 'r')]),
+            "r" => (1, big, vec![(Command::Fold, 'f'), (Command::Call, 'c')]),
+            "f" => return Node::Terminal(-0.5),
+            "rf" => return Node::Terminal(1.0),
+            _ => return Node::Terminal(showdown * self.stack),
+        };
+        let key = format!("{}:{}", class, state.history);
+        let actions = actions.into_iter()
+            .map(|(action, letter)| {
+                let mut next = state.clone();
+                next.history.push(letter);
+                (action, next)
+            })
+            .collect();
+        Node::Decision { player, key, actions }
+    }
+}
+
+// the most players a push/fold chart is solved for
+pub const MAX_PLAYERS: usize = 6;
+// the usual names of the seats, counting back from the big blind
+const POSITIONS: [&str; MAX_PLAYERS] = ["BB", "SB", "BTN", "CO", "HJ", "UTG"];
+
+// a push/fold spot for any number of players: each one in turn moves all in
+// or folds, and once someone is all in the rest call or fold. Players are
+// listed in the order they act, so the last two are the blinds and heads up
+// the small blind acts first
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setup {
+    pub stacks: Vec<f64>, // in big blinds, before the blinds and antes
+    pub ante: f64,
+    // the prizes for each place, to play for ICM equity rather than chips
+    pub payouts: Option<Vec<f64>>,
+}
+
+impl Setup {
+    pub fn new(players: usize, stack: f64) -> Setup {
+        Setup { stacks: vec![stack; players], ante: 0.0, payouts: None }
+    }
+
+    pub fn stacks(mut self, stacks: Vec<f64>) -> Setup {
+        self.stacks = stacks;
+        self
+    }
+
+    pub fn ante(mut self, ante: f64) -> Setup {
+        self.ante = ante;
+        self
+    }
+
+    // payouts are scaled to shares of the prize pool
+    pub fn icm(mut self, payouts: Vec<f64>) -> Setup {
+        let total: f64 = payouts.iter().sum();
+        self.payouts = Some(payouts.iter().map(|p| p / total).collect());
+        self
+    }
+
+    pub fn players(&self) -> usize {
+        self.stacks.len()
+    }
+
+    fn check(&self) -> Result<(), GameError> {
+        let invalid = |reason: &str| Err(GameError::InvalidSetup(reason.to_string()));
+        if self.players() < 2 || self.players() > MAX_PLAYERS {
+            return invalid(&format!("push/fold needs 2 to {} players", MAX_PLAYERS));
+        }
+        if self.stacks.iter().any(|&s| s <= 0.0) || self.ante < 0.0 {
+            return invalid("push/fold stacks must be positive and antes can't be negative");
+        }
+        match self.payouts {
+            Some(ref payouts) if payouts.is_empty() || payouts.iter().any(|&p| p < 0.0) => {
+                invalid("push/fold payouts must be positive")
+            },
+            _ => Ok(()),
+        }
+    }
+
+    // the blind and ante a player has to put in, or their whole stack
+    fn posted(&self, player: usize) -> f64 {
+        let blind = match self.players() - player {
+            1 => 1.0,
+            2 => 0.5,
+            _ => 0.0,
+        };
+        (blind + self.ante).min(self.stacks[player])
+    }
+}
+
+// a sampled deal reduced to each player's hand class and how many of the
+// other hands theirs beats at showdown
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Deal {
+    classes: [usize; MAX_PLAYERS],
+    strength: [usize; MAX_PLAYERS],
+}
+
+// the distinct deals and how many times each came up
+fn deal_hands(players: usize, deals: usize, seed: u64) -> Vec<(Deal, usize)> {
+    let mut rng = seeded_rng(seed);
+    let mut deck = table::build_deck();
+    let mut counts = HashMap::new();
+    for _ in 0..deals {
+        rng.shuffle(&mut deck);
+        let mut cards = deck[2 * players - 2..2 * players + 5].to_vec();
+        let mut values = [0; MAX_PLAYERS];
+        let mut deal = Deal { classes: [0; MAX_PLAYERS], strength: [0; MAX_PLAYERS] };
+        for i in 0..players {
+            cards[..2].clone_from_slice(&deck[2 * i..2 * i + 2]);
+            values[i] = hand_value(&cards);
+            deal.classes[i] = HandClass::of(&cards[0], &cards[1]).index();
+        }
+        for i in 0..players {
+            deal.strength[i] = values[..players].iter().filter(|&&v| v < values[i]).count();
+        }
+        *counts.entry(deal).or_insert(0) += 1;
+    }
+    // sorted, so that solving is deterministic
+    let mut deals: Vec<(Deal, usize)> = counts.into_iter().collect();
+    deals.sort();
+    deals
+}
+
+// decisions are numbered by how many players acted before, then by which
+// of them went in, one bit each
+fn spot_index(acted: usize, went_in: usize) -> usize {
+    (1 << acted) - 1 + went_in
+}
+
+// each player's stack after the pot goes to the best live hands. The pot is
+// split in layers, so a player all in for less only wins what they matched
+fn settle(setup: &Setup, put_in: &[f64; MAX_PLAYERS], live: &[bool; MAX_PLAYERS],
+          strength: &[usize; MAX_PLAYERS]) -> [f64; MAX_PLAYERS] {
+    let players = setup.players();
+    let mut after = [0.0; MAX_PLAYERS];
+    for i in 0..players {
+        after[i] = setup.stacks[i] - put_in[i];
+    }
+    let mut levels = *put_in;
+    levels[..players].sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let mut below = 0.0;
+    for &level in &levels[..players] {
+        if level <= below {
+            continue;
+        }
+        let share = |i: usize| (put_in[i].min(level) - below).max(0.0);
+        let contesting = |i: usize| live[i] && put_in[i] >= level;
+        match (0..players).filter(|&i| contesting(i)).map(|i| strength[i]).max() {
+            Some(best) => {
+                let layer: f64 = (0..players).map(share).sum();
+                let winners = (0..players).filter(|&i| contesting(i) && strength[i] == best);
+                let split = layer / winners.clone().count() as f64;
+                for i in winners {
+                    after[i] += split;
+                }
+            },
+            // nobody still in matched it, so it goes back
+            None => for (i, chips) in after[..players].iter_mut().enumerate() {
+                *chips += share(i);
+            },
+        }
+        below = level;
+    }
+    after
+}
+
+// one pass over the deals with every player following the charts
+struct Evaluation<'a> {
+    setup: &'a Setup,
+    charts: &'a [Vec<f64>],
+    // by decision and hand class, the value of folding and of going in,
+    // weighted by how likely the other players are to get there
+    values: Vec<Vec<[f64; 2]>>,
+    // ICM equity at the start of the hand, and after each result seen so far
+    equity: Vec<f64>,
+    icm: HashMap<[u64; MAX_PLAYERS], [f64; MAX_PLAYERS]>,
+}
+
+impl<'a> Evaluation<'a> {
+    fn new(setup: &'a Setup, charts: &'a [Vec<f64>]) -> Evaluation<'a> {
+        let equity = match setup.payouts {
+            Some(ref payouts) => icm::equity(&setup.stacks, payouts),
+            None => Vec::new(),
+        };
+        Evaluation {
+            setup,
+            charts,
+            values: vec![vec![[0.0; 2]; 169]; charts.len()],
+            equity,
+            icm: HashMap::new(),
+        }
+    }
+
+    // what each player wins from `acted` on, where `went_in` has a bit for
+    // each player all in or calling so far and `reach` is, for each player,
+    // the times the deal came up by the chance of the others playing this way
+    fn walk(&mut self, deal: &Deal, acted: usize, went_in: usize,
+            reach: [f64; MAX_PLAYERS]) -> [f64; MAX_PLAYERS] {
+        let players = self.setup.players();
+        if acted == players || (acted == players - 1 && went_in == 0) {
+            return self.payoff(deal, went_in);
+        }
+        if reach[..players].iter().all(|&r| r == 0.0) {
+            return [0.0; MAX_PLAYERS];
+        }
+        let spot = spot_index(acted, went_in);
+        let class = deal.classes[acted];
+        let p = self.charts[spot][class];
+        let mut value = [0.0; MAX_PLAYERS];
+        for &(action, chance) in &[(0, 1.0 - p), (1, p)] {
+            // adds nothing, to the player to act or anyone else
+            if chance == 0.0 && reach[acted] == 0.0 {
+                continue;
+            }
+            let mut next = reach;
+            for (i, r) in next.iter_mut().enumerate() {
+                if i != acted {
+                    *r *= chance;
+                }
+            }
+            let result = self.walk(deal, acted + 1, went_in | action << acted, next);
+            self.values[spot][class][action] += reach[acted] * result[acted];
+            for i in 0..players {
+                value[i] += chance * result[i];
+            }
+        }
+        value
+    }
+
+    fn payoff(&mut self, deal: &Deal, went_in: usize) -> [f64; MAX_PLAYERS] {
+        let players = self.setup.players();
+        let mut put_in = [0.0; MAX_PLAYERS];
+        let mut live = [false; MAX_PLAYERS];
+        for i in 0..players {
+            put_in[i] = self.setup.posted(i);
+            if went_in >> i & 1 == 1 {
+                put_in[i] = self.setup.stacks[i];
+                live[i] = true;
+            }
+        }
+        // folded round to the big blind
+        live[players - 1] |= went_in == 0;
+        let after = settle(self.setup, &put_in, &live, &deal.strength);
+        let mut payoff = [0.0; MAX_PLAYERS];
+        match self.setup.payouts {
+            Some(ref payouts) => {
+                let equity = self.icm.entry(after.map(f64::to_bits)).or_insert_with(|| {
+                    let mut equity = [0.0; MAX_PLAYERS];
+                    equity[..players].copy_from_slice(&icm::equity(&after[..players], payouts));
+                    equity
+                });
+                for i in 0..players {
+                    payoff[i] = equity[i] - self.equity[i];
+                }
+            },
+            None => for i in 0..players {
+                payoff[i] = after[i] - self.setup.stacks[i];
+            },
+        }
+        payoff
+    }
+}
+
+// the equilibrium of a push/fold spot: for every decision, the chance of
+// each of the 169 hand classes moving all in or calling
+#[derive(Debug, Clone)]
+pub struct Charts {
+    pub setup: Setup,
+    charts: Vec<Vec<f64>>, // by decision, then by HandClass::index
+    gain: f64,
+}
+
+impl Charts {
+    // fictitious play over `deals` sampled deals: each chart is the average
+    // of its best responses to the others so far
+    pub fn solve(setup: &Setup, deals: usize, iterations: usize,
+                 seed: u64) -> Result<Charts, GameError> {
+        setup.check()?;
+        let players = setup.players();
+        let total = deals.max(1);
+        let deals = deal_hands(players, total, seed);
+        let mut charts = vec![vec![0.5; 169]; spot_index(players, 0)];
+        for iteration in 0..iterations {
+            let values = evaluate(setup, &charts, &deals);
+            for (chart, values) in charts.iter_mut().zip(values) {
+                for (p, v) in chart.iter_mut().zip(values) {
+                    let best = match v[1].partial_cmp(&v[0]) {
+                        Some(Ordering::Greater) => 1.0,
+                        Some(Ordering::Less) => 0.0,
+                        _ => *p,
+                    };
+                    *p += (best - *p) / (iteration + 1) as f64;
+                }
+            }
+        }
+
+        // every player acts once, so the most one can gain is the sum of what
+        // they'd gain at each of their decisions
+        let values = evaluate(setup, &charts, &deals);
+        let mut gains = vec![0.0; players];
+        for (acted, gain) in gains.iter_mut().enumerate() {
+            for went_in in 0..1 << acted {
+                let spot = spot_index(acted, went_in);
+                for (p, v) in charts[spot].iter().zip(&values[spot]) {
+                    *gain += v[0].max(v[1]) - (1.0 - p) * v[0] - p * v[1];
+                }
+            }
+        }
+        let gain = gains.iter().cloned().fold(0.0, f64::max) / total as f64;
+        Ok(Charts { setup: setup.clone(), charts, gain })
+    }
+
+    // the most any one player could win per hand by playing differently, in
+    // big blinds, or in shares of the prize pool for ICM
+    pub fn gain(&self) -> f64 {
+        self.gain
+    }
+
+    // every decision's history, in the order they come up. Histories have
+    // an 'f' for each fold, an 'r' for the first player all in and a 'c' for
+    // each call after that
+    pub fn histories(&self) -> Vec<String> {
+        let players = self.setup.players();
+        let mut histories = Vec::new();
+        for acted in 0..players {
+            for went_in in 0..1usize << acted {
+                if acted == players - 1 && went_in == 0 {
+                    continue;
+                }
+                let first = went_in.trailing_zeros() as usize;
+                histories.push((0..acted)
+                    .map(|i| match went_in >> i & 1 {
+                        0 => 'f',
+                        _ if i == first => 'r',
+                        _ => 'c',
+                    })
+                    .collect());
+            }
+        }
+        histories
+    }
+
+    fn spot(&self, history: &str) -> Option<usize> {
+        let players = self.setup.players();
+        let mut went_in = 0;
+        for (i, action) in history.chars().enumerate() {
+            match (action, went_in != 0) {
+                ('f', _) => {},
+                ('r', false) | ('c', true) => went_in |= 1 << i,
+                _ => return None,
+            }
+        }
+        let acted = history.len();
+        if acted >= players || (acted == players - 1 && went_in == 0) {
+            return None;
+        }
+        Some(spot_index(acted, went_in))
+    }
+
+    // the chance of each hand class going in after `history`, by
+    // HandClass::index
+    pub fn chart(&self, history: &str) -> Option<&[f64]> {
+        self.spot(history).map(|spot| &self.charts[spot][..])
+    }
+
+    pub fn chance(&self, history: &str, class: &HandClass) -> Option<f64> {
+        self.chart(history).map(|chart| chart[class.index()])
+    }
+
+    pub fn position(&self, player: usize) -> &'static str {
+        POSITIONS[self.setup.players() - 1 - player]
+    }
+
+    // what the decision after `history` is, like "SB calling BTN's all in"
+    pub fn title(&self, history: &str) -> String {
+        let player = self.position(history.len());
+        let shover = match history.find('r') {
+            Some(shover) => self.position(shover),
+            None => return format!("{} moving all in when folded to", player),
+        };
+        let callers: Vec<&str> = history.match_indices('c')
+            .map(|(i, _)| self.position(i))
+            .collect();
+        if callers.is_empty() {
+            format!("{} calling {}'s all in", player, shover)
+        } else {
+            format!("{} calling {}'s all in after {} called", player, shover, callers.join(", "))
+        }
+    }
+}
+
+// the value of each action for every decision and hand class
+fn evaluate(setup: &Setup, charts: &[Vec<f64>],
+            deals: &[(Deal, usize)]) -> Vec<Vec<[f64; 2]>> {
+    let mut evaluation = Evaluation::new(setup, charts);
+    for &(ref deal, count) in deals {
+        evaluation.walk(deal, 0, 0, [count as f64; MAX_PLAYERS]);
+    }
+    evaluation.values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(name: &str) -> HandClass {
+        name.parse().unwrap()
+    }
+
+    // the share of hands that go in after `history`, weighted by combos
+    fn range(charts: &Charts, history: &str) -> f64 {
+        let chart = charts.chart(history).unwrap();
+        HandClass::all().iter().map(|c| chart[c.index()] * c.combos() as f64).sum::<f64>() / 1326.0
+    }
+
+    #[test]
+    fn heads_up_ranges_fit_the_stacks() {
+        let deep = Charts::solve(&Setup::new(2, 20.0), 5000, 50, 1).unwrap();
+        for history in &["", "r"] {
+            assert!(deep.chance(history, &class("AA")).unwrap() > 0.95);
+            assert!(deep.chance(history, &class("72o")).unwrap() < 0.05);
+        }
+        // calling an all in needs a better hand than moving in
+        assert!(range(&deep, "r") < range(&deep, ""));
+        // and short stacks move in with far more
+        let short = Charts::solve(&Setup::new(2, 4.0), 5000, 50, 1).unwrap();
+        assert!(range(&short, "") > range(&deep, "") + 0.2);
+        assert!(deep.gain() < 0.05, "gain {}", deep.gain());
+    }
+
+    #[test]
+    fn bad_setups_are_invalid() {
+        let setups = [
+            Setup::new(1, 10.0),
+            Setup::new(2, 10.0).stacks(vec![10.0, 0.0]),
+            Setup::new(3, 10.0).ante(-0.1),
+            Setup::new(3, 10.0).icm(vec![]),
+        ];
+        for setup in &setups {
+            match Charts::solve(setup, 10, 1, 1) {
+                Err(GameError::InvalidSetup(ref reason)) => assert!(reason.contains("push/fold")),
+                other => panic!("solved {:?}", other.map(|c| c.setup)),
+            }
+        }
+    }
+}
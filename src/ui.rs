@@ -1,17 +1,221 @@
-use std::io::{self, Write};
+use poker::game::player::{Player, Command};
+use poker::game::table::{Table, display_cards};
+use poker::game::event::{Event, TableObserver, View};
+use poker::game::betting::LegalActions;
+use poker::game::cash::CashGame;
+use poker::game::dealer::Dealer;
+use poker::game::clock::Deadline;
+use poker::game::error::GameError;
+use poker::game::strategy::{Strategy, Observation};
+use poker::bot::rules::RuleBot;
+use poker::bot::monte_carlo::MonteCarloBot;
+use poker::bot::profile::Profile;
+use poker::bot::solved::SolvedBot;
+use poker::bot::push_fold::{ChartBook, PushFoldBot};
+use poker::bot::chart::{ChartBot, PreflopChart};
+use poker::bot::external::ExternalBot;
+use poker::bot::hand_class::HandClass;
+use poker::solver::SolvedStrategy;
+use poker::history::HandHistory;
+use poker::history::replay::Replay;
+use poker::history::stats::StatsTracker;
+use poker::ladder::Ladder;
 
-use game::player::{Player, Command};
-use game::table::Table;
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
 
-pub fn game_setup(table: &mut Table) {
+// asks the human at the terminal what to do, showing the stats of the
+// players at the table when asked
+pub struct TerminalStrategy {
+    stats: Rc<RefCell<StatsTracker>>,
+    quit: bool,
+}
+
+impl TerminalStrategy {
+    pub fn new(stats: Rc<RefCell<StatsTracker>>) -> TerminalStrategy {
+        TerminalStrategy { stats, quit: false }
+    }
+}
+
+impl Strategy for TerminalStrategy {
+    fn act(&mut self, observation: &Observation) -> Command {
+        let names: Vec<&str> = observation.table.seats.iter()
+            .map(|seat| seat.name.as_str())
+            .filter(|&name| name != observation.name())
+            .collect();
+        let deadline = observation.deadline.as_ref();
+        match get_player_action(&observation.legal, &self.stats, &names, deadline) {
+            Some(cmd) => cmd,
+            None => {
+                self.quit = true;
+                Command::Fold // never played
+            },
+        }
+    }
+
+    // the human decides on rebuys between hands
+    fn rebuy(&mut self, _cash: &CashGame) -> Option<i32> {
+        None
+    }
+
+    fn has_quit(&self) -> bool {
+        self.quit
+    }
+}
+
+// what a computer player picked with --bots plays: a profile, the Monte
+// Carlo bot for "montecarlo", or a limit hold'em strategy saved by the
+// solver for "solved:<file>"
+#[derive(Debug, Clone)]
+pub enum BotChoice {
+    Profile(Profile),
+    MonteCarlo,
+    Solved(String, SolvedStrategy),
+}
+
+impl BotChoice {
+    pub fn name(&self) -> &str {
+        match *self {
+            BotChoice::Profile(ref profile) => &profile.name,
+            BotChoice::MonteCarlo => "Monte Carlo",
+            BotChoice::Solved(ref path, _) => path,
+        }
+    }
+}
+
+impl FromStr for BotChoice {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<BotChoice, GameError> {
+        match s {
+            "montecarlo" => Ok(BotChoice::MonteCarlo),
+            solved if solved.starts_with("solved:") => {
+                let path = &solved[7..];
+                let file = File::open(path)
+                    .map_err(|err| GameError::InvalidStrategy(format!("{}: {}", path, err)))?;
+                let strategy = SolvedStrategy::load(BufReader::new(file))?;
+                SolvedBot::new(strategy.clone(), 0)?;
+                Ok(BotChoice::Solved(path.to_string(), strategy))
+            },
+            profile => profile.parse().map(BotChoice::Profile),
+        }
+    }
+}
+
+// how the computer players play: each external bot, then each choice in
+// turn, then all-rounders. All but the external bots play preflop from the
+// chart, if there is one, and push or fold when short, and the rule-based
+// ones share the stats everyone sees with the human
+pub struct Lineup {
+    pub bots: Vec<BotChoice>,
+    pub stats: Rc<RefCell<StatsTracker>>,
+    external: Vec<String>,
+    chart: Option<Rc<PreflopChart>>,
+    book: Rc<RefCell<ChartBook>>,
+}
+
+impl Lineup {
+    pub fn new(bots: Vec<BotChoice>, chart: Option<PreflopChart>,
+               stats: Rc<RefCell<StatsTracker>>) -> Lineup {
+        Lineup {
+            bots,
+            stats,
+            external: Vec::new(),
+            chart: chart.map(Rc::new),
+            book: Rc::new(RefCell::new(ChartBook::new())),
+        }
+    }
+
+    // the commands that run external bots
+    pub fn external(mut self, commands: Vec<String>) -> Lineup {
+        self.external = commands;
+        self
+    }
+
+    // how many computer players have been asked for
+    pub fn len(&self) -> usize {
+        self.external.len() + self.bots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // who plays computer player `i`, counting from 0, if not an all-rounder
+    pub fn describe(&self, i: usize) -> Option<String> {
+        match self.external.get(i) {
+            Some(command) => Some(format!("runs {}", command)),
+            None => {
+                self.bots.get(i - self.external.len()).map(|bot| format!("plays {}", bot.name()))
+            },
+        }
+    }
+
+    // the strategy of the computer player `i`, counting from 0. An external
+    // bot that can't be run is replaced by an all-rounder
+    pub fn bot(&self, i: usize, seed: u64) -> Box<dyn Strategy> {
+        if let Some(command) = self.external.get(i) {
+            match ExternalBot::launch(command) {
+                Ok(bot) => return Box::new(bot),
+                Err(err) => println!("{}", err),
+            }
+        }
+        let choice = i.checked_sub(self.external.len()).and_then(|i| self.bots.get(i));
+        let mut bot: Box<dyn Strategy> = match choice {
+            Some(BotChoice::Profile(profile)) => {
+                Box::new(profile.bot(seed).stats(self.stats.clone()))
+            },
+            Some(BotChoice::MonteCarlo) => Box::new(MonteCarloBot::new(seed)),
+            Some(BotChoice::Solved(_, strategy)) => {
+                // checked when it was picked
+                Box::new(SolvedBot::new(strategy.clone(), seed).expect("a limit strategy"))
+            },
+            None => Box::new(RuleBot::new(seed).stats(self.stats.clone())),
+        };
+        if let Some(ref chart) = self.chart {
+            bot = Box::new(ChartBot::new(chart.clone(), bot, seed));
+        }
+        Box::new(PushFoldBot::new(bot, self.book.clone(), seed))
+    }
+}
+
+// returns the name of the human player, or none if they leave before
+// sitting down. Each external bot and bot picked gets a computer player,
+// otherwise four all-rounders are seated
+pub fn game_setup(dealer: &mut Dealer, lineup: &Lineup) -> Option<String> {
     println!("\nWelcome to Rust-Poker!");
     println!("======================\n");
     
     // TO DELETE
     let player_name = "Santi".to_string();
-    let num_players = 5;
-    let human_player = Player::new(player_name, true);
-    table.add_player(human_player);
+    let num_players = if lineup.is_empty() { 5 } else { lineup.len().min(8) + 1 };
+    if let Some(cash) = dealer.table().cash_game().cloned() {
+        loop {
+            let answer = terminal_request(&format!("How many chips would you like to buy \
+                                                    in for (between {} and {})?",
+                                                   cash.min_buy_in, cash.max_buy_in));
+            if answer.eq_ignore_ascii_case("leave") {
+                return None;
+            }
+            let amount = answer.parse().unwrap_or(-1);
+            let strategy = Box::new(TerminalStrategy::new(lineup.stats.clone()));
+            let human_player = Player::new(player_name.clone(), true);
+            match dealer.buy_in(human_player, amount, strategy) {
+                Ok(()) => break,
+                Err(err) => println!("{}!", err),
+            }
+        }
+    } else {
+        let human_player = Player::new(player_name.clone(), true);
+        dealer.seat(human_player, Box::new(TerminalStrategy::new(lineup.stats.clone())));
+    }
 
     // let mut player_name = terminal_request("What's your name?");
     // let human_player = Player::new(player_name, true);
@@ -36,83 +240,362 @@ pub fn game_setup(table: &mut Table) {
     
     for i in 1..num_players {
         let cpu_player = Player::new(format!("CPU_{}", i), false);
-        table.add_player(cpu_player);
+        let seed = dealer.table().seed().wrapping_add(i as u64);
+        if let Some(description) = lineup.describe(i - 1) {
+            println!("{} {}", cpu_player.name, description);
+        }
+        let bot = lineup.bot(i - 1, seed);
+        match dealer.table().cash_game().map(|cash| cash.max_buy_in) {
+            Some(amount) => {
+                if let Err(err) = dealer.buy_in(cpu_player, amount, bot) {
+                    println!("CPU_{} can't sit down: {}", i, err);
+                }
+            },
+            None => dealer.seat(cpu_player, bot),
+        }
+    }
+    Some(player_name)
+}
+
+// lets the human manage their seat in a cash game before the next hand.
+// Returns false if they quit
+pub fn between_hands(table: &mut Table, name: &str) -> bool {
+    loop {
+        let (chips, sitting_out) = match table.find_player(name) {
+            Some(player) => (player.chips, player.sitting_out),
+            None => return true,
+        };
+        let cash = table.cash_game().cloned().unwrap_or_default();
+
+        let result = if chips <= 0 {
+            let request = "You're out of chips! Would you like to <rebuy>, <leave>, <quit>?";
+            match terminal_request(request).to_lowercase().as_str() {
+                "quit" => return false,
+                "rebuy" => {
+                    let amount = request_amount(&format!("How many chips (between {} and {})?",
+                                                         cash.min_buy_in, cash.max_buy_in));
+                    table.rebuy(name, amount)
+                },
+                "leave" => table.leave(name).map(|_| ()),
+                _ => {
+                    println!("Invalid command!");
+                    continue;
+                },
+            }.map_err(|e| e.to_string())
+        } else {
+            let seat = if sitting_out { "sit in" } else { "sit out" };
+            let request = format!("You have {} chips. Would you like to <deal>, <top up>, \
+                                   <{}>, <leave>, <quit>?", chips, seat);
+            match terminal_request(&request).to_lowercase().as_str() {
+                "deal" => return true,
+                "quit" => return false,
+                "top up" => {
+                    let amount = request_amount(&format!("How many chips (up to {})?",
+                                                         cash.max_top_up(chips)));
+                    table.top_up(name, amount)
+                },
+                "sit out" if !sitting_out => table.sit_out(name),
+                "sit in" if sitting_out => table.sit_in(name),
+                "leave" => table.leave(name).map(|_| ()),
+                _ => {
+                    println!("Invalid command!");
+                    continue;
+                },
+            }.map_err(|e| e.to_string())
+        };
+        if let Err(err) = result {
+            println!("{}!", err);
+        }
+    }
+}
+
+// prints events for `hero`, or for a spectator watching every hand
+pub struct TerminalObserver {
+    hero: Option<String>,
+}
+
+impl TerminalObserver {
+    pub fn new(hero: Option<&str>) -> TerminalObserver {
+        TerminalObserver { hero: hero.map(|name| name.to_string()) }
     }
 }
 
-pub fn get_player_action(options: Vec<Command>) -> Command {
-    let mut request = String::from("Would you like to");
-    let mut max = None;
-    for option in &options {
-        request = request + &format!(" <{}>,", option);
-        if let &Command::Raise(x) = option {
-            max = Some(x);
+impl TableObserver for TerminalObserver {
+    fn notify(&mut self, event: &Event) {
+        match *event {
+            Event::HandStarted { hand_number, ref button, .. } => {
+                println!("\n--- Hand #{} ({} has the button) ---", hand_number, button);
+            },
+            Event::BlindPosted { ref name, amount } => {
+                println!("{} posts a blind of {}", name, amount);
+            },
+            Event::DeadBlindPosted { ref name, amount } => {
+                println!("{} posts a missed blind of {}", name, amount);
+            },
+            Event::HoleCards { ref name, cards: (ref c1, ref c2) } => {
+                if self.hero.as_ref() == Some(name) {
+                    println!("Here are your cards: [{}, {}]", c1, c2);
+                } else {
+                    println!("{} is dealt [{}, {}]", name, c1, c2);
+                }
+            },
+            Event::TimedOut { ref name } => println!("{} ran out of time", name),
+            Event::Action { ref name, ref command, amount } => match *command {
+                Command::Raise(x) => println!("{} raises {} (puts in {})", name, x, amount),
+                Command::Call => println!("{} calls {}", name, amount),
+                ref cmd => println!("{}: {}", name, cmd),
+            },
+            Event::StreetDealt { ref cards, .. } => {
+                println!("community cards: {}", display_cards(cards));
+            },
+            Event::UncalledBet { ref name, amount } => {
+                println!("{} takes back an uncalled {}", name, amount);
+            },
+            Event::Showdown { ref name, ref hand } => print!("{} shows {}", name, hand),
+            Event::PotAwarded { ref name, amount, pot: 0 } => println!("{} wins {}", name, amount),
+            Event::PotAwarded { ref name, amount, pot } => {
+                println!("{} wins {} from side pot {}", name, amount, pot);
+            },
+            Event::Rake { amount, total } => println!("Rake: {} (total {})", amount, total),
+            Event::HandFinished { .. } => {},
+            Event::Rebuy { ref name, amount } => println!("{} rebuys for {} chips", name, amount),
+            Event::PlayerLeft { ref name, chips } => {
+                println!("{} leaves the table with {} chips", name, chips);
+            },
         }
     }
-    request.pop(); // remove final comma
-    request = request + "?";
+}
 
-    let mut parsed = Err(());
-    while parsed.is_err() {
-        let action = terminal_request(&request);
-        parsed = parse_command(action.to_lowercase());
-        
-        if parsed.is_ok() {
-            if let &Command::Raise(x) = parsed.as_ref().unwrap() {
-                if max.is_some() && x > max.unwrap() { 
-                    parsed = Err(()); // bet more than possible
+// reminds a resuming player where the hand stands
+pub fn show_hand(table: &Table, name: &str) {
+    println!("\n--- Resuming hand #{} ---", table.hand_number());
+    if let Some((c1, c2)) = table.find_player(name).and_then(|p| p.cards.clone()) {
+        println!("Here are your cards: [{}, {}]", c1, c2);
+    }
+    if !table.community_cards().is_empty() {
+        println!("community cards: {}", display_cards(table.community_cards()));
+    }
+    println!("The pot is {}", table.pot());
+}
+
+// a 13 by 13 grid of the hand classes, showing the ones played at least
+// half the time
+pub fn print_chart(title: &str, chart: &[f64]) {
+    println!("\n{}", title);
+    for row in 0..13 {
+        let cells: Vec<String> = (0..13)
+            .map(|column| {
+                let class = HandClass::at(row, column);
+                if chart[class.index()] >= 0.5 {
+                    format!("{:<4}", class.to_string())
+                } else {
+                    " .  ".to_string()
                 }
-            } else if !options.contains(parsed.as_ref().unwrap()) {
-                parsed = Err(());
-                print!("Not an option. ");
-            }
+            })
+            .collect();
+        println!("{}", cells.join(""));
+    }
+    let combos: f64 = HandClass::all().iter()
+        .map(|class| chart[class.index()] * class.combos() as f64)
+        .sum();
+    println!("{:.1}% of hands", 100.0 * combos / 1326.0);
+}
+
+// one line per player of their percentages, and bets and raises per call,
+// with dashes where they haven't had the chance yet
+pub fn print_stats(stats: &StatsTracker, names: &[&str]) {
+    println!("{:<16}{:>7}{:>6}{:>6}{:>6}{:>6}{:>6}{:>6}{:>6}",
+             "Player", "Hands", "VPIP", "PFR", "3Bet", "AF", "FtCB", "WTSD", "W$SD");
+    let percent = |ratio: Option<f64>| {
+        ratio.map_or("-".to_string(), |r| format!("{:.0}", 100.0 * r))
+    };
+    for &name in names {
+        let player = match stats.player(name) {
+            Some(player) => player,
+            None => {
+                println!("{:<16}{:>7}", name, 0);
+                continue;
+            },
+        };
+        println!("{:<16}{:>7}{:>6}{:>6}{:>6}{:>6}{:>6}{:>6}{:>6}", name, player.hands,
+                 percent(player.vpip()), percent(player.pfr()), percent(player.three_bet()),
+                 player.aggression().map_or("-".to_string(), |af| format!("{:.1}", af)),
+                 percent(player.fold_to_cbet()), percent(player.wtsd()),
+                 percent(player.won_at_showdown()));
+    }
+}
+
+pub fn print_leaderboard(ladder: &Ladder) {
+    println!("{:>4}  {:<16}{:>7}{:>7}{:>6}", "Rank", "Player", "Rating", "Games", "Wins");
+    for (i, (name, rating)) in ladder.leaderboard().into_iter().enumerate() {
+        println!("{:>4}  {:<16}{:>7.0}{:>7}{:>6}", i + 1, name, rating.rating, rating.games,
+                 rating.wins);
+    }
+}
+
+pub fn replay_hand(hand: HandHistory) {
+    let mut replay = Replay::new(hand);
+    replay.add_observer(View::Omniscient, Rc::new(RefCell::new(TerminalObserver::new(None))));
+    while !replay.is_finished() {
+        replay.play_street();
+        if !replay.is_finished() {
+            terminal_request("Press enter for the next street");
         }
-        
-        if parsed.is_err() {
-            println!("Invalid command!");
+    }
+    if replay.issues().is_empty() {
+        println!("The hand follows the rules");
+    }
+    for issue in replay.issues() {
+        println!("Inconsistency: {}", issue);
+    }
+}
+
+fn request_amount(request: &str) -> i32 {
+    terminal_request(request).parse().unwrap_or(-1)
+}
+
+// the game is saved before every decision, so quitting (none) loses nothing.
+// `names` are the players whose stats are shown when asked. Checks or folds
+// if nothing is chosen by `deadline`
+pub fn get_player_action(legal: &LegalActions, stats: &RefCell<StatsTracker>,
+                         names: &[&str], deadline: Option<&Deadline>) -> Option<Command> {
+    let mut options = Vec::new();
+    if legal.can_check {
+        options.push("<Check>".to_string());
+    }
+    if legal.can_call {
+        options.push(format!("<Call {}>", legal.call_amount));
+    }
+    if let Some((min, max)) = legal.raise_to {
+        let verb = if legal.is_bet() { "Bet" } else { "Raise to" };
+        if min == max {
+            options.push(format!("<{} {}>", verb, min));
+        } else {
+            options.push(format!("<{} _ ({} to {})>", verb, min, max));
+        }
+    }
+    options.push("<Fold>".to_string());
+    options.push("<Leave>".to_string());
+    options.push("<Stats>".to_string());
+    options.push("<Quit>".to_string());
+    let request = format!("Would you like to {}?", options.join(", "));
+
+    loop {
+        let request = match deadline {
+            Some(deadline) => {
+                let left = deadline.left().as_secs_f64().ceil();
+                format!("{} ({:.0}s left)", request, left)
+            },
+            None => request.clone(),
+        };
+        let action = match terminal_request_by(&request, deadline) {
+            Some(action) => action,
+            None => return Some(legal.check_or_fold()),
+        };
+        if action.eq_ignore_ascii_case("quit") {
+            return None;
+        }
+        if action.eq_ignore_ascii_case("stats") {
+            print_stats(&stats.borrow(), names);
+            continue;
         }
+        match parse_command(action.to_lowercase(), legal) {
+            Ok(ref cmd) if legal.allows(cmd) => return Some(cmd.clone()),
+            Ok(_) => print!("Not an option. "),
+            Err(()) => {},
+        }
+        println!("Invalid command!");
     }
-    parsed.unwrap()
 }
 
-fn parse_command(str: String) -> Result<Command, ()> {
+// bets and raises are typed as the amount to raise to
+fn parse_command(str: String, legal: &LegalActions) -> Result<Command, ()> {
     match str.as_str() {
-        "post blind" => Ok(Command::PostBlind),
         "fold" => Ok(Command::Fold),
         "check" => Ok(Command::Check),
         "call" => Ok(Command::Call),
         "leave" => Ok(Command::Leave),
+        "bet" | "raise" => match legal.raise_to {
+            Some((min, max)) if min == max => Ok(Command::Raise(min - legal.current_bet)),
+            _ => Err(()),
+        },
         action => {
-            if action.len() < 7 {
-                return Err(())
-            }
-            let (action, num) = action.split_at(6);
-            if action != "raise " {
-                return Err(())
-            }
+            let num = ["raise to ", "raise ", "bet "].iter()
+                .find(|prefix| action.starts_with(*prefix))
+                .map(|prefix| &action[prefix.len()..])
+                .ok_or(())?;
             let x : i32 = num.parse().unwrap_or(-1);
-            if x <= 0 {
+            if x <= legal.current_bet {
                 return Err(())
             }
-            Ok(Command::Raise(x))
+            Ok(Command::Raise(x - legal.current_bet))
         }
     }
 }
 
 fn terminal_request(request : &str) -> String {
+    terminal_request_by(request, None).unwrap_or_default()
+}
+
+// how long to wait for a line before looking at the clock again
+const INPUT_POLL: Duration = Duration::from_millis(100);
+
+// the answer typed, or none if it isn't in by `deadline`
+fn terminal_request_by(request: &str, deadline: Option<&Deadline>) -> Option<String> {
     println!("{}", request);
     print!("> ");
-    loop {
-        io::stdout().flush().unwrap();
-        let mut buf = String::new();
-        match io::stdin().read_line(&mut buf) {
-            Ok(_) => {
-                return buf.trim().to_string();
+    io::stdout().flush().unwrap();
+    let mut input = terminal_input().lock().unwrap();
+    // whatever was typed since the last answer timed out was meant for it
+    if input.late {
+        while input.lines.try_recv().is_ok() {}
+        input.late = false;
+    }
+    let line = match deadline {
+        // the deadline is by the dealer's clock, so it's checked again after
+        // every short wait rather than turned into one long one
+        Some(deadline) => loop {
+            if deadline.passed() {
+                println!();
+                input.late = true;
+                return None;
             }
-            _ => {}
-        }
-        println!("Invalid input!");
-        println!("Again, {}", request);
-        print!("> ");
+            match input.lines.recv_timeout(deadline.left().min(INPUT_POLL)) {
+                Err(RecvTimeoutError::Timeout) => {},
+                line => break line.ok(),
+            }
+        },
+        None => input.lines.recv().ok(),
+    };
+    match line {
+        Some(line) => Some(line.trim().to_string()),
+        // once the input has ended the player leaves the table
+        None => {
+            println!("leave");
+            Some("leave".to_string())
+        },
     }
 }
+
+// the lines typed at the terminal, read on a thread of their own so that
+// waiting for one can be given up on
+struct TerminalInput {
+    lines: Receiver<String>,
+    late: bool, // an answer timed out, so anything typed since is late
+}
+
+fn terminal_input() -> &'static Mutex<TerminalInput> {
+    static INPUT: OnceLock<Mutex<TerminalInput>> = OnceLock::new();
+    INPUT.get_or_init(|| {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            let stdin = io::stdin();
+            for line in stdin.lock().lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Mutex::new(TerminalInput { lines, late: false })
+    })
+}
@@ -0,0 +1,85 @@
+extern crate poker;
+
+use poker::bot::external::ExternalBot;
+use poker::game::dealer::Dealer;
+use poker::game::event::{Event, EventLog, View};
+use poker::game::player::{Command, Player};
+use poker::game::strategy::{Observation, Strategy};
+use poker::game::table::Table;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+// this package's own program, which answers as echo-bot
+fn echo_bot() -> String {
+    format!("{} echo-bot", env!("CARGO_BIN_EXE_src"))
+}
+
+// the first turn of a hand between two players
+fn observation() -> Observation {
+    let mut table = Table::build_table();
+    table.set_seed(1);
+    for name in &["a", "b"] {
+        table.add_player(Player::with_chips(name.to_string(), false, 1000));
+    }
+    table.start_hand().unwrap();
+    table.observation(table.to_act().unwrap()).unwrap()
+}
+
+// what a bot falls back on without an answer
+fn check_or_fold(obs: &Observation) -> Command {
+    if obs.legal.can_check { Command::Check } else { Command::Fold }
+}
+
+#[test]
+fn echo_bots_play_a_hand() {
+    let mut dealer = Dealer::new(Table::build_table());
+    for name in &["a", "b"] {
+        let bot = ExternalBot::launch(&echo_bot()).unwrap();
+        dealer.seat(Player::with_chips(name.to_string(), false, 1000), Box::new(bot));
+    }
+    let log = Rc::new(RefCell::new(EventLog::default()));
+    dealer.table_mut().add_observer(View::Omniscient, log.clone());
+    dealer.play_hand().unwrap();
+
+    let events = &log.borrow().events;
+    let commands: Vec<Command> = events.iter()
+        .filter_map(|event| match *event {
+            Event::Action { ref command, .. } => Some(command.clone()),
+            _ => None,
+        })
+        .collect();
+    // every street is checked or called down to a showdown
+    assert!(commands.iter().all(|c| *c == Command::Check || *c == Command::Call));
+    assert!(events.iter().any(|event| matches!(*event, Event::Showdown { .. })));
+}
+
+#[test]
+fn a_bot_that_never_answers_is_timed_out() {
+    let obs = observation();
+    let mut bot = ExternalBot::launch("sleep 5").unwrap()
+        .timeout(Duration::from_millis(100));
+    let started = Instant::now();
+    assert_eq!(bot.act(&obs), check_or_fold(&obs));
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert!(bot.is_connected());
+}
+
+#[test]
+fn a_bot_that_exits_checks_or_folds() {
+    let obs = observation();
+    let mut bot = ExternalBot::launch("true").unwrap();
+    assert_eq!(bot.act(&obs), check_or_fold(&obs));
+    assert!(!bot.is_connected());
+    // and keeps doing so without waiting on it
+    let started = Instant::now();
+    assert_eq!(bot.act(&obs), check_or_fold(&obs));
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn a_missing_program_fails_to_launch() {
+    assert!(ExternalBot::launch("no-such-poker-bot").is_err());
+    assert!(ExternalBot::launch("").is_err());
+}